@@ -13,16 +13,40 @@ mod circuits {
         last_updated: u64,
     }
 
+    // Each side's like intensity (0-100), kept beside the session so it is
+    // only ever revealed inside the aggregate match confidence
+    pub struct LikeIntents {
+        user_a_intent: u8,
+        user_b_intent: u8,
+    }
+
     pub struct UserLikeAction {
         user_id: u64,
         target_id: u64,
         like_action: bool,
         timestamp: u64,
+        intent_strength: u8,
     }
+    // Minimum age enforced inside MPC, independent of the public profile age
+    const MIN_MATCHING_AGE: u8 = 18;
+
+    // Interest count at which the interests score saturates; mirrors the
+    // program's MAX_INTERESTS
+    const MAX_INTERESTS: u8 = 10;
+
+    // Intent strengths above this are clamped to it
+    const MAX_INTENT_STRENGTH: u8 = 100;
+
+    // submit_like status when a super like was recorded without completing a
+    // mutual match; a mutual match reports 2 whatever the weight
+    const STATUS_SUPER_LIKED: u8 = 4;
+
     pub struct MatchResult {
         is_mutual_match: bool,
         session_status: u8,
         match_timestamp: u64,
+        confidence: u8,
+        compatibility_score: u8,
     }
 
 
@@ -32,7 +56,7 @@ mod circuits {
         user_a_id: u64,
         user_b_id: u64,
         current_timestamp: u64
-    ) -> Enc<Mxe, MatchSession> {
+    ) -> (Enc<Mxe, MatchSession>, Enc<Mxe, LikeIntents>) {
         let match_session = MatchSession {
             user_a_id,
             user_b_id,
@@ -41,26 +65,65 @@ mod circuits {
             session_created_at: current_timestamp,
             last_updated: current_timestamp,
         };
+        let intents = LikeIntents {
+            user_a_intent: 0,
+            user_b_intent: 0,
+        };
 
-        mxe.from_arcis(match_session)
+        (mxe.from_arcis(match_session), mxe.from_arcis(intents))
     }
 
 
+    // `like_weight` is plaintext and set by the program (1 for submit_like,
+    // 2 for submit_super_like), so a client cannot dodge the super like quota
+    // by encrypting a heavier weight into an ordinary like
     #[instruction]
     pub fn submit_like(
         like_action_ctxt: Enc<Shared, UserLikeAction>,
         match_session_ctxt: Enc<Mxe, MatchSession>,
-    ) -> (Enc<Mxe, MatchSession>, u8) {
+        intents_ctxt: Enc<Mxe, LikeIntents>,
+        like_weight: u8,
+    ) -> (Enc<Mxe, MatchSession>, Enc<Mxe, LikeIntents>, u8) {
         let like_action = like_action_ctxt.to_arcis();
         let mut match_session = match_session_ctxt.to_arcis();
+        let mut intents = intents_ctxt.to_arcis();
+
+        // Intent only counts alongside an actual like; the weight scales it
+        // and is recorded with it, still clamped to MAX_INTENT_STRENGTH
+        let strength = if like_action.intent_strength > MAX_INTENT_STRENGTH {
+            MAX_INTENT_STRENGTH
+        } else {
+            like_action.intent_strength
+        };
+        let weight = if like_weight == 0 { 1u16 } else { like_weight as u16 };
+        let weighted = strength as u16 * weight;
+        let intent = if !like_action.like_action {
+            0
+        } else if weighted > MAX_INTENT_STRENGTH as u16 {
+            MAX_INTENT_STRENGTH
+        } else {
+            weighted as u8
+        };
+        let liked_status = if like_action.like_action && like_weight > 1 {
+            STATUS_SUPER_LIKED
+        } else {
+            1u8
+        };
+
+        // A self-like is rejected like any other invalid action, so the flag
+        // does not reveal which check failed
+        let distinct_ids = like_action.user_id != like_action.target_id;
         
         let mut status_flag = 0u8;
-        if like_action.user_id == match_session.user_a_id && 
+        if !distinct_ids {
+            status_flag = 0;
+        } else if like_action.user_id == match_session.user_a_id && 
            like_action.target_id == match_session.user_b_id &&
            !match_session.user_a_liked {
             match_session.user_a_liked = like_action.like_action;
             match_session.last_updated = like_action.timestamp;
-            status_flag = 1;
+            intents.user_a_intent = intent;
+            status_flag = liked_status;
             if match_session.user_a_liked && match_session.user_b_liked {
                 status_flag = 2;
             }
@@ -70,7 +133,8 @@ mod circuits {
                   !match_session.user_b_liked {
             match_session.user_b_liked = like_action.like_action;
             match_session.last_updated = like_action.timestamp;
-            status_flag = 1;
+            intents.user_b_intent = intent;
+            status_flag = liked_status;
             if match_session.user_a_liked && match_session.user_b_liked {
                 status_flag = 2;
             }
@@ -79,21 +143,116 @@ mod circuits {
             status_flag = 0;
         }
         
-        (match_session_ctxt.owner.from_arcis(match_session), status_flag.reveal())
+        (
+            match_session_ctxt.owner.from_arcis(match_session),
+            intents_ctxt.owner.from_arcis(intents),
+            status_flag.reveal(),
+        )
     }
 
 
+    pub struct WithdrawLikeAction {
+        user_id: u64,
+        timestamp: u64,
+    }
+
+    #[instruction]
+    pub fn withdraw_like(
+        withdraw_action_ctxt: Enc<Shared, WithdrawLikeAction>,
+        match_session_ctxt: Enc<Mxe, MatchSession>,
+        intents_ctxt: Enc<Mxe, LikeIntents>,
+    ) -> (Enc<Mxe, MatchSession>, Enc<Mxe, LikeIntents>, u8) {
+        let withdraw_action = withdraw_action_ctxt.to_arcis();
+        let mut match_session = match_session_ctxt.to_arcis();
+        let mut intents = intents_ctxt.to_arcis();
+
+        // Once both sides liked the mutual interest is already public, so
+        // neither like can be taken back
+        let mutual = match_session.user_a_liked && match_session.user_b_liked;
+
+        // 1 = withdrawn, 0 = nothing to withdraw
+        let mut status_flag = 0u8;
+        if mutual {
+            status_flag = 0;
+        } else if withdraw_action.user_id == match_session.user_a_id && match_session.user_a_liked {
+            match_session.user_a_liked = false;
+            match_session.last_updated = withdraw_action.timestamp;
+            intents.user_a_intent = 0;
+            status_flag = 1;
+        } else if withdraw_action.user_id == match_session.user_b_id && match_session.user_b_liked {
+            match_session.user_b_liked = false;
+            match_session.last_updated = withdraw_action.timestamp;
+            intents.user_b_intent = 0;
+            status_flag = 1;
+        }
+
+        (
+            match_session_ctxt.owner.from_arcis(match_session),
+            intents_ctxt.owner.from_arcis(intents),
+            status_flag.reveal(),
+        )
+    }
+
+
+    pub struct PassAction {
+        user_id: u64,
+        target_id: u64,
+    }
+
+    // submit_pass status when a participant passed for good; 0 means the
+    // pass was not applied. Kept apart from every other circuit's statuses
+    // (3 is check_mutual_match's veto) so no revealed status reads as a pass.
+    const STATUS_PASSED: u8 = 5;
+
+    #[instruction]
+    pub fn submit_pass(
+        pass_action_ctxt: Enc<Shared, PassAction>,
+        match_session_ctxt: Enc<Mxe, MatchSession>,
+    ) -> u8 {
+        let pass_action = pass_action_ctxt.to_arcis();
+        let match_session = match_session_ctxt.to_arcis();
+
+        let is_participant = (pass_action.user_id == match_session.user_a_id &&
+            pass_action.target_id == match_session.user_b_id) ||
+            (pass_action.user_id == match_session.user_b_id &&
+            pass_action.target_id == match_session.user_a_id);
+
+        // A pass cannot undo interest that is already mutual
+        let mutual = match_session.user_a_liked && match_session.user_b_liked;
+
+        let status_flag = if is_participant && !mutual { STATUS_PASSED } else { 0u8 };
+        status_flag.reveal()
+    }
+
+
+    // Session status when both liked but a dealbreaker vetoed the match
+    const STATUS_VETOED: u8 = 3;
+
     #[instruction]
     pub fn check_mutual_match(
         match_session_ctxt: Enc<Mxe, MatchSession>,
+        intents_ctxt: Enc<Mxe, LikeIntents>,
+        user_a_data_ctxt: Enc<Shared, MatchingData>,
+        user_b_data_ctxt: Enc<Shared, MatchingData>,
         current_timestamp: u64
     ) -> MatchResult {
         let match_session = match_session_ctxt.to_arcis();
-        
-        let is_mutual = match_session.user_a_liked && match_session.user_b_liked;
+        let intents = intents_ctxt.to_arcis();
+        let user_a_data = user_a_data_ctxt.to_arcis();
+        let user_b_data = user_b_data_ctxt.to_arcis();
+
+        // Mutual likes never override a hard filter on either side. The hard
+        // filter also holds the age gate, read from each participant's staged
+        // profile rather than anything sent with a like
+        let both_liked = match_session.user_a_liked && match_session.user_b_liked;
+        let vetoed = hard_filter_conflict(&user_a_data.prefs, &user_a_data.profile, &user_b_data.profile) ||
+            hard_filter_conflict(&user_b_data.prefs, &user_b_data.profile, &user_a_data.profile);
+        let is_mutual = both_liked && !vetoed;
         
         let status = if is_mutual {
             1u8
+        } else if both_liked {
+            STATUS_VETOED
         } else if match_session.user_a_liked || match_session.user_b_liked {
             0u8
         } else {
@@ -101,21 +260,68 @@ mod circuits {
         };
         
         let match_timestamp = if is_mutual { current_timestamp } else { 0u64 };
+
+        // Confidence is the mean intent of a confirmed match; neither side's
+        // intent is revealed on its own
+        let mean_intent = (intents.user_a_intent as u16 + intents.user_b_intent as u16) / 2;
+        let confidence = if is_mutual { mean_intent as u8 } else { 0u8 };
+
+        // The pair's core compatibility (no optional bonuses, no reputation),
+        // revealed only for a confirmed match
+        let (pair_points, _, _) = pair_score(
+            &user_a_data.prefs,
+            &user_a_data.profile,
+            &user_b_data.prefs,
+            &user_b_data.profile,
+            0,
+        );
+        let capped_points = if pair_points > 100 { 100u8 } else { pair_points };
+        let compatibility_score = if is_mutual { capped_points } else { 0u8 };
         
         MatchResult {
             is_mutual_match: is_mutual,
             session_status: status,
             match_timestamp,
+            confidence,
+            compatibility_score,
         }.reveal()
     }
 
 
+    #[instruction]
+    pub fn rotate_session_nonce(
+        match_session_ctxt: Enc<Mxe, MatchSession>,
+    ) -> Enc<Mxe, MatchSession> {
+        let match_session = match_session_ctxt.to_arcis();
+
+        // Re-encrypting the unchanged state draws a fresh output nonce
+        match_session_ctxt.owner.from_arcis(match_session)
+    }
+
+
     pub struct UserPreferences {
         preferred_age_min: u8,
         preferred_age_max: u8,
         interests_count: u8,
         location_preference: u8,
         relationship_type: u8,
+        pet_preference: u8,
+        pet_dealbreaker: bool,
+        budget_tier_min: u8,
+        budget_tier_max: u8,
+        relationship_status_preference: u8,
+        religion_preference: u8,
+        diet_preference: u8,
+        diet_dealbreaker: bool,
+        max_age_gap: u8,
+        children_preference: u8,
+        min_youngest_child_bucket: u8,
+        open_to_long_distance: bool,
+        preferred_height_min: u8,
+        preferred_height_max: u8,
+        body_type_preference: u8,
+        preferred_genders: u8,
+        preferred_distance_km: u16,
     }
 
     pub struct UserProfile {
@@ -123,29 +329,401 @@ mod circuits {
         interests_count: u8,
         location_score: u8,
         relationship_type: u8,
+        pet_status: u8,
+        zodiac: u8,
+        budget_tier: u8,
+        personality_type: u8,
+        relationship_status: u8,
+        religion: u8,
+        religion_importance: u8,
+        diet: u8,
+        num_children: u8,
+        youngest_child_age_bucket: u8,
+        height_cm: u8,
+        body_type: u8,
+        gender: u8,
+        grid_x: u16,
+        grid_y: u16,
+    }
+
+    // Pet status codes (UserProfile.pet_status), 0 = no pets
+    const PET_STATUS_OWNER: u8 = 1;
+    const PET_STATUS_ALLERGIC: u8 = 2;
+
+    // Pet preference codes (UserPreferences.pet_preference), 0 = no preference
+    const PET_PREF_LOVES_PETS: u8 = 1;
+    const PET_PREF_NO_PETS: u8 = 2;
+
+    // Distance dealbreaker: location_preference is the minimum pair location
+    // score a user accepts (0 = any). Waived when either side is open to long
+    // distance, which leaves only the soft location points.
+    fn distance_conflict(
+        user_a_prefs: &UserPreferences,
+        user_b_prefs: &UserPreferences,
+        location_score: u16,
+    ) -> bool {
+        let a_too_far = user_a_prefs.location_preference > 0 &&
+            location_score < user_a_prefs.location_preference as u16;
+        let b_too_far = user_b_prefs.location_preference > 0 &&
+            location_score < user_b_prefs.location_preference as u16;
+        let long_distance_ok = user_a_prefs.open_to_long_distance || user_b_prefs.open_to_long_distance;
+        (a_too_far || b_too_far) && !long_distance_ok
+    }
+
+    // Quantized positions are ~1 km grid cells; (0, 0) means no coordinates
+    fn has_coordinates(profile: &UserProfile) -> bool {
+        profile.grid_x != 0 || profile.grid_y != 0
+    }
+
+    // Squared grid distance in u64, so the widest possible gap cannot wrap
+    fn squared_distance(a: &UserProfile, b: &UserProfile) -> u64 {
+        let dx = (if a.grid_x > b.grid_x { a.grid_x - b.grid_x } else { b.grid_x - a.grid_x }) as u64;
+        let dy = (if a.grid_y > b.grid_y { a.grid_y - b.grid_y } else { b.grid_y - a.grid_y }) as u64;
+        dx * dx + dy * dy
+    }
+
+    // Whether a squared distance falls within a radius in km (0 = any distance),
+    // compared against the squared radius so no square root is needed
+    fn within_radius(preferred_distance_km: u16, squared_distance: u64) -> bool {
+        let radius = preferred_distance_km as u64;
+        preferred_distance_km == 0 || squared_distance <= radius * radius
+    }
+
+    // Pair location score (0-25): 25 when the distance is within both users'
+    // preferred radius, 12 when within one. Falls back to the averaged
+    // location_score when either side has no coordinates.
+    fn pair_location_score(
+        user_a_prefs: &UserPreferences,
+        user_a_profile: &UserProfile,
+        user_b_prefs: &UserPreferences,
+        user_b_profile: &UserProfile,
+    ) -> u16 {
+        let averaged = (user_a_profile.location_score as u16 + user_b_profile.location_score as u16) / 2;
+        let distance = squared_distance(user_a_profile, user_b_profile);
+        let a_within = within_radius(user_a_prefs.preferred_distance_km, distance);
+        let b_within = within_radius(user_b_prefs.preferred_distance_km, distance);
+        let banded = if a_within && b_within {
+            25u16
+        } else if a_within || b_within {
+            12u16
+        } else {
+            0u16
+        };
+        if has_coordinates(user_a_profile) && has_coordinates(user_b_profile) { banded } else { averaged }
+    }
+
+    // Whether the age difference is within the chooser's max gap (0 = no limit)
+    fn within_age_gap(max_age_gap: u8, chooser_age: u8, candidate_age: u8) -> bool {
+        let gap = if chooser_age > candidate_age {
+            chooser_age - candidate_age
+        } else {
+            candidate_age - chooser_age
+        };
+        max_age_gap == 0 || gap <= max_age_gap
+    }
+
+    // Categorical codes (relationship status, diet) map to one bit each in the
+    // matching preference mask; an empty mask accepts all
+    const MASK_CODE_COUNT: usize = 8;
+
+    // Whether `code` is in the accepted bitmask, checking every bit so the
+    // secret code never selects a branch
+    fn mask_accepts(accepted_mask: u8, code: u8) -> bool {
+        let mut accepted = accepted_mask == 0;
+        for bit in 0..MASK_CODE_COUNT {
+            if code == bit as u8 && (accepted_mask & (1u8 << bit)) != 0 {
+                accepted = true;
+            }
+        }
+        accepted
+    }
+
+    // Diet points (0-3) when the candidate's diet is explicitly accepted, and
+    // whether a rejected diet is a dealbreaker for the chooser
+    fn diet_fit(chooser_prefs: &UserPreferences, candidate: &UserProfile) -> (u8, bool) {
+        let has_preference = chooser_prefs.diet_preference != 0;
+        let accepted = mask_accepts(chooser_prefs.diet_preference, candidate.diet);
+        let points = if has_preference && accepted { 3 } else { 0 };
+        let dealbreaker = chooser_prefs.diet_dealbreaker && !accepted;
+        (points, dealbreaker)
+    }
+
+    // Religion preference 0 = no preference; importance runs 0 (none) to 3
+    const RELIGION_NO_PREFERENCE: u8 = 0;
+    const RELIGION_IMPORTANCE_DEALBREAKER: u8 = 3;
+
+    // Values points (0-3) scaled by how much the chooser cares, and whether a
+    // mismatch is a dealbreaker for them
+    fn religion_fit(chooser_prefs: &UserPreferences, chooser: &UserProfile, candidate: &UserProfile) -> (u8, bool) {
+        let has_preference = chooser_prefs.religion_preference != RELIGION_NO_PREFERENCE;
+        let matches = candidate.religion == chooser_prefs.religion_preference;
+        let importance = if chooser.religion_importance > RELIGION_IMPORTANCE_DEALBREAKER {
+            RELIGION_IMPORTANCE_DEALBREAKER
+        } else {
+            chooser.religion_importance
+        };
+
+        let points = if has_preference && matches { importance } else { 0 };
+        let dealbreaker = has_preference && !matches && importance == RELIGION_IMPORTANCE_DEALBREAKER;
+        (points, dealbreaker)
+    }
+
+    // Children preference codes (UserPreferences.children_preference), 0 = no preference
+    const CHILDREN_PREF_NONE: u8 = 1;
+    const CHILDREN_PREF_OPEN: u8 = 2;
+
+    // Family points (0-3) when the candidate's children fit the chooser's
+    // preference; a soft signal only, never a dealbreaker
+    fn children_fit(chooser_prefs: &UserPreferences, candidate: &UserProfile) -> u8 {
+        let has_children = candidate.num_children > 0;
+        let prefers_none = chooser_prefs.children_preference == CHILDREN_PREF_NONE && !has_children;
+        let open = chooser_prefs.children_preference == CHILDREN_PREF_OPEN &&
+            (!has_children || candidate.youngest_child_age_bucket >= chooser_prefs.min_youngest_child_bucket);
+        if prefers_none || open { 3 } else { 0 }
+    }
+
+    // Physical points (0-3): 2 when the candidate's height is inside the
+    // chooser's range, 1 when their body type is in the chooser's mask. Unset
+    // preferences (0) earn nothing and never filter.
+    fn physical_fit(chooser_prefs: &UserPreferences, candidate: &UserProfile) -> u8 {
+        let has_height_preference = chooser_prefs.preferred_height_min != 0 || chooser_prefs.preferred_height_max != 0;
+        let above_min = candidate.height_cm >= chooser_prefs.preferred_height_min;
+        let below_max = chooser_prefs.preferred_height_max == 0 || candidate.height_cm <= chooser_prefs.preferred_height_max;
+        let height_points = if has_height_preference && above_min && below_max { 2 } else { 0 };
+
+        let has_body_type_preference = chooser_prefs.body_type_preference != 0;
+        let body_type_points = if has_body_type_preference && mask_accepts(chooser_prefs.body_type_preference, candidate.body_type) {
+            1
+        } else {
+            0
+        };
+        height_points + body_type_points
+    }
+
+    // Whether `candidate` violates any of the chooser's dealbreakers; the same
+    // conditions that zero a compatibility score
+    fn hard_filter_conflict(chooser_prefs: &UserPreferences, chooser: &UserProfile, candidate: &UserProfile) -> bool {
+        let pet_conflict = chooser_prefs.pet_dealbreaker &&
+            chooser.pet_status == PET_STATUS_ALLERGIC &&
+            candidate.pet_status == PET_STATUS_OWNER;
+        let (_, religion_dealbreaker) = religion_fit(chooser_prefs, chooser, candidate);
+        let (_, diet_dealbreaker) = diet_fit(chooser_prefs, candidate);
+
+        pet_conflict ||
+            religion_dealbreaker ||
+            diet_dealbreaker ||
+            candidate.age < MIN_MATCHING_AGE ||
+            !mask_accepts(chooser_prefs.relationship_status_preference, candidate.relationship_status) ||
+            !mask_accepts(chooser_prefs.preferred_genders, candidate.gender)
+    }
+
+    // Whether a dealbreaker rules the pair out: either side's hard filters
+    // against the other, or the distance dealbreaker at the pair's location
+    // score. Shared by pair_score and score_candidate so both veto alike
+    fn pair_dealbreaker(
+        user_a_prefs: &UserPreferences,
+        user_a_profile: &UserProfile,
+        user_b_prefs: &UserPreferences,
+        user_b_profile: &UserProfile,
+        location_score: u16,
+    ) -> bool {
+        hard_filter_conflict(user_a_prefs, user_a_profile, user_b_profile) ||
+            hard_filter_conflict(user_b_prefs, user_b_profile, user_a_profile) ||
+            distance_conflict(user_a_prefs, user_b_prefs, location_score)
+    }
+
+    // A user's own preferences and profile, staged once for the match check
+    pub struct MatchingData {
+        prefs: UserPreferences,
+        profile: UserProfile,
+    }
+
+    // Optional scoring components, toggled per deployment
+    const SCORING_FLAG_ZODIAC: u8 = 1 << 0;
+    const SCORING_FLAG_PERSONALITY: u8 = 1 << 1;
+
+    // Element of each sign (Aries..Pisces): 0 = fire, 1 = earth, 2 = air, 3 = water
+    const ZODIAC_ELEMENTS: [u8; 12] = [0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3];
+
+    // Bonus points for each element pairing (max 3)
+    const ZODIAC_ELEMENT_COMPATIBILITY: [[u8; 4]; 4] = [
+        [3, 0, 2, 0],
+        [0, 3, 0, 2],
+        [2, 0, 3, 0],
+        [0, 2, 0, 3],
+    ];
+
+    // Personality types in order: INTJ, INTP, ENTJ, ENTP, INFJ, INFP, ENFJ, ENFP,
+    // ISTJ, ISFJ, ESTJ, ESFJ, ISTP, ISFP, ESTP, ESFP
+    const PERSONALITY_TYPE_COUNT: usize = 16;
+
+    // Bonus points for each personality pairing (max 5)
+    const PERSONALITY_COMPATIBILITY: [[u8; PERSONALITY_TYPE_COUNT]; PERSONALITY_TYPE_COUNT] = [
+        [3, 4, 4, 5, 2, 3, 3, 4, 1, 0, 2, 1, 2, 1, 3, 2], // INTJ
+        [4, 3, 5, 4, 3, 2, 4, 3, 2, 1, 3, 2, 1, 0, 2, 1], // INTP
+        [4, 5, 3, 4, 3, 4, 2, 3, 2, 1, 1, 0, 3, 2, 2, 1], // ENTJ
+        [5, 4, 4, 3, 4, 3, 3, 2, 3, 2, 2, 1, 2, 1, 1, 0], // ENTP
+        [2, 3, 3, 4, 3, 4, 4, 5, 0, 1, 1, 2, 1, 2, 2, 3], // INFJ
+        [3, 2, 4, 3, 4, 3, 5, 4, 1, 2, 2, 3, 0, 1, 1, 2], // INFP
+        [3, 4, 2, 3, 4, 5, 3, 4, 1, 2, 0, 1, 2, 3, 1, 2], // ENFJ
+        [4, 3, 3, 2, 5, 4, 4, 3, 2, 3, 1, 2, 1, 2, 0, 1], // ENFP
+        [1, 2, 2, 3, 0, 1, 1, 2, 3, 2, 4, 3, 4, 3, 5, 4], // ISTJ
+        [0, 1, 1, 2, 1, 2, 2, 3, 2, 3, 3, 4, 3, 4, 4, 5], // ISFJ
+        [2, 3, 1, 2, 1, 2, 0, 1, 4, 3, 3, 2, 5, 4, 4, 3], // ESTJ
+        [1, 2, 0, 1, 2, 3, 1, 2, 3, 4, 2, 3, 4, 5, 3, 4], // ESFJ
+        [2, 1, 3, 2, 1, 0, 2, 1, 4, 3, 5, 4, 3, 2, 4, 3], // ISTP
+        [1, 0, 2, 1, 2, 1, 3, 2, 3, 4, 4, 5, 2, 3, 3, 4], // ISFP
+        [3, 2, 2, 1, 2, 1, 1, 0, 5, 4, 4, 3, 4, 3, 3, 2], // ESTP
+        [2, 1, 1, 0, 3, 2, 2, 1, 4, 5, 3, 4, 3, 4, 2, 3], // ESFP
+    ];
+
+    // Bonus points for a pair of personality types, scanning the whole matrix
+    // so neither secret index selects a branch
+    fn personality_bonus(type_a: u8, type_b: u8) -> u8 {
+        let mut bonus = 0u8;
+        for i in 0..PERSONALITY_TYPE_COUNT {
+            for j in 0..PERSONALITY_TYPE_COUNT {
+                if type_a == i as u8 && type_b == j as u8 {
+                    bonus = PERSONALITY_COMPATIBILITY[i][j];
+                }
+            }
+        }
+        bonus
+    }
+
+    // Bonus points for a pair of signs, looked up in constant time so the
+    // secret signs never select a branch. A sign outside 0-11 earns nothing.
+    fn zodiac_bonus(zodiac_a: u8, zodiac_b: u8) -> u8 {
+        let mut element_a = 0u8;
+        let mut element_b = 0u8;
+        let mut known_a = false;
+        let mut known_b = false;
+        for sign in 0..12 {
+            if zodiac_a == sign as u8 {
+                element_a = ZODIAC_ELEMENTS[sign];
+                known_a = true;
+            }
+            if zodiac_b == sign as u8 {
+                element_b = ZODIAC_ELEMENTS[sign];
+                known_b = true;
+            }
+        }
+        let mut bonus = 0u8;
+        for i in 0..4 {
+            for j in 0..4 {
+                if element_a == i as u8 && element_b == j as u8 {
+                    bonus = ZODIAC_ELEMENT_COMPATIBILITY[i][j];
+                }
+            }
+        }
+        if known_a && known_b { bonus } else { 0 }
+    }
+
+    // Encrypted aggregate of the star ratings a user has received
+    pub struct Reputation {
+        rating_sum: u32,
+        rating_count: u32,
+    }
+
+    pub struct FeedbackInput {
+        rating: u8,
+    }
+
+    // Ratings needed before reputation moves a score, and the points it moves
+    const REPUTATION_MIN_RATINGS: u32 = 3;
+    const REPUTATION_ADJUSTMENT: u8 = 2;
+
+    // (bonus, penalty) for one user: a 4+ star average earns the bonus, 2 or
+    // fewer the penalty. Compared as sums so no division runs in MPC.
+    fn reputation_adjustment(reputation: &Reputation, has_reputation: bool) -> (u8, u8) {
+        let rated = has_reputation && reputation.rating_count >= REPUTATION_MIN_RATINGS;
+        let mut bonus = 0u8;
+        let mut penalty = 0u8;
+        if rated && reputation.rating_sum >= 4 * reputation.rating_count {
+            bonus = REPUTATION_ADJUSTMENT;
+        }
+        if rated && reputation.rating_sum <= 2 * reputation.rating_count {
+            penalty = REPUTATION_ADJUSTMENT;
+        }
+        (bonus, penalty)
     }
 
     #[instruction]
-    pub fn calculate_compatibility(
-        user_a_prefs_ctxt: Enc<Shared, UserPreferences>,
-        user_b_profile_ctxt: Enc<Shared, UserProfile>,
-        user_b_prefs_ctxt: Enc<Shared, UserPreferences>, 
-        user_a_profile_ctxt: Enc<Shared, UserProfile>,
-    ) -> u8 {
-        let user_a_prefs = user_a_prefs_ctxt.to_arcis();
-        let user_b_profile = user_b_profile_ctxt.to_arcis();
-        let user_b_prefs = user_b_prefs_ctxt.to_arcis();
-        let user_a_profile = user_a_profile_ctxt.to_arcis();
-        
+    pub fn submit_feedback(
+        feedback_ctxt: Enc<Shared, FeedbackInput>,
+        reputation_ctxt: Enc<Mxe, Reputation>,
+        is_first_rating: bool,
+    ) -> (Enc<Mxe, Reputation>, bool) {
+        let feedback = feedback_ctxt.to_arcis();
+        let stored = reputation_ctxt.to_arcis();
+
+        // A fresh account holds no ciphertext yet, so start from zero
+        let mut reputation = Reputation { rating_sum: 0, rating_count: 0 };
+        if !is_first_rating {
+            reputation = stored;
+        }
+
+        // Out-of-range ratings leave the aggregate untouched
+        let accepted = feedback.rating >= 1 && feedback.rating <= 5;
+        if accepted {
+            reputation.rating_sum += feedback.rating as u32;
+            reputation.rating_count += 1;
+        }
+
+        (reputation_ctxt.owner.from_arcis(reputation), accepted.reveal())
+    }
+
+    // Per-factor sub-scores behind a compatibility score, so clients can say
+    // which factors a pair matched well on without seeing the raw attributes
+    pub struct ScoreBreakdown {
+        age: u8,
+        interests: u8,
+        location: u8,
+        relationship: u8,
+        physical: u8,
+    }
+
+    // Breakdown from the sub-scores in field order, all zero unless the pair is viable
+    fn score_breakdown(viable: bool, sub_scores: [u8; 5]) -> ScoreBreakdown {
+        let mut breakdown = ScoreBreakdown { age: 0, interests: 0, location: 0, relationship: 0, physical: 0 };
+        if viable {
+            breakdown.age = sub_scores[0];
+            breakdown.interests = sub_scores[1];
+            breakdown.location = sub_scores[2];
+            breakdown.relationship = sub_scores[3];
+            breakdown.physical = sub_scores[4];
+        }
+        breakdown
+    }
+
+    // Two-sided score before reputation and the 100 cap, the age gate, and the
+    // sub-scores in `ScoreBreakdown` order; shared by calculate_compatibility
+    // and check_mutual_match
+    fn pair_score(
+        user_a_prefs: &UserPreferences,
+        user_a_profile: &UserProfile,
+        user_b_prefs: &UserPreferences,
+        user_b_profile: &UserProfile,
+        scoring_flags: u8,
+    ) -> (u8, bool, [u8; 5]) {
+        // Hard age gate on the encrypted ages; only the pass/fail bit is revealed
+        let age_gate_passed = user_a_profile.age >= MIN_MATCHING_AGE &&
+            user_b_profile.age >= MIN_MATCHING_AGE;
+
         let mut compatibility_score = 0u8;
         
-        // Age compatibility (0-30 points)
-        if user_b_profile.age >= user_a_prefs.preferred_age_min && 
+        // Age compatibility (0-30 points): both ranges and both max gaps must hold
+        let age_points = if user_b_profile.age >= user_a_prefs.preferred_age_min && 
            user_b_profile.age <= user_a_prefs.preferred_age_max &&
            user_a_profile.age >= user_b_prefs.preferred_age_min &&
-           user_a_profile.age <= user_b_prefs.preferred_age_max {
-            compatibility_score += 30;
-        }
+           user_a_profile.age <= user_b_prefs.preferred_age_max &&
+           within_age_gap(user_a_prefs.max_age_gap, user_a_profile.age, user_b_profile.age) &&
+           within_age_gap(user_b_prefs.max_age_gap, user_b_profile.age, user_a_profile.age) {
+            30u8
+        } else {
+            0u8
+        };
+        compatibility_score += age_points;
         
         // Interests compatibility (0-25 points)
         let interests_score = if user_a_profile.interests_count > 0 && user_b_profile.interests_count > 0 {
@@ -155,22 +733,351 @@ mod circuits {
             } else {
                 user_b_profile.interests_count
             };
-            (min_interests * 25) / 10 // Scale to max 25 points
+            let min_interests = if min_interests > MAX_INTERESTS { MAX_INTERESTS } else { min_interests };
+            // Scale in u16 and cap before narrowing, so no count can wrap the score
+            let scaled = (min_interests as u16 * 25) / MAX_INTERESTS as u16; // Scale to max 25 points
+            if scaled > 25 { 25u8 } else { scaled as u8 }
         } else {
             0
         };
-        compatibility_score += if interests_score > 25 { 25 } else { interests_score };
+        compatibility_score += interests_score;
         
-        // Location compatibility (0-25 points)
-        let location_score = (user_a_profile.location_score + user_b_profile.location_score) / 2;
-        compatibility_score += if location_score > 25 { 25 } else { location_score };
+        // Location compatibility (0-25 points), banded by distance when both
+        // users have coordinates, otherwise averaged in u16 so it cannot wrap
+        let location_score = pair_location_score(user_a_prefs, user_a_profile, user_b_prefs, user_b_profile);
+        let location_points = if location_score > 25 { 25u8 } else { location_score as u8 };
+        compatibility_score += location_points;
         
         // Relationship type compatibility (0-20 points)
-        if user_a_profile.relationship_type == user_b_profile.relationship_type {
-            compatibility_score += 20;
+        let relationship_points = if user_a_profile.relationship_type == user_b_profile.relationship_type { 20u8 } else { 0u8 };
+        compatibility_score += relationship_points;
+
+        // Pet compatibility (0-5 bonus points)
+        let a_pets_ok = (user_a_prefs.pet_preference == PET_PREF_LOVES_PETS && user_b_profile.pet_status == PET_STATUS_OWNER) ||
+            (user_a_prefs.pet_preference == PET_PREF_NO_PETS && user_b_profile.pet_status != PET_STATUS_OWNER);
+        let b_pets_ok = (user_b_prefs.pet_preference == PET_PREF_LOVES_PETS && user_a_profile.pet_status == PET_STATUS_OWNER) ||
+            (user_b_prefs.pet_preference == PET_PREF_NO_PETS && user_a_profile.pet_status != PET_STATUS_OWNER);
+        if a_pets_ok && b_pets_ok {
+            compatibility_score += 5;
+        } else if a_pets_ok || b_pets_ok {
+            compatibility_score += 2;
+        }
+
+        // Budget compatibility for date planning (0-5 bonus points)
+        let a_budget_ok = user_b_profile.budget_tier >= user_a_prefs.budget_tier_min &&
+            user_b_profile.budget_tier <= user_a_prefs.budget_tier_max;
+        let b_budget_ok = user_a_profile.budget_tier >= user_b_prefs.budget_tier_min &&
+            user_a_profile.budget_tier <= user_b_prefs.budget_tier_max;
+        if a_budget_ok && b_budget_ok {
+            compatibility_score += 5;
+        } else if a_budget_ok || b_budget_ok {
+            compatibility_score += 2;
+        }
+
+        // Zodiac compatibility (0-3 bonus points), only when enabled
+        if scoring_flags & SCORING_FLAG_ZODIAC != 0 {
+            compatibility_score += zodiac_bonus(user_a_profile.zodiac, user_b_profile.zodiac);
+        }
+
+        // Personality compatibility (0-5 bonus points), only when enabled
+        if scoring_flags & SCORING_FLAG_PERSONALITY != 0 {
+            compatibility_score += personality_bonus(user_a_profile.personality_type, user_b_profile.personality_type);
+        }
+
+        // Religion/values compatibility (0-6 bonus points), weighted by each side's importance
+        let (a_religion_points, _) = religion_fit(user_a_prefs, user_a_profile, user_b_profile);
+        let (b_religion_points, _) = religion_fit(user_b_prefs, user_b_profile, user_a_profile);
+        compatibility_score += a_religion_points + b_religion_points;
+
+        // Diet compatibility (0-6 bonus points)
+        let (a_diet_points, _) = diet_fit(user_a_prefs, user_b_profile);
+        let (b_diet_points, _) = diet_fit(user_b_prefs, user_a_profile);
+        compatibility_score += a_diet_points + b_diet_points;
+
+        // Children/family compatibility (0-6 bonus points), soft only
+        compatibility_score += children_fit(user_a_prefs, user_b_profile) + children_fit(user_b_prefs, user_a_profile);
+
+        // Height/body-type compatibility (0-6 bonus points), soft only
+        let physical_points = physical_fit(user_a_prefs, user_b_profile) + physical_fit(user_b_prefs, user_a_profile);
+        compatibility_score += physical_points;
+
+        // Pet allergy, religion, diet, relationship status, gender and distance
+        // dealbreakers from either side
+        if pair_dealbreaker(user_a_prefs, user_a_profile, user_b_prefs, user_b_profile, location_score) {
+            compatibility_score = 0;
+        }
+        
+        if !age_gate_passed {
+            compatibility_score = 0;
         }
+
+        let sub_scores = [age_points, interests_score, location_points, relationship_points, physical_points];
+        (compatibility_score, age_gate_passed, sub_scores)
+    }
+
+    // Plaintext mirror with unit tests: programs/contract/src/scoring.rs.
+    // Keep the weights, caps and dealbreakers in sync with it.
+    #[instruction]
+    pub fn calculate_compatibility(
+        user_a_data_ctxt: Enc<Shared, MatchingData>,
+        user_b_data_ctxt: Enc<Shared, MatchingData>,
+        user_a_reputation_ctxt: Enc<Mxe, Reputation>,
+        user_b_reputation_ctxt: Enc<Mxe, Reputation>,
+        user_a_has_reputation: bool,
+        user_b_has_reputation: bool,
+        scoring_flags: u8,
+    ) -> (u8, bool, Enc<Shared, ScoreBreakdown>, Enc<Shared, ScoreBreakdown>) {
+        // Each user's staged MatchingData, as read by check_mutual_match
+        let user_a_data = user_a_data_ctxt.to_arcis();
+        let user_b_data = user_b_data_ctxt.to_arcis();
+        let user_a_prefs = user_a_data.prefs;
+        let user_a_profile = user_a_data.profile;
+        let user_b_prefs = user_b_data.prefs;
+        let user_b_profile = user_b_data.profile;
         
-        // Return score capped at 100
-        if compatibility_score > 100 { 100 } else { compatibility_score }.reveal()
+        let (mut compatibility_score, age_gate_passed, sub_scores) =
+            pair_score(&user_a_prefs, &user_a_profile, &user_b_prefs, &user_b_profile, scoring_flags);
+
+        // A zeroed pair reveals no sub-scores, so the breakdown never hints at
+        // which dealbreaker fired
+        let viable = compatibility_score > 0;
+
+        // Reputation nudges a viable pair's score a few points either way;
+        // a missing reputation account contributes nothing
+        let user_a_reputation = user_a_reputation_ctxt.to_arcis();
+        let user_b_reputation = user_b_reputation_ctxt.to_arcis();
+        let (a_bonus, a_penalty) = reputation_adjustment(&user_a_reputation, user_a_has_reputation);
+        let (b_bonus, b_penalty) = reputation_adjustment(&user_b_reputation, user_b_has_reputation);
+        if compatibility_score > 0 {
+            compatibility_score += a_bonus + b_bonus;
+        }
+
+        // Return score capped at 100, less any reputation penalty
+        let capped_score = if compatibility_score > 100 { 100 } else { compatibility_score };
+        let penalty = a_penalty + b_penalty;
+        let final_score = if capped_score > penalty { capped_score - penalty } else { 0 };
+        (
+            final_score.reveal(),
+            age_gate_passed.reveal(),
+            user_a_data_ctxt.owner.from_arcis(score_breakdown(viable, sub_scores)),
+            user_b_data_ctxt.owner.from_arcis(score_breakdown(viable, sub_scores)),
+        )
+    }
+
+
+    // Candidates scored per batch computation
+    const COMPATIBILITY_BATCH_SIZE: usize = 8;
+
+    // One-sided score of `candidate_data` against the requesting user's
+    // preferences: points count only what the requester asked for, but the
+    // dealbreakers are the pair's, as in `pair_score`.
+    // Plaintext mirror: `score_candidate` in programs/contract/src/scoring.rs.
+    fn score_candidate(
+        prefs: &UserPreferences,
+        profile: &UserProfile,
+        candidate_data: &MatchingData,
+        scoring_flags: u8,
+    ) -> u8 {
+        let candidate = &candidate_data.profile;
+        let mut score = 0u8;
+
+        // Age compatibility (0-30 points)
+        if candidate.age >= prefs.preferred_age_min &&
+            candidate.age <= prefs.preferred_age_max &&
+            within_age_gap(prefs.max_age_gap, profile.age, candidate.age) {
+            score += 30;
+        }
+
+        // Interests compatibility (0-25 points)
+        let interests_score = if profile.interests_count > 0 && candidate.interests_count > 0 {
+            let min_interests = if profile.interests_count < candidate.interests_count {
+                profile.interests_count
+            } else {
+                candidate.interests_count
+            };
+            let min_interests = if min_interests > MAX_INTERESTS { MAX_INTERESTS } else { min_interests };
+            let scaled = (min_interests as u16 * 25) / MAX_INTERESTS as u16;
+            if scaled > 25 { 25u8 } else { scaled as u8 }
+        } else {
+            0
+        };
+        score += interests_score;
+
+        // Location compatibility (0-25 points): full band within the requester's
+        // radius when both have coordinates, otherwise the averaged score
+        let location_score = if has_coordinates(profile) && has_coordinates(candidate) {
+            if within_radius(prefs.preferred_distance_km, squared_distance(profile, candidate)) { 25u16 } else { 0u16 }
+        } else {
+            (profile.location_score as u16 + candidate.location_score as u16) / 2
+        };
+        score += if location_score > 25 { 25u8 } else { location_score as u8 };
+
+        // Relationship type compatibility (0-20 points)
+        if profile.relationship_type == candidate.relationship_type {
+            score += 20;
+        }
+
+        // Pet compatibility (0-5 bonus points)
+        let pets_ok = (prefs.pet_preference == PET_PREF_LOVES_PETS && candidate.pet_status == PET_STATUS_OWNER) ||
+            (prefs.pet_preference == PET_PREF_NO_PETS && candidate.pet_status != PET_STATUS_OWNER);
+        if pets_ok {
+            score += 5;
+        }
+
+        // Budget compatibility (0-5 bonus points)
+        if candidate.budget_tier >= prefs.budget_tier_min && candidate.budget_tier <= prefs.budget_tier_max {
+            score += 5;
+        }
+
+        // Zodiac compatibility (0-3 bonus points), only when enabled
+        if scoring_flags & SCORING_FLAG_ZODIAC != 0 {
+            score += zodiac_bonus(profile.zodiac, candidate.zodiac);
+        }
+
+        // Personality compatibility (0-5 bonus points), only when enabled
+        if scoring_flags & SCORING_FLAG_PERSONALITY != 0 {
+            score += personality_bonus(profile.personality_type, candidate.personality_type);
+        }
+
+        // Religion/values compatibility (0-3 bonus points)
+        let (religion_points, _) = religion_fit(prefs, profile, candidate);
+        score += religion_points;
+
+        // Diet compatibility (0-3 bonus points)
+        let (diet_points, _) = diet_fit(prefs, candidate);
+        score += diet_points;
+
+        // Children/family compatibility (0-3 bonus points)
+        score += children_fit(prefs, candidate);
+
+        // Height/body-type compatibility (0-3 bonus points)
+        score += physical_fit(prefs, candidate);
+
+        // The veto sees both sides' preferences and the pair location score,
+        // so a candidate the pair score would zero never ranks
+        let pair_location = pair_location_score(prefs, profile, &candidate_data.prefs, candidate);
+        if pair_dealbreaker(prefs, profile, &candidate_data.prefs, candidate, pair_location) {
+            score = 0;
+        }
+
+        if score > 100 { 100 } else { score }
+    }
+
+    // Each candidate's own staged MatchingData, as read by calculate_compatibility.
+    // The program pads slots past candidate_count with the requester's data.
+    #[instruction]
+    pub fn calculate_compatibility_batch(
+        requester_ctxt: Enc<Shared, MatchingData>,
+        candidate_0_ctxt: Enc<Shared, MatchingData>,
+        candidate_1_ctxt: Enc<Shared, MatchingData>,
+        candidate_2_ctxt: Enc<Shared, MatchingData>,
+        candidate_3_ctxt: Enc<Shared, MatchingData>,
+        candidate_4_ctxt: Enc<Shared, MatchingData>,
+        candidate_5_ctxt: Enc<Shared, MatchingData>,
+        candidate_6_ctxt: Enc<Shared, MatchingData>,
+        candidate_7_ctxt: Enc<Shared, MatchingData>,
+        candidate_count: u8,
+        scoring_flags: u8,
+    ) -> ([u8; COMPATIBILITY_BATCH_SIZE], bool) {
+        let requester = requester_ctxt.to_arcis();
+        let candidates = [
+            candidate_0_ctxt.to_arcis(),
+            candidate_1_ctxt.to_arcis(),
+            candidate_2_ctxt.to_arcis(),
+            candidate_3_ctxt.to_arcis(),
+            candidate_4_ctxt.to_arcis(),
+            candidate_5_ctxt.to_arcis(),
+            candidate_6_ctxt.to_arcis(),
+            candidate_7_ctxt.to_arcis(),
+        ];
+
+        // Requester's own age gate; underage candidates score 0 individually
+        let age_gate_passed = requester.profile.age >= MIN_MATCHING_AGE;
+
+        let mut scores = [0u8; COMPATIBILITY_BATCH_SIZE];
+        for i in 0..COMPATIBILITY_BATCH_SIZE {
+            let score = score_candidate(&requester.prefs, &requester.profile, &candidates[i], scoring_flags);
+            // Padded slots (past candidate_count) are never revealed
+            if age_gate_passed && (i as u8) < candidate_count {
+                scores[i] = score;
+            }
+        }
+
+        (scores.reveal(), age_gate_passed.reveal())
+    }
+
+
+    pub struct DisclosureInput {
+        consent: bool,
+        exact_age: u8,
+        income_bracket: u8,
+    }
+
+    pub struct DisclosedProfile {
+        exact_age: u8,
+        income_bracket: u8,
+    }
+
+    #[instruction]
+    pub fn reveal_matched_data(
+        user_a_input_ctxt: Enc<Shared, DisclosureInput>,
+        user_b_input_ctxt: Enc<Shared, DisclosureInput>,
+        match_session_ctxt: Enc<Mxe, MatchSession>,
+    ) -> (Enc<Shared, DisclosedProfile>, Enc<Shared, DisclosedProfile>, bool) {
+        let user_a_input = user_a_input_ctxt.to_arcis();
+        let user_b_input = user_b_input_ctxt.to_arcis();
+        let match_session = match_session_ctxt.to_arcis();
+
+        // Only disclose when the pair actually matched and both opted in
+        let is_mutual = match_session.user_a_liked && match_session.user_b_liked;
+        let both_consented = is_mutual && user_a_input.consent && user_b_input.consent;
+
+        let mut for_user_a = DisclosedProfile { exact_age: 0, income_bracket: 0 };
+        let mut for_user_b = DisclosedProfile { exact_age: 0, income_bracket: 0 };
+        if both_consented {
+            for_user_a.exact_age = user_b_input.exact_age;
+            for_user_a.income_bracket = user_b_input.income_bracket;
+            for_user_b.exact_age = user_a_input.exact_age;
+            for_user_b.income_bracket = user_a_input.income_bracket;
+        }
+
+        (
+            user_a_input_ctxt.owner.from_arcis(for_user_a),
+            user_b_input_ctxt.owner.from_arcis(for_user_b),
+            both_consented.reveal(),
+        )
+    }
+
+
+    // Up to 64 bytes of answer text, packed little-endian into u128 chunks
+    pub struct IcebreakerAnswer {
+        chunks: [u128; 4],
+    }
+
+    #[instruction]
+    pub fn reveal_icebreakers(
+        user_a_answer_ctxt: Enc<Shared, IcebreakerAnswer>,
+        user_b_answer_ctxt: Enc<Shared, IcebreakerAnswer>,
+        match_session_ctxt: Enc<Mxe, MatchSession>,
+    ) -> (Enc<Shared, IcebreakerAnswer>, Enc<Shared, IcebreakerAnswer>, bool) {
+        let user_a_answer = user_a_answer_ctxt.to_arcis();
+        let user_b_answer = user_b_answer_ctxt.to_arcis();
+        let match_session = match_session_ctxt.to_arcis();
+
+        // Answers only cross over once both users liked each other
+        let is_mutual = match_session.user_a_liked && match_session.user_b_liked;
+
+        let mut for_user_a = IcebreakerAnswer { chunks: [0u128; 4] };
+        let mut for_user_b = IcebreakerAnswer { chunks: [0u128; 4] };
+        if is_mutual {
+            for_user_a.chunks = user_b_answer.chunks;
+            for_user_b.chunks = user_a_answer.chunks;
+        }
+
+        (
+            user_a_answer_ctxt.owner.from_arcis(for_user_a),
+            user_b_answer_ctxt.owner.from_arcis(for_user_b),
+            is_mutual.reveal(),
+        )
     }
 }
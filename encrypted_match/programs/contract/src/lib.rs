@@ -6,12 +6,67 @@ use arcium_anchor::prelude::*;
 use arcium_client::idl::arcium::types::CallbackAccount;
 use serde::{Serialize, Deserialize};
 
+#[cfg(feature = "mock-mpc")]
+mod mock_mpc;
+pub mod migration;
+pub mod scoring;
+
 declare_id!("Gjs746NpmhmHR5RXY21qNRzw2igtLcMAUZWDjABesiT4");
 
 // ============================================================================
 // DATA MODELS AND STRUCTS
 // ============================================================================
 
+/// Result of `have_matched`; the timestamp is only shared with participants
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct HaveMatchedView {
+    pub matched: bool,
+    pub matched_at: Option<i64>,
+}
+
+/// In-flight computation returned by `get_pending_computation`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PendingComputation {
+    pub comp_def_offset: u32,
+    pub computation_offset: u64,
+    pub queued_at: i64,
+}
+
+/// One cached batch of scores returned by `get_compatibility_scores_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CompatibilityScoresEntry {
+    pub owner: Pubkey,
+    pub scores: [u8; COMPATIBILITY_BATCH_SIZE],
+    pub candidate_count: u8,
+    pub scored_at: i64,
+    pub stale: bool,                        // Never scored, or older than COMPATIBILITY_CACHE_TTL
+}
+
+/// Boost state returned by `get_boost_status`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BoostStatus {
+    pub is_boosted: bool,
+    pub boost_until: i64,
+    pub remaining_seconds: i64,
+}
+
+/// Counters and flags for the profile screen, returned by `get_user_stats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UserStats {
+    pub owner: Pubkey,
+    pub total_likes_given: u32,
+    pub total_likes_received: u32,
+    pub total_matches: u32,
+    pub completeness: u8,
+    pub is_active: bool,
+    pub is_discoverable: bool,
+    pub is_verified: bool,
+    pub matching_paused: bool,
+    pub privacy_level: u8,
+    pub is_boosted: bool,
+    pub boost_until: i64,
+}
+
 /// Profile creation data structure for blockchain storage
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct CreateProfileData {
@@ -23,6 +78,20 @@ pub struct CreateProfileData {
     pub encrypted_preferences: Vec<u8>,     // Encrypted matching preferences  
     pub encryption_pubkey: [u8; 32],        // User's public key for encryption
     pub profile_version: u8,
+    pub gender: u8,                         // GENDER_* code
+    pub orientation: u8,                    // ORIENTATION_* code
+    pub photo_urls: Vec<String>,            // Gallery starting with avatar_url, or empty
+    pub bio: String,                        // Free text, may be empty
+}
+
+/// New public profile values; `None` leaves a field unchanged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateProfileData {
+    pub username: Option<String>,
+    pub avatar_url: Option<String>,
+    pub age: Option<u8>,
+    pub location_city: Option<String>,
+    pub bio: Option<String>,
 }
 
 /// Complete profile input (for client-side processing before encryption)
@@ -40,16 +109,105 @@ pub struct CreateProfileInput {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PrivateProfileData {
     pub income: String,  // Simplified to just income for sensitive data
+    pub pet_status: u8,  // 0 = no pets, 1 = pet owner, 2 = allergic
+    pub zodiac: u8,      // 0 = Aries ... 11 = Pisces
+    pub budget_tier: u8, // Date spending comfort, 0 = budget ... 3 = luxury
+    pub exact_age: u8,   // Encrypted age checked in MPC against the 18+ floor
+    pub personality_type: u8, // 0 = INTJ ... 15 = ESFP (see circuit ordering)
+    pub relationship_status: u8, // 0 = single, 1 = divorced, 2 = widowed, 3 = separated
+    pub religion: u8,            // 0 = unspecified, 1.. = app-defined religion codes
+    pub religion_importance: u8, // 0 = not important ... 3 = dealbreaker on mismatch
+    pub diet: u8,                // 0 = omnivore, 1 = vegetarian, 2 = vegan, 3 = pescatarian, 4 = halal, 5 = kosher
+    pub num_children: u8,              // Existing children, 0 = none
+    pub youngest_child_age_bucket: u8, // 0 = none, 1 = under 5, 2 = 5-12, 3 = 13-17, 4 = adult
+    pub height_cm: u8,                 // Height in cm (capped at 255), 0 = unspecified
+    pub body_type: u8,                 // 0 = unspecified, 1 = slim, 2 = athletic, 3 = average, 4 = curvy, 5 = large
+    pub grid_x: u16,                   // Quantized position from `quantize_coordinates`, (0, 0) = unset
+    pub grid_y: u16,
 }
 
 /// Matching preferences data
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, AnchorSerialize, AnchorDeserialize)]
 pub struct MatchingPreferences {
     pub preferred_age_min: u8,
     pub preferred_age_max: u8,
     pub preferred_distance_km: u16,
     pub interests: Vec<String>,
     pub relationship_type: String,
+    pub pet_preference: u8,      // 0 = no preference, 1 = loves pets, 2 = prefers no pets
+    pub pet_dealbreaker: bool,   // Never match pet owners when allergic
+    pub budget_tier_min: u8,     // Accepted date budget range (inclusive)
+    pub budget_tier_max: u8,
+    pub relationship_status_preference: u8, // Bitmask of accepted statuses, 0 = any
+    pub religion_preference: u8,            // Preferred religion code, 0 = no preference
+    pub diet_preference: u8,                // Bitmask of accepted diets, 0 = any
+    pub diet_dealbreaker: bool,             // Never match a diet outside the accepted set
+    pub max_age_gap: u8,                    // Max years between ages, 0 = no limit
+    pub children_preference: u8,            // 0 = no preference, 1 = prefers no children, 2 = open to children
+    pub min_youngest_child_bucket: u8,      // Youngest child bucket accepted when open, 0 = any
+    pub open_to_long_distance: bool,        // Waives the distance dealbreaker for both sides
+    pub preferred_height_min: u8,           // Preferred height range in cm, 0 = no bound
+    pub preferred_height_max: u8,
+    pub body_type_preference: u8,           // Bitmask of preferred body types, 0 = no preference
+    pub preferred_genders: u8,              // Bitmask of accepted GENDER_* codes, 0 = any
+}
+
+impl MatchingPreferences {
+    /// Check before encrypting, exposed as `validate_matching_preferences`;
+    /// the program cannot see the plaintext, so this keeps inputs within the
+    /// circuit's fixed bounds
+    pub fn validate(&self) -> Result<()> {
+        require!(self.interests.len() <= MAX_INTERESTS, ErrorCode::TooManyInterests);
+        require!(
+            self.preferred_height_max == 0 || self.preferred_height_min <= self.preferred_height_max,
+            ErrorCode::InvalidHeightRange
+        );
+        Ok(())
+    }
+}
+
+/// Read-only profile shape returned by `get_profile_view`.
+///
+/// Borsh layout, version 2 (fields in declaration order): summary_version u8,
+/// owner [32], username/avatar_url (u32 len + bytes), age u8, location_city
+/// (u32 len + bytes), is_active u8, three u32 totals, created_at and
+/// last_updated i64, is_discoverable u8, completeness u8, is_verified u8,
+/// is_redacted u8 (added in version 2).
+/// Fields are only ever appended, and any change bumps PROFILE_VIEW_VERSION.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProfileView {
+    pub summary_version: u8,                // PROFILE_VIEW_VERSION
+    pub owner: Pubkey,
+    pub username: String,
+    pub avatar_url: String,
+    pub age: u8,
+    pub location_city: String,
+    pub is_active: bool,
+    pub total_likes_given: u32,
+    pub total_likes_received: u32,
+    pub total_matches: u32,
+    pub created_at: i64,
+    pub last_updated: i64,
+
+    // Derived flags
+    pub is_discoverable: bool,
+    pub completeness: u8,                   // 0-100
+    pub is_verified: bool,
+    pub is_redacted: bool,                  // Fields hidden by the owner's privacy_level
+}
+
+/// One page of a list view; pass `next_cursor` back as `cursor` to continue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<u32>,         // None when this is the last page
+}
+
+/// One page of a byte history; pass `next_offset` back as `offset` to continue
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BytePage {
+    pub bytes: Vec<u8>,
+    pub next_offset: Option<u32>,         // Byte offset of the next page; None on the last
 }
 
 // ============================================================================
@@ -81,12 +239,57 @@ pub struct UserProfile {
     // Encrypted interaction history
     pub encrypted_likes_given: Vec<u8>,
     pub encrypted_likes_received: Vec<u8>,
-    pub encrypted_matches: Vec<u8>,
+
+    // Public match history: MATCH_RECORD_LEN-byte records in the clear,
+    // readable by anyone who fetches the profile
+    pub match_history: Vec<u8>,
     
     // Public statistics
     pub total_likes_given: u32,
     pub total_likes_received: u32,
     pub total_matches: u32,
+
+    // Encrypted icebreaker answer, only revealed to a mutual match
+    pub icebreaker_answer: Vec<u8>,          // nonce + 4 ciphertexts, or empty
+
+    // Stops new sessions targeting this user without hiding the profile
+    pub matching_paused: bool,
+
+    // Discovery boost, active while the clock is before this timestamp
+    pub boost_until: i64,
+
+    // Which public fields non-matched viewers see, PRIVACY_LEVEL_*
+    pub privacy_level: u8,
+
+    // Who may like this profile, LIKE_POLICY_*, and the public age range
+    // used by the mutual-age-range policy (0 = unbounded)
+    pub like_policy: u8,
+    pub like_age_min: u8,
+    pub like_age_max: u8,
+
+    // Account layout, see PROFILE_LAYOUT_VERSION and `migrate_profile`
+    pub layout_version: u8,
+
+    // Match records moved out of `match_history` into `MatchArchive` pages
+    pub archived_match_records: u32,
+
+    // Sessions involving this user that are not yet finalized; blocks close_profile
+    pub open_sessions: u32,
+
+    // Public identity used for preference filtering, GENDER_* and ORIENTATION_*
+    pub gender: u8,
+    pub orientation: u8,
+
+    // Photo gallery in display order; the first photo is always avatar_url.
+    // Empty for profiles that only have an avatar.
+    pub photo_urls: Vec<String>,
+
+    // Public free-text description, empty if the user wrote none
+    pub bio: String,
+
+    pub last_seen: i64,                     // Last owner heartbeat, for "online recently"
+
+    pub is_verified: bool,                  // Badge set by the config's verification authority
 }
 
 impl UserProfile {
@@ -106,10 +309,194 @@ impl UserProfile {
         500 +     // encrypted_preferences (4 + 496)
         500 +     // encrypted_likes_given (4 + 496)
         500 +     // encrypted_likes_received (4 + 496)
-        300 +     // encrypted_matches (4 + 296)
+        300 +     // match_history (4 + 296)
         4 +       // total_likes_given
         4 +       // total_likes_received
-        4;        // total_matches
+        4 +       // total_matches
+        4 + ICEBREAKER_ANSWER_LEN + // icebreaker_answer
+        1 +       // matching_paused
+        8 +       // boost_until
+        1 +       // privacy_level
+        1 +       // like_policy
+        1 +       // like_age_min
+        1 +       // like_age_max
+        1 +       // layout_version
+        4 +       // archived_match_records
+        4 +       // open_sessions
+        1 +       // gender
+        1 +       // orientation
+        4 + MAX_PHOTOS * (4 + MAX_PHOTO_URL_LEN) + // photo_urls
+        4 + MAX_BIO_LEN + // bio
+        8 +       // last_seen
+        1;        // is_verified
+
+    /// Space of a freshly created profile: every field at full size except the
+    /// four encrypted vectors and the photo gallery, which hold only their
+    /// length prefixes until `create_profile` sizes them to the submitted
+    /// data, `grow_profile` reserves more or `add_photo` grows the account
+    pub const COMPACT_SPACE: usize =
+        Self::INIT_SPACE - 996 - 496 - 496 - 496 - MAX_PHOTOS * (4 + MAX_PHOTO_URL_LEN);
+
+    /// Space a photo gallery takes beyond its length prefix
+    pub fn photo_space(photo_urls: &[String]) -> usize {
+        photo_urls.iter().map(|url| 4 + url.len()).sum()
+    }
+
+    /// Account length (discriminator included) to allocate for the profile's
+    /// current contents, never one that reads as a legacy layout
+    pub fn account_len(&self) -> usize {
+        migration::self_describing_len(8 + self.required_space())
+    }
+
+    /// Space the profile needs to hold its current encrypted vectors and photos
+    pub fn required_space(&self) -> usize {
+        Self::COMPACT_SPACE +
+            self.encrypted_private_data.len() +
+            self.encrypted_preferences.len() +
+            self.encrypted_likes_given.len() +
+            self.encrypted_likes_received.len() +
+            Self::photo_space(&self.photo_urls)
+    }
+
+    /// Replaces the avatar, keeping it the gallery's first photo
+    pub fn set_avatar(&mut self, avatar_url: String) {
+        if let Some(first) = self.photo_urls.first_mut() {
+            *first = avatar_url.clone();
+        }
+        self.avatar_url = avatar_url;
+    }
+
+    /// Latest sign of activity: a profile write or a presence heartbeat
+    pub fn last_active_at(&self) -> i64 {
+        self.last_seen.max(self.last_updated)
+    }
+
+    /// Match records held inline in `match_history`
+    pub fn inline_match_records(&self) -> usize {
+        self.match_history.len() / MATCH_RECORD_LEN
+    }
+
+    /// Appends a confirmed match to the inline history; fails once the
+    /// blob's space is used up, until `archive_match_records` makes room
+    pub fn push_match_record(&mut self, session_id: u64, matched_at: i64, matched_user: Pubkey) -> Result<()> {
+        require!(
+            self.inline_match_records() < MAX_INLINE_MATCH_RECORDS as usize,
+            ErrorCode::MatchHistoryFull
+        );
+        self.match_history.extend_from_slice(&session_id.to_le_bytes());
+        self.match_history.extend_from_slice(&matched_at.to_le_bytes());
+        self.match_history.extend_from_slice(matched_user.as_ref());
+        Ok(())
+    }
+
+    /// Whether `age` falls inside this profile's public like age range
+    fn like_age_range_accepts(&self, age: u8) -> bool {
+        (self.like_age_min == 0 || age >= self.like_age_min) &&
+            (self.like_age_max == 0 || age <= self.like_age_max)
+    }
+
+    /// Whether this profile's like policy admits a like from `liker`
+    pub fn accepts_like_from(&self, liker: &UserProfile) -> bool {
+        match self.like_policy {
+            LIKE_POLICY_VERIFIED_ONLY => liker.is_verified,
+            LIKE_POLICY_MUTUAL_AGE_RANGE => {
+                self.like_age_range_accepts(liker.age) && liker.like_age_range_accepts(self.age)
+            },
+            _ => true,
+        }
+    }
+
+    /// Whether a discovery boost is currently active
+    pub fn is_boosted(&self, now: i64) -> bool {
+        now < self.boost_until
+    }
+
+    /// Splits the stored icebreaker answer into its nonce and ciphertexts
+    pub fn icebreaker_ciphertexts(&self) -> Result<(u128, [[u8; 32]; 4])> {
+        require!(
+            self.icebreaker_answer.len() == ICEBREAKER_ANSWER_LEN,
+            ErrorCode::IcebreakerMissing
+        );
+        let nonce = u128::from_le_bytes(self.icebreaker_answer[..16].try_into().unwrap());
+        let mut ciphertexts = [[0u8; 32]; 4];
+        for (i, chunk) in self.icebreaker_answer[16..].chunks_exact(32).enumerate() {
+            ciphertexts[i].copy_from_slice(chunk);
+        }
+        Ok((nonce, ciphertexts))
+    }
+
+    /// Whether the profile should appear in discovery
+    pub fn is_discoverable(&self) -> bool {
+        self.is_active && self.completeness() >= 60
+    }
+
+    /// Profile completeness score (0-100), 20 points per filled section
+    pub fn completeness(&self) -> u8 {
+        let sections = [
+            !self.avatar_url.is_empty(),
+            !self.location_city.is_empty(),
+            self.encryption_pubkey != [0u8; 32],
+            !self.encrypted_private_data.is_empty(),
+            !self.encrypted_preferences.is_empty(),
+        ];
+        sections.iter().filter(|filled| **filled).count() as u8 * 20
+    }
+
+    /// Builds the consolidated read-path view of this profile
+    pub fn to_view(&self) -> ProfileView {
+        ProfileView {
+            summary_version: PROFILE_VIEW_VERSION,
+            owner: self.owner,
+            username: self.username.clone(),
+            avatar_url: self.avatar_url.clone(),
+            age: self.age,
+            location_city: self.location_city.clone(),
+            is_active: self.is_active,
+            total_likes_given: self.total_likes_given,
+            total_likes_received: self.total_likes_received,
+            total_matches: self.total_matches,
+            created_at: self.created_at,
+            last_updated: self.last_updated,
+            is_discoverable: self.is_discoverable(),
+            completeness: self.completeness(),
+            is_verified: self.is_verified,
+            is_redacted: false,
+        }
+    }
+
+    /// Bundles the public counters and derived flags for the profile screen
+    pub fn to_stats(&self, now: i64) -> UserStats {
+        UserStats {
+            owner: self.owner,
+            total_likes_given: self.total_likes_given,
+            total_likes_received: self.total_likes_received,
+            total_matches: self.total_matches,
+            completeness: self.completeness(),
+            is_active: self.is_active,
+            is_discoverable: self.is_discoverable(),
+            is_verified: self.is_verified,
+            matching_paused: self.matching_paused,
+            privacy_level: self.privacy_level,
+            is_boosted: self.is_boosted(now),
+            boost_until: self.boost_until,
+        }
+    }
+
+    /// Builds the view for a viewer; without full access, fields hidden by
+    /// the owner's privacy level are blanked and the view is marked redacted
+    pub fn to_view_for(&self, full_access: bool) -> ProfileView {
+        let mut view = self.to_view();
+        if full_access || self.privacy_level == PRIVACY_LEVEL_PUBLIC {
+            return view;
+        }
+
+        view.location_city = String::new();
+        if self.privacy_level >= PRIVACY_LEVEL_HIDDEN {
+            view.avatar_url = String::new();
+        }
+        view.is_redacted = true;
+        view
+    }
 }
 
 /// Match session account for encrypted matching between two users
@@ -125,6 +512,38 @@ pub struct MatchPairSession {
     pub is_finalized: bool,
     pub match_found: bool,
     pub bump: u8,
+
+    // In-flight MPC computation, recorded at queue time and checked by callbacks
+    pub pending_comp_def_offset: u32,       // 0 when nothing is in flight
+    pub pending_computation_offset: u64,
+    pub pending_since: i64,
+
+    pub matched_at: i64,                    // 0 until a mutual match is confirmed
+
+    pub ciphertext_layout_version: u8,      // 0 = legacy, see CIPHERTEXT_LAYOUT_VERSION
+
+    // Opt-in read receipts, set when each participant's client shows the session
+    pub user_a_seen_at: i64,                // 0 until user_a marks it seen
+    pub user_b_seen_at: i64,                // 0 until user_b marks it seen
+
+    pub conversation_started_at: i64,       // 0 until a participant starts talking after a match
+    pub finalization_reason: u8,            // FINALIZATION_REASON_* once finalized
+
+    // Set once each participant's rating of the other has been aggregated
+    pub user_a_rated: bool,
+    pub user_b_rated: bool,
+
+    pub total_compute_fees: u64,            // Lamports the fee pool gained from this session's computations
+
+    // Encrypted `LikeIntents`, written by init and submit_like under their own nonce
+    pub intents_nonce: u128,
+    pub encrypted_intents: [[u8; 32]; 2],
+    pub match_confidence: u8,               // Mean like intent (0-100) of a confirmed match, else 0
+
+    // Computation account of the last callback applied, so a redelivery is a no-op
+    pub last_processed_computation: Pubkey,
+
+    pub is_ready_for_verification: bool,    // Set once both likes are in; gates check_mutual_match
 }
 
 impl MatchPairSession {
@@ -138,507 +557,8142 @@ impl MatchPairSession {
         8 +        // last_updated
         1 +        // is_finalized
         1 +        // match_found
-        1;         // bump
-}
+        1 +        // bump
+        4 +        // pending_comp_def_offset
+        8 +        // pending_computation_offset
+        8 +        // pending_since
+        8 +        // matched_at
+        1 +        // ciphertext_layout_version
+        8 +        // user_a_seen_at
+        8 +        // user_b_seen_at
+        8 +        // conversation_started_at
+        1 +        // finalization_reason
+        1 +        // user_a_rated
+        1 +        // user_b_rated
+        8 +        // total_compute_fees
+        16 +       // intents_nonce
+        32 * 2 +   // encrypted_intents
+        1 +        // match_confidence
+        32 +       // last_processed_computation
+        1;         // is_ready_for_verification
 
-// ============================================================================
-// CONTEXT STRUCTS
-// ============================================================================
+    /// Byte offset of `encrypted_intents`: the discriminator plus every field
+    /// declared before it
+    pub const ENCRYPTED_INTENTS_OFFSET: u32 =
+        8 + 8 + 32 + 32 + 32 * 6 + 16 + 8 + 8 + 1 + 1 + 1 + 4 + 8 * 3 + 1 + 8 * 3 + 1 + 1 + 1 + 8 + 16;
 
-/// Context for creating a user profile
-#[derive(Accounts)]
-#[instruction(profile_data: CreateProfileData)]
-pub struct CreateProfile<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    #[account(
-        init,
-        payer = user,
-        space = 8 + UserProfile::INIT_SPACE,
-        seeds = [b"user_profile", user.key().as_ref()],
-        bump
-    )]
-    pub user_profile: Account<'info, UserProfile>,
-    
-    pub system_program: Program<'info, System>,
+    /// Whether a match has sat unstarted for at least `archive_window` seconds
+    /// since it was made or last revived
+    pub fn is_stale_match(&self, now: i64, archive_window: i64) -> bool {
+        self.is_finalized &&
+            self.match_found &&
+            self.finalization_reason == FINALIZATION_REASON_CHECKED &&
+            self.conversation_started_at == 0 &&
+            now.saturating_sub(self.matched_at.max(self.last_updated)) >= archive_window
+    }
+
+    /// The lower of the two participants, the first pair seed
+    pub fn pair_low(&self) -> Pubkey {
+        session_pair(&self.user_a, &self.user_b).0
+    }
+
+    /// The higher of the two participants, the second pair seed
+    pub fn pair_high(&self) -> Pubkey {
+        session_pair(&self.user_a, &self.user_b).1
+    }
+
+    /// Re-derives this session's PDA from its stored seeds
+    pub fn derive_address(&self) -> Result<Pubkey> {
+        Pubkey::create_program_address(
+            &[b"pair_session", self.pair_low().as_ref(), self.pair_high().as_ref(), &[self.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::InvalidSession.into())
+    }
+
+    /// Records a computation queued against this session. Only one may be in
+    /// flight at a time, unless the previous one has timed out without a callback.
+    pub fn begin_computation(
+        &mut self,
+        comp_def_offset: u32,
+        computation_offset: u64,
+        now: i64,
+    ) -> Result<()> {
+        require!(
+            self.pending_comp_def_offset == 0 ||
+            now.saturating_sub(self.pending_since) > PENDING_COMPUTATION_TIMEOUT,
+            ErrorCode::ComputationPending
+        );
+        self.pending_comp_def_offset = comp_def_offset;
+        self.pending_computation_offset = computation_offset;
+        self.pending_since = now;
+        Ok(())
+    }
+
+    /// Adds the MPC fee one queued computation charged to the running total
+    pub fn record_compute_fee(&mut self, fee: u64) {
+        self.total_compute_fees = self.total_compute_fees.saturating_add(fee);
+    }
+
+    /// Stores the encrypted state a circuit handed back
+    fn store_ciphertexts(&mut self, state: SessionCiphertexts) {
+        self.encrypted_match_data = state.match_data;
+        self.nonce = state.nonce;
+        self.encrypted_intents = state.intents;
+        self.intents_nonce = state.intents_nonce;
+    }
+
+    /// The computation awaiting its callback, if any
+    pub fn pending_computation(&self) -> Option<PendingComputation> {
+        (self.pending_comp_def_offset != 0).then(|| PendingComputation {
+            comp_def_offset: self.pending_comp_def_offset,
+            computation_offset: self.pending_computation_offset,
+            queued_at: self.pending_since,
+        })
+    }
+
+    /// Whether the session has been idle longer than `idle_ttl` seconds
+    pub fn is_expired(&self, now: i64, idle_ttl: i64) -> bool {
+        now.saturating_sub(self.last_updated) > idle_ttl
+    }
+
+    /// Verifies, inside a callback, that `session_key` is this genuine session
+    /// and that `computation` is the pending `comp_def_offset` computation,
+    /// then clears the pending marker. Returns false, leaving the session
+    /// untouched, when `computation` was already applied
+    pub fn complete_computation(
+        &mut self,
+        session_key: Pubkey,
+        comp_def_offset: u32,
+        computation: Pubkey,
+    ) -> Result<bool> {
+        require_keys_eq!(session_key, self.derive_address()?, ErrorCode::InvalidSession);
+        if computation == self.last_processed_computation {
+            return Ok(false);
+        }
+        require_eq!(self.pending_comp_def_offset, comp_def_offset, ErrorCode::UnexpectedCallback);
+        require_keys_eq!(
+            computation,
+            derive_comp_pda!(self.pending_computation_offset),
+            ErrorCode::UnexpectedCallback
+        );
+        self.pending_comp_def_offset = 0;
+        self.pending_computation_offset = 0;
+        self.pending_since = 0;
+        self.last_processed_computation = computation;
+        Ok(true)
+    }
 }
 
-/// Context for initializing computation definitions
-#[derive(Accounts)]
-pub struct InitCompDef<'info> {
-    #[account(mut)]
-    pub payer: Signer<'info>,
+/// Relayer authorized to submit encrypted likes on a user's behalf
+#[account]
+pub struct LikeDelegate {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub updated_at: i64,
+    pub bump: u8,
 }
 
-// ============================================================================
-// EVENTS
-// ============================================================================
+impl LikeDelegate {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        32 +       // delegate
+        8 +        // updated_at
+        1;         // bump
+}
 
-/// Event emitted when a user profile is created
-#[event]
-pub struct ProfileCreatedEvent {
-    pub user: Pubkey,
-    pub profile_pda: Pubkey,
-    pub username: String,
-    pub age: u8,
-    pub location_city: String,
-    pub timestamp: i64,
+/// Likes a user has submitted in the current UTC day; clients read
+/// `remaining` against the config's `max_daily_likes` to show the quota left
+#[account]
+pub struct LikeQuota {
+    pub owner: Pubkey,
+    pub likes_today: u16,
+    pub super_likes_today: u16,
+    pub window_start: i64,                  // Start of the UTC day both counts cover
+    pub bump: u8,
 }
 
-/// Event emitted when a match session is created
-#[event]
-pub struct MatchSessionCreatedEvent {
-    pub session_id: u64,
-    pub user_a: Pubkey,
-    pub user_b: Pubkey,
-    pub created_at: i64,
+impl LikeQuota {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        2 +        // likes_today
+        2 +        // super_likes_today
+        8 +        // window_start
+        1;         // bump
+
+    /// Whether `now` falls in a later UTC day than the counted one. Days are
+    /// compared rather than timestamps, so a clock that steps back never
+    /// reopens a window that was already used up.
+    fn window_elapsed(&self, now: i64) -> bool {
+        now.div_euclid(SECONDS_PER_DAY) > self.window_start.div_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Starts fresh counts when a later UTC day has begun
+    fn roll_window(&mut self, now: i64) {
+        if self.window_elapsed(now) {
+            self.likes_today = 0;
+            self.super_likes_today = 0;
+            self.window_start = now.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY;
+        }
+    }
+
+    /// Likes still available at `now` under `limit`
+    pub fn remaining(&self, now: i64, limit: u16) -> u16 {
+        if self.window_elapsed(now) {
+            limit
+        } else {
+            limit.saturating_sub(self.likes_today)
+        }
+    }
+
+    /// Super likes still available at `now`
+    pub fn remaining_super_likes(&self, now: i64) -> u16 {
+        if self.window_elapsed(now) {
+            MAX_DAILY_SUPER_LIKES
+        } else {
+            MAX_DAILY_SUPER_LIKES.saturating_sub(self.super_likes_today)
+        }
+    }
+
+    /// Counts one like at `now` against `limit`
+    pub fn consume(&mut self, now: i64, limit: u16) -> Result<()> {
+        self.roll_window(now);
+        require!(self.likes_today < limit, ErrorCode::DailyLikeLimitReached);
+        self.likes_today += 1;
+        Ok(())
+    }
+
+    /// Counts one super like at `now` against `MAX_DAILY_SUPER_LIKES`
+    pub fn consume_super_like(&mut self, now: i64) -> Result<()> {
+        self.roll_window(now);
+        require!(self.super_likes_today < MAX_DAILY_SUPER_LIKES, ErrorCode::DailySuperLikeLimitReached);
+        self.super_likes_today += 1;
+        Ok(())
+    }
 }
 
-/// Event emitted when a like is submitted
-#[event]
-pub struct LikeSubmittedEvent {
-    pub session_id: u64,
-    pub timestamp: i64,
+/// Users the owner has blocked; private to the owner
+#[account]
+pub struct Blocklist {
+    pub owner: Pubkey,
+    pub blocked: Vec<Pubkey>,
+    pub bump: u8,
 }
 
-/// Event emitted when mutual interest is detected
-#[event]
-pub struct MutualInterestDetectedEvent {
-    pub session_id: u64,
-    pub timestamp: i64,
+impl Blocklist {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        4 + 32 * MAX_BLOCKED_USERS + // blocked
+        1;         // bump
+
+    pub fn contains(&self, user: &Pubkey) -> bool {
+        self.blocked.contains(user)
+    }
 }
 
-/// Event emitted when a mutual match is found
-#[event]
-pub struct MutualMatchFoundEvent {
-    pub session_id: u64,
-    pub user_a: Pubkey,
-    pub user_b: Pubkey,
-    pub matched_at: i64,
-    pub can_start_conversation: bool,
+/// A reporter's latest abuse report against one user, renewable once
+/// `REPORT_COOLDOWN` has passed
+#[account]
+pub struct ProfileReport {
+    pub reporter: Pubkey,
+    pub reported: Pubkey,
+    pub reason_code: u8,
+    pub reported_at: i64,                   // 0 until a report is accepted
+    pub bump: u8,
 }
 
-/// Event emitted when no mutual match is found
-#[event]
-pub struct NoMutualMatchEvent {
-    pub session_id: u64,
-    pub finalized_at: i64,
+impl ProfileReport {
+    pub const INIT_SPACE: usize =
+        32 +       // reporter
+        32 +       // reported
+        1 +        // reason_code
+        8 +        // reported_at
+        1;         // bump
 }
 
-// ============================================================================
-// CONSTANTS
-// ============================================================================
-
-/// Computation definition offsets for MPC operations
-const COMP_DEF_OFFSET_INIT_MATCH_SESSION: u32 = comp_def_offset("init_match_session");
-const COMP_DEF_OFFSET_SUBMIT_LIKE: u32 = comp_def_offset("submit_like");
-const COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH: u32 = comp_def_offset("check_mutual_match");
-const COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY: u32 = comp_def_offset("calculate_compatibility");
+/// Distinct verified reporters a user has had, which decides auto-deactivation
+#[account]
+pub struct ReportTally {
+    pub reported: Pubkey,
+    pub verified_reports: u32,
+    pub bump: u8,
+}
 
-// ============================================================================
-// ERROR CODES
-// ============================================================================
+impl ReportTally {
+    pub const INIT_SPACE: usize =
+        32 +       // reported
+        4 +        // verified_reports
+        1;         // bump
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("The computation was aborted")]
-    AbortedComputation,
-    #[msg("Cluster not set")]
-    ClusterNotSet,
-    #[msg("Username too short (minimum 3 characters)")]
-    UsernameTooShort,
-    #[msg("Username too long (maximum 32 characters)")]
-    UsernameTooLong,
-    #[msg("Username can only contain letters, numbers and underscores")]
-    InvalidUsernameFormat,
-    #[msg("Age must be between 18-99")]
-    InvalidAge,
-    #[msg("Private data too large (maximum 1000 bytes)")]
-    DataTooLarge,
-    #[msg("Preferences too large (maximum 500 bytes)")]
-    PreferencesTooLarge,
-    #[msg("Profile already exists")]
-    ProfileAlreadyExists,
-    #[msg("Invalid encrypted data")]
-    InvalidEncryptedData,
-    #[msg("Avatar is required")]
-    AvatarRequired,
-    #[msg("Location information is required")]
-    LocationRequired,
-    #[msg("Invalid encryption key")]
-    InvalidEncryptionKey,
-    #[msg("User is not authorized to perform this action")]
-    UnauthorizedUser,
-    #[msg("Invalid session")]
-    InvalidSession,
+    /// Counts one more distinct verified reporter; true once the count has
+    /// reached `AUTO_DEACTIVATION_REPORT_THRESHOLD`
+    pub fn record(&mut self) -> bool {
+        self.verified_reports = self.verified_reports.saturating_add(1);
+        self.verified_reports >= AUTO_DEACTIVATION_REPORT_THRESHOLD
+    }
 }
 
-// ============================================================================
-// MAIN PROGRAM MODULE
-// ============================================================================
+/// One page of a user's match history spilled out of `match_history`,
+/// oldest records first
+#[account]
+pub struct MatchArchive {
+    pub owner: Pubkey,
+    pub page: u32,
+    pub records: Vec<[u8; MATCH_RECORD_LEN]>,
+    pub bump: u8,
+}
 
-#[arcium_program]
-pub mod contract {
-    use super::*;
+impl MatchArchive {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        4 +        // page
+        4 + MATCH_RECORD_LEN * MATCH_ARCHIVE_PAGE_RECORDS + // records
+        1;         // bump
+}
 
-    // ========================================================================
-    // PROFILE MANAGEMENT FUNCTIONS
-    // ========================================================================
+/// Short-lived claim on a username, consumed by `create_profile` so a
+/// pending signup cannot be front-run
+#[account]
+pub struct UsernameReservation {
+    pub reserver: Pubkey,
+    pub username_hash: [u8; 32],            // See `username_hash`
+    pub expires_at: i64,
+    pub bump: u8,
+}
 
-    /// Creates a new user profile with encrypted sensitive data
-    pub fn create_profile(
-        ctx: Context<CreateProfile>,
-        profile_data: CreateProfileData,
-    ) -> Result<()> {
-        let user_profile = &mut ctx.accounts.user_profile;
-        let clock = Clock::get()?;
-        
-        // Input validation
-        require!(profile_data.username.len() >= 3, ErrorCode::UsernameTooShort);
-        require!(profile_data.username.len() <= 32, ErrorCode::UsernameTooLong);
-        require!(profile_data.age >= 18 && profile_data.age <= 99, ErrorCode::InvalidAge);
-        require!(profile_data.encrypted_private_data.len() <= 1000, ErrorCode::DataTooLarge);
-        require!(profile_data.encrypted_preferences.len() <= 500, ErrorCode::PreferencesTooLarge);
-        require!(!profile_data.avatar_url.is_empty(), ErrorCode::AvatarRequired);
-        require!(!profile_data.location_city.is_empty(), ErrorCode::LocationRequired);
-        require!(profile_data.encryption_pubkey != [0u8; 32], ErrorCode::InvalidEncryptionKey);
-        require!(
-            profile_data.username.chars().all(|c| c.is_alphanumeric() || c == '_'),
-            ErrorCode::InvalidUsernameFormat
-        );
+impl UsernameReservation {
+    pub const INIT_SPACE: usize =
+        32 +       // reserver
+        32 +       // username_hash
+        8 +        // expires_at
+        1;         // bump
 
-        // Set account metadata
-        user_profile.owner = ctx.accounts.user.key();
-        user_profile.bump = ctx.bumps.user_profile;
-        user_profile.created_at = clock.unix_timestamp;
-        user_profile.last_updated = clock.unix_timestamp;
-        user_profile.profile_version = profile_data.profile_version;
-        
-        // Set public profile information
-        user_profile.username = profile_data.username.clone();
-        user_profile.avatar_url = profile_data.avatar_url;
-        user_profile.age = profile_data.age;
-        user_profile.location_city = profile_data.location_city.clone();
-        user_profile.is_active = true;
-        
-        // Set encryption data
-        user_profile.encryption_pubkey = profile_data.encryption_pubkey;
-        user_profile.encrypted_private_data = profile_data.encrypted_private_data;
-        user_profile.encrypted_preferences = profile_data.encrypted_preferences;
-        
-        // Initialize interaction history
-        user_profile.encrypted_likes_given = Vec::new();
-        user_profile.encrypted_likes_received = Vec::new();
-        user_profile.encrypted_matches = Vec::new();
-        user_profile.total_likes_given = 0;
-        user_profile.total_likes_received = 0;
-        user_profile.total_matches = 0;
-        
-        // Emit profile creation event
-        emit!(ProfileCreatedEvent {
-            user: ctx.accounts.user.key(),
-            profile_pda: user_profile.key(),
-            username: profile_data.username,
-            age: profile_data.age,
-            location_city: profile_data.location_city,
-            timestamp: clock.unix_timestamp,
-        });
-        
-        msg!("Profile created successfully for user: {}", ctx.accounts.user.key());
-        Ok(())
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_at
     }
+}
 
-    // ========================================================================
-    // ENCRYPTED MATCHING FUNCTIONS
-    // ========================================================================
-
+/// Claim on a username by the profile currently holding it, so no two
+/// profiles share one. Keyed by `username_hash`, so names differing only in
+/// case ("Alice" and "alice") collide
+#[account]
+pub struct UsernameRegistry {
+    pub owner: Pubkey,
+    pub username_hash: [u8; 32],
+    pub bump: u8,
+}
 
-    /// Initializes an encrypted matching session between two users
-    pub fn init_match_session(
-        ctx: Context<InitMatchSession>,
-        computation_offset: u64,
-        session_id: u64,
-        user_a: Pubkey,
-        user_b: Pubkey,
-        nonce: u128,
-    ) -> Result<()> {
-        let match_session = &mut ctx.accounts.match_pair_session;
-        let clock = Clock::get()?;
-        
-        // Initialize session data
-        match_session.session_id = session_id;
-        match_session.user_a = user_a;
-        match_session.user_b = user_b;
-        match_session.nonce = nonce;
-        match_session.created_at = clock.unix_timestamp;
-        match_session.last_updated = clock.unix_timestamp;
-        match_session.is_finalized = false;
-        match_session.match_found = false;
-        match_session.bump = ctx.bumps.match_pair_session;
-        
-        // Convert public keys to u64 IDs for MPC computation
-        let user_a_bytes: [u8; 8] = user_a.key().as_ref()[0..8].try_into().unwrap();
-        let user_b_bytes: [u8; 8] = user_b.key().as_ref()[0..8].try_into().unwrap();
-        let user_a_id = u64::from_le_bytes(user_a_bytes);
-        let user_b_id = u64::from_le_bytes(user_b_bytes);
-        
-        // Prepare arguments for MPC computation
-        let args = vec![
-            Argument::PlaintextU128(nonce),
-            Argument::PlaintextU64(user_a_id),
-            Argument::PlaintextU64(user_b_id),
-            Argument::PlaintextU64(clock.unix_timestamp as u64),
-        ];
+impl UsernameRegistry {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        32 +       // username_hash
+        1;         // bump
+}
 
-        // Queue the encrypted computation
-        let session_key = match_session.key();
-        queue_computation(
-            ctx.accounts,
-            computation_offset,
-            args,
-            vec![CallbackAccount {
-                pubkey: session_key,
-                is_writable: true,
-            }],
-            None,
-        )?;
+/// Global source of session ids for `init_match_session_auto`
+#[account]
+pub struct SessionCounter {
+    pub last_session_id: u64,               // 0 until the first session is opened
+    pub bump: u8,
+}
 
-        // Emit session creation event
-        emit!(MatchSessionCreatedEvent {
-            session_id,
-            user_a,
-            user_b,
-            created_at: clock.unix_timestamp,
-        });
+impl SessionCounter {
+    pub const INIT_SPACE: usize =
+        8 +        // last_session_id
+        1;         // bump
 
-        Ok(())
+    /// Advances the counter and returns the new session id, starting at 1
+    pub fn next(&mut self) -> Result<u64> {
+        self.last_session_id = self.last_session_id
+            .checked_add(1)
+            .ok_or(ErrorCode::CounterOverflow)?;
+        Ok(self.last_session_id)
     }
+}
 
-    /// Callback for match session initialization MPC computation
-    #[arcium_callback(encrypted_ix = "init_match_session")]
-    pub fn init_match_session_callback(
-        ctx: Context<InitMatchSessionCallback>,
-        output: ComputationOutputs<InitMatchSessionOutput>,
-    ) -> Result<()> {
-        let encrypted_session = match output {
-            ComputationOutputs::Success(InitMatchSessionOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+/// Per-user monotonic nonce source for client-side encryption
+#[account]
+pub struct NonceCounter {
+    pub owner: Pubkey,
+    pub next_nonce: u128,
+    pub bump: u8,
+}
 
-        let match_session = &mut ctx.accounts.match_pair_session;
-        match_session.encrypted_match_data = encrypted_session.ciphertexts;
-        match_session.nonce = encrypted_session.nonce;
+impl NonceCounter {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        16 +       // next_nonce
+        1;         // bump
 
-        msg!("Match session initialized with encrypted data");
+    /// Accepts `nonce` only if it was never handed out before, then advances
+    pub fn consume(&mut self, nonce: u128) -> Result<()> {
+        require!(nonce >= self.next_nonce, ErrorCode::NonceReused);
+        self.next_nonce = nonce.checked_add(1).ok_or(ErrorCode::NonceReused)?;
         Ok(())
     }
+}
 
+/// Global program configuration, created once by the first admin
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,              // Pubkey::default() when no transfer is pending
+    pub min_session_ttl: i64,               // Bounds for personalized session expiry (seconds)
+    pub max_session_ttl: i64,
+    pub match_archive_window: i64,          // Seconds an unstarted match stays active
+    pub min_account_age_secs: i64,          // Profile age required before matching, 0 = none
+    pub inline_match_cap: u8,               // Match records kept in `match_history` before archiving
+    pub max_daily_likes: u16,               // Likes each user may submit per UTC day
+    pub verification_authority: Pubkey,     // Sole signer of `set_verification`, rotated by the admin
+    pub bump: u8,
+}
 
-    /// Submits an encrypted like action for a user
-    pub fn submit_like(
-        ctx: Context<SubmitLike>,
-        computation_offset: u64,
-        encrypted_user_id: [u8; 32],
-        encrypted_target_id: [u8; 32], 
-        encrypted_like_action: [u8; 32],
-        encrypted_timestamp: [u8; 32],
-        pub_key: [u8; 32],
-        nonce: u128,
-    ) -> Result<()> {
-        let match_session = &ctx.accounts.match_pair_session;
-        
-        // Validate user authorization
-        require!(
-            ctx.accounts.user.key() == match_session.user_a || 
-            ctx.accounts.user.key() == match_session.user_b,
-            ErrorCode::UnauthorizedUser
-        );
+impl ProgramConfig {
+    pub const INIT_SPACE: usize =
+        32 +       // admin
+        32 +       // pending_admin
+        8 +        // min_session_ttl
+        8 +        // max_session_ttl
+        8 +        // match_archive_window
+        8 +        // min_account_age_secs
+        1 +        // inline_match_cap
+        2 +        // max_daily_likes
+        32 +       // verification_authority
+        1;         // bump
+}
 
-        // Prepare encrypted arguments for MPC computation
-        let args = vec![
-            Argument::ArcisPubkey(pub_key),
-            Argument::PlaintextU128(nonce),
-            Argument::EncryptedU8(encrypted_user_id),
-            Argument::EncryptedU8(encrypted_target_id), 
-            Argument::EncryptedU8(encrypted_like_action),
-            Argument::EncryptedU8(encrypted_timestamp),
-            Argument::PlaintextU128(match_session.nonce),
-            Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
-        ];
+/// Platform-wide aggregate counters, maintained incrementally
+#[account]
+pub struct PlatformStats {
+    pub total_profiles: u64,
+    pub active_profiles: u64,
+    pub total_sessions: u64,
+    pub finalized_sessions: u64,
+    pub total_matches: u64,
+    pub last_rebuilt_at: i64,
+    pub bump: u8,
+}
 
-        // Queue encrypted like computation
-        queue_computation(
-            ctx.accounts,
-            computation_offset,
-            args,
-            vec![CallbackAccount {
-                pubkey: match_session.key(),
-                is_writable: true,
-            }],
-            None,
-        )?;
+impl PlatformStats {
+    pub const INIT_SPACE: usize =
+        8 +        // total_profiles
+        8 +        // active_profiles
+        8 +        // total_sessions
+        8 +        // finalized_sessions
+        8 +        // total_matches
+        8 +        // last_rebuilt_at
+        1;         // bump
+}
 
-        Ok(())
-    }
+/// A participant's encrypted consent and disclosable fields for a matched session
+#[account]
+pub struct DisclosureConsent {
+    pub session: Pubkey,
+    pub owner: Pubkey,
+    pub encryption_pubkey: [u8; 32],
+    pub nonce: u128,
+    pub encrypted_input: [[u8; 32]; 3],     // consent, exact_age, income_bracket
+    pub submitted_at: i64,
+    pub bump: u8,
+}
 
-    #[arcium_callback(encrypted_ix = "submit_like")]
-    pub fn submit_like_callback(
-        ctx: Context<SubmitLikeCallback>,
-        output: ComputationOutputs<SubmitLikeOutput>,
-    ) -> Result<()> {
-        let (updated_session, status_flag) = match output {
-            ComputationOutputs::Success(SubmitLikeOutput { field_0 }) => {
-                (field_0.field_0, field_0.field_1)
-            },
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+impl DisclosureConsent {
+    pub const INIT_SPACE: usize =
+        32 +       // session
+        32 +       // owner
+        32 +       // encryption_pubkey
+        16 +       // nonce
+        32 * 3 +   // encrypted_input (3 x 32 bytes)
+        8 +        // submitted_at
+        1;         // bump
 
-        let match_session = &mut ctx.accounts.match_pair_session;
-        match_session.encrypted_match_data = updated_session.ciphertexts;
-        match_session.nonce = updated_session.nonce;
-        match_session.last_updated = Clock::get()?.unix_timestamp;
-
-        match status_flag {
-            1 => {
-                emit!(LikeSubmittedEvent {
-                    session_id: match_session.session_id,
-                    timestamp: match_session.last_updated,
-                });
-                msg!("Like action recorded successfully");
-            },
-            2 => {
-                msg!("Mutual interest detected! Session ready for verification");
-                
-                emit!(MutualInterestDetectedEvent {
-                    session_id: match_session.session_id,
-                    timestamp: match_session.last_updated,
-                });
-            },
-            _ => {
-                msg!("Like action not processed (possibly duplicate or invalid)");
-            }
-        }
+    /// Byte offset of `encrypted_input` within the account data
+    pub const ENCRYPTED_INPUT_OFFSET: u32 = 8 + 32 + 32 + 32 + 16;
+}
 
-        Ok(())
-    }
+/// Latest results of a user's batch compatibility run
+#[account]
+pub struct CompatibilityBatch {
+    pub owner: Pubkey,
+    pub candidate_count: u8,
+    pub scores: [u8; COMPATIBILITY_BATCH_SIZE],
+    pub scored_at: i64,
+    pub bump: u8,
+    pub last_processed_computation: Pubkey, // Computation account whose scores were last applied
+}
 
+impl CompatibilityBatch {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        1 +        // candidate_count
+        COMPATIBILITY_BATCH_SIZE + // scores
+        8 +        // scored_at
+        1 +        // bump
+        32;        // last_processed_computation
+}
 
-    pub fn check_mutual_match(
-        ctx: Context<CheckMutualMatch>,
-        computation_offset: u64,
-    ) -> Result<()> {
-        let match_session = &ctx.accounts.match_pair_session;
-        let clock = Clock::get()?;
+/// A user's own preferences and profile encrypted for MPC, read by the
+/// match check to enforce dealbreakers
+#[account]
+pub struct MatchingCiphertexts {
+    pub owner: Pubkey,
+    pub encryption_pubkey: [u8; 32],
+    pub nonce: u128,
+    pub encrypted_data: [[u8; 32]; MATCHING_DATA_CIPHERTEXTS], // prefs, then profile
+    pub updated_at: i64,
+    pub bump: u8,
+}
 
-        let args = vec![
-            Argument::PlaintextU128(match_session.nonce),
-            Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
-            Argument::PlaintextU64(clock.unix_timestamp as u64),
-        ];
+impl MatchingCiphertexts {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        32 +       // encryption_pubkey
+        16 +       // nonce
+        32 * MATCHING_DATA_CIPHERTEXTS + // encrypted_data
+        8 +        // updated_at
+        1;         // bump
 
-        queue_computation(
-            ctx.accounts,
-            computation_offset,
-            args,
-            vec![CallbackAccount {
-                pubkey: match_session.key(),
-                is_writable: true,
-            }],
-            None,
-        )?;
+    /// Byte offset of `encrypted_data` within the account data
+    pub const ENCRYPTED_DATA_OFFSET: u32 = 8 + 32 + 32 + 16;
+}
 
-        Ok(())
-    }
+/// Encrypted aggregate of the star ratings a user has received
+#[account]
+pub struct Reputation {
+    pub owner: Pubkey,
+    pub nonce: u128,
+    pub encrypted_reputation: [[u8; 32]; 2], // rating_sum, rating_count (MXE-encrypted)
+    pub initialized: bool,                   // false until the first rating is aggregated
+    pub pending_since: i64,                  // 0 when no rating is in flight
+    pub updated_at: i64,
+    pub bump: u8,
+}
 
-    #[arcium_callback(encrypted_ix = "check_mutual_match")]
-    pub fn check_mutual_match_callback(
-        ctx: Context<CheckMutualMatchCallback>,
-        output: ComputationOutputs<CheckMutualMatchOutput>,
-    ) -> Result<()> {
-        let match_result = match output {
-            ComputationOutputs::Success(CheckMutualMatchOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
+impl Reputation {
+    pub const INIT_SPACE: usize =
+        32 +       // owner
+        16 +       // nonce
+        32 * 2 +   // encrypted_reputation
+        1 +        // initialized
+        8 +        // pending_since
+        8 +        // updated_at
+        1;         // bump
 
-        let match_session = &mut ctx.accounts.match_pair_session;
-        match_session.is_finalized = true;
+    /// Byte offset of `encrypted_reputation` within the account data
+    pub const ENCRYPTED_REPUTATION_OFFSET: u32 = 8 + 32 + 16;
+}
 
-        let is_mutual_match = match_result.field_0;
-        let session_status = match_result.field_1;
-        let _match_timestamp = match_result.field_2;
+// ============================================================================
+// CONTEXT STRUCTS
+// ============================================================================
 
-        if is_mutual_match {
-            match_session.match_found = true;
-            
-            emit!(MutualMatchFoundEvent {
-                session_id: match_session.session_id,
-                user_a: match_session.user_a,
-                user_b: match_session.user_b,
-                matched_at: Clock::get()?.unix_timestamp,
-                can_start_conversation: true,
-            });
+/// Context for creating a user profile
+#[derive(Accounts)]
+#[instruction(profile_data: CreateProfileData)]
+pub struct CreateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = migration::self_describing_len(
+            8 + UserProfile::COMPACT_SPACE +
+                profile_data.encrypted_private_data.len() +
+                profile_data.encrypted_preferences.len() +
+                UserProfile::photo_space(&profile_data.photo_urls),
+        ),
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
 
-            msg!("Mutual match confirmed! Both users liked each other!");
-        } else {
-            match_session.match_found = false;
-            
-            emit!(NoMutualMatchEvent {
-                session_id: match_session.session_id,
-                finalized_at: Clock::get()?.unix_timestamp,
-            });
+    #[account(
+        mut,
+        seeds = [b"username_reservation", username_hash(&profile_data.username).as_ref()],
+        bump = username_reservation.bump,
+        close = user
+    )]
+    pub username_reservation: Account<'info, UsernameReservation>,
 
-            let status_msg = match session_status {
-                0 => "Pending - only one user has acted",
-                2 => "No match - both users passed or no actions",
-                _ => "Unknown status"
-            };
-            msg!("No mutual match found - {}", status_msg);
-        }
+    // Left unclaimed when the previous holder renamed or closed their profile
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UsernameRegistry::INIT_SPACE,
+        seeds = [b"username", username_hash(&profile_data.username).as_ref()],
+        bump
+    )]
+    pub username_registry: Account<'info, UsernameRegistry>,
 
-        msg!("Match session finalized - session_id: {}", match_session.session_id);
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    
+    pub system_program: Program<'info, System>,
+}
 
-        Ok(())
-    }
+/// Context for changing a profile's public fields in place
+#[derive(Accounts)]
+#[instruction(update: UpdateProfileData)]
+pub struct UpdateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
 
-    // ========================================================================
-    // MPC COMPUTATION DEFINITION INITIALIZATION FUNCTIONS
-    // ========================================================================
+    /// CHECK: Registry entry of the current username, released on a rename;
+    /// may not exist for profiles created before the registry
+    #[account(
+        mut,
+        seeds = [b"username", username_hash(&user_profile.username).as_ref()],
+        bump
+    )]
+    pub old_username_registry: UncheckedAccount<'info>,
 
-    /// Dummy function for compatibility
-    pub fn init_add_together_comp_def(_ctx: Context<InitCompDef>) -> Result<()> {
-        Ok(())
-    }
+    /// Required only when the username changes
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UsernameRegistry::INIT_SPACE,
+        seeds = [b"username", username_hash(update.username.as_deref().unwrap_or_default()).as_ref()],
+        bump
+    )]
+    pub new_username_registry: Option<Account<'info, UsernameRegistry>>,
 
-    /// Initialize computation definition for match session initialization
-    pub fn init_init_match_session_comp_def(ctx: Context<InitInitMatchSessionCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
-        Ok(())
-    }
+    pub system_program: Program<'info, System>,
+}
 
-    /// Initialize computation definition for like submission
-    pub fn init_submit_like_comp_def(ctx: Context<InitSubmitLikeCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
-        Ok(())
-    }
+/// Context for editing the caller's photo gallery
+#[derive(Accounts)]
+pub struct ManagePhotos<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
 
-    /// Initialize computation definition for mutual match checking
-    pub fn init_check_mutual_match_comp_def(ctx: Context<InitCheckMutualMatchCompDef>) -> Result<()> {
-        init_comp_def(ctx.accounts, true, 0, None, None)?;
-        Ok(())
-    }
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub system_program: Program<'info, System>,
 }
 
-// ============================================================================
-// ACCOUNT VALIDATION STRUCTS FOR MPC OPERATIONS
-// ============================================================================
+/// Context for reserving a username ahead of `create_profile`
+#[derive(Accounts)]
+#[instruction(username_hash: [u8; 32])]
+pub struct ReserveUsername<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
 
+    #[account(
+        init,
+        payer = user,
+        space = 8 + UsernameReservation::INIT_SPACE,
+        seeds = [b"username_reservation", username_hash.as_ref()],
+        bump
+    )]
+    pub username_reservation: Account<'info, UsernameReservation>,
 
-#[queue_computation_accounts("init_match_session", payer)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for releasing a username reservation, refunding its reserver
 #[derive(Accounts)]
-#[instruction(computation_offset: u64, session_id: u64, user_a: Pubkey, user_b: Pubkey)]
-pub struct InitMatchSession<'info> {
+pub struct ReleaseUsernameReservation<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"username_reservation", username_reservation.username_hash.as_ref()],
+        bump = username_reservation.bump,
+        has_one = reserver @ ErrorCode::InvalidUsernameReservation,
+        close = reserver
+    )]
+    pub username_reservation: Account<'info, UsernameReservation>,
+
+    /// CHECK: Rent refund destination, bound to the reservation by `has_one`
+    #[account(mut)]
+    pub reserver: UncheckedAccount<'info>,
+}
+
+/// Context for closing a user's finalized sessions (passed as remaining accounts)
+#[derive(Accounts)]
+pub struct CloseFinalizedSessions<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+/// Context for a participant closing one finalized session
+#[derive(Accounts)]
+pub struct CloseMatchSession<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+}
+
+/// Context for the admin granting a discovery boost
+#[derive(Accounts)]
+pub struct GrantBoost<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::UnauthorizedUser
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.owner.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for the verification authority setting a profile's badge
+#[derive(Accounts)]
+pub struct SetVerification<'info> {
+    pub verification_authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = verification_authority @ ErrorCode::UnauthorizedUser
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.owner.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for reading a profile's counters and flags
+#[derive(Accounts)]
+pub struct GetUserStats<'info> {
+    #[account(
+        seeds = [b"user_profile", user_profile.owner.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for reading or clearing a profile's boost
+#[derive(Accounts)]
+pub struct BoostStatusAccounts<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_profile.owner.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for pausing or resuming new match sessions for a user
+#[derive(Accounts)]
+pub struct SetMatchingPaused<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for the owner marking themselves as online
+#[derive(Accounts)]
+pub struct Heartbeat<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for the owner deactivating or reactivating their profile
+#[derive(Accounts)]
+pub struct SetProfileActive<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+}
+
+/// Context for deleting the caller's profile and reclaiming its rent
+#[derive(Accounts)]
+pub struct CloseProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        close = user
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    /// CHECK: Registry entry of the profile's username, released with it;
+    /// may not exist for profiles created before the registry
+    #[account(
+        mut,
+        seeds = [b"username", username_hash(&user_profile.username).as_ref()],
+        bump
+    )]
+    pub username_registry: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+}
+
+/// Context for proving control of a profile to an off-chain backend
+#[derive(Accounts)]
+pub struct ProveProfileOwnership<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for replacing a user's encrypted matching preferences
+#[derive(Accounts)]
+pub struct SetPreferences<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for setting a user's encrypted icebreaker answer
+#[derive(Accounts)]
+pub struct SetIcebreakerAnswer<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for authorizing a relayer to submit likes
+#[derive(Accounts)]
+pub struct SetLikeDelegate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LikeDelegate::INIT_SPACE,
+        seeds = [b"like_delegate", user.key().as_ref()],
+        bump
+    )]
+    pub like_delegate: Account<'info, LikeDelegate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for revoking a relayer authorization
+#[derive(Accounts)]
+pub struct RevokeLikeDelegate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"like_delegate", user.key().as_ref()],
+        bump = like_delegate.bump
+    )]
+    pub like_delegate: Account<'info, LikeDelegate>,
+}
+
+/// Context for adding a user to the caller's blocklist
+#[derive(Accounts)]
+pub struct AddBlock<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Blocklist::INIT_SPACE,
+        seeds = [b"blocklist", user.key().as_ref()],
+        bump
+    )]
+    pub blocklist: Account<'info, Blocklist>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for removing a user from the caller's blocklist
+#[derive(Accounts)]
+pub struct RemoveBlock<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"blocklist", user.key().as_ref()],
+        bump = blocklist.bump
+    )]
+    pub blocklist: Account<'info, Blocklist>,
+}
+
+/// Context for reporting another user's profile to moderators
+#[derive(Accounts)]
+#[instruction(reported: Pubkey)]
+pub struct ReportProfile<'info> {
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    #[account(
+        seeds = [b"user_profile", reporter.key().as_ref()],
+        bump = reporter_profile.bump
+    )]
+    pub reporter_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", reported.as_ref()],
+        bump = reported_profile.bump
+    )]
+    pub reported_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        init_if_needed,
+        payer = reporter,
+        space = 8 + ProfileReport::INIT_SPACE,
+        seeds = [b"report", reporter.key().as_ref(), reported.as_ref()],
+        bump
+    )]
+    pub profile_report: Account<'info, ProfileReport>,
+
+    #[account(
+        init_if_needed,
+        payer = reporter,
+        space = 8 + ReportTally::INIT_SPACE,
+        seeds = [b"report_tally", reported.as_ref()],
+        bump
+    )]
+    pub report_tally: Account<'info, ReportTally>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for computing a user's recommended session expiry
+#[derive(Accounts)]
+pub struct GetRecommendedSessionExpiry<'info> {
+    #[account(
+        seeds = [b"user_profile", user_profile.owner.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+/// Context for reading a profile view
+#[derive(Accounts)]
+pub struct GetProfileView<'info> {
+    #[account(
+        seeds = [b"user_profile", user_profile.owner.as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    /// Viewer; the owner and matched participants see private fields
+    pub caller: Option<Signer<'info>>,
+
+    /// A finalized, matched session between the caller and the profile owner
+    #[account(
+        seeds = [b"pair_session", match_proof.pair_low().as_ref(), match_proof.pair_high().as_ref()],
+        bump = match_proof.bump
+    )]
+    pub match_proof: Option<Account<'info, MatchPairSession>>,
+}
+
+/// Context for setting a profile's privacy level
+#[derive(Accounts)]
+pub struct SetProfilePrivacy<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for setting a profile's inbound like policy
+#[derive(Accounts)]
+pub struct SetLikePolicy<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+}
+
+/// Context for moving the caller's profile to the current account layout
+#[derive(Accounts)]
+pub struct MigrateProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump
+    )]
+    /// CHECK: May hold any past layout, decoded by `migration::decode_profile`
+    pub user_profile: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for reserving more space for the caller's encrypted profile data
+#[derive(Accounts)]
+pub struct GrowProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for archiving the oldest records of the caller's match history
+#[derive(Accounts)]
+pub struct ArchiveMatchRecords<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    // The page the next spilled record lands in
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + MatchArchive::INIT_SPACE,
+        seeds = [
+            b"match_archive",
+            user.key().as_ref(),
+            (user_profile.archived_match_records / MATCH_ARCHIVE_PAGE_RECORDS as u32).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub match_archive: Account<'info, MatchArchive>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for reading one page of the caller's archived match history
+#[derive(Accounts)]
+#[instruction(cursor: Option<u32>)]
+pub struct GetMatchArchive<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"user_profile", user.key().as_ref()],
+        bump = user_profile.bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(
+        seeds = [b"match_archive", user.key().as_ref(), cursor.unwrap_or(0).to_le_bytes().as_ref()],
+        bump = match_archive.bump
+    )]
+    pub match_archive: Account<'info, MatchArchive>,
+}
+
+/// Context for reading the caller's own blocklist
+#[derive(Accounts)]
+pub struct GetBlocklist<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"blocklist", user.key().as_ref()],
+        bump = blocklist.bump
+    )]
+    pub blocklist: Account<'info, Blocklist>,
+}
+
+/// Context for reading a session's in-flight computation
+#[derive(Accounts)]
+pub struct GetPendingComputation<'info> {
+    #[account(
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+}
+
+/// Context for a participant marking a session's likes as seen
+#[derive(Accounts)]
+pub struct MarkLikeSeen<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+}
+
+/// Context for a participant acting on their own session
+#[derive(Accounts)]
+pub struct SessionParticipant<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+}
+
+/// Context for archiving a stale match; anyone may call it once the window passes
+#[derive(Accounts)]
+pub struct ArchiveStaleMatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+/// Context for expiring an idle session; anyone may call it once the window passes
+#[derive(Accounts)]
+pub struct ExpireMatchSession<'info> {
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+}
+
+/// Context for checking whether two users matched in a given session
+#[derive(Accounts)]
+pub struct HaveMatched<'info> {
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+}
+
+/// Context for checking whether the caller and a target have blocked each other
+#[derive(Accounts)]
+#[instruction(target: Pubkey)]
+pub struct IsBlocked<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"blocklist", user.key().as_ref()],
+        bump = user_blocklist.bump
+    )]
+    pub user_blocklist: Option<Account<'info, Blocklist>>,
+
+    #[account(
+        seeds = [b"blocklist", target.as_ref()],
+        bump = target_blocklist.bump
+    )]
+    pub target_blocklist: Option<Account<'info, Blocklist>>,
+}
+
+/// Context for submitting post-match disclosure consent
+#[derive(Accounts)]
+pub struct SubmitDisclosureConsent<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + DisclosureConsent::INIT_SPACE,
+        seeds = [b"disclosure", match_pair_session.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub disclosure_consent: Account<'info, DisclosureConsent>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for staging a chunk of the user's matching ciphertexts
+#[derive(Accounts)]
+pub struct StageMatchingCiphertexts<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + MatchingCiphertexts::INIT_SPACE,
+        seeds = [b"matching_ciphertexts", user.key().as_ref()],
+        bump
+    )]
+    pub matching_ciphertexts: Box<Account<'info, MatchingCiphertexts>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for creating a user's nonce counter
+#[derive(Accounts)]
+pub struct InitNonceCounter<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + NonceCounter::INIT_SPACE,
+        seeds = [b"nonce_counter", user.key().as_ref()],
+        bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for reading a user's next safe nonce
+#[derive(Accounts)]
+pub struct GetNextNonce<'info> {
+    #[account(
+        seeds = [b"nonce_counter", nonce_counter.owner.as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+}
+
+/// Context for creating the program config and platform stats; only the
+/// program's upgrade authority may create them
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::UnauthorizedUser
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PlatformStats::INIT_SPACE,
+        seeds = [b"platform_stats"],
+        bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for recomputing platform stats from profile/session accounts
+#[derive(Accounts)]
+pub struct RebuildStats<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::UnauthorizedUser
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+}
+
+/// Context for deactivating a batch of profiles passed as remaining accounts
+#[derive(Accounts)]
+pub struct BulkDeactivate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::UnauthorizedUser
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+}
+
+/// Context for admin-only config changes
+#[derive(Accounts)]
+pub struct AdminConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::UnauthorizedUser
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+/// Context for the pending admin to claim the role
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = pending_admin @ ErrorCode::UnauthorizedUser
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+/// Context for reading cached compatibility batches (passed as remaining accounts)
+#[derive(Accounts)]
+pub struct GetCompatibilityScoresBatch<'info> {
+    pub user: Signer<'info>,
+}
+
+/// Context for validating profile input without creating an account
+#[derive(Accounts)]
+pub struct ValidateProfileInput<'info> {
+    pub user: Signer<'info>,
+}
+
+/// Context for initializing computation definitions
+#[derive(Accounts)]
+pub struct InitCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+/// Event emitted when a username is reserved for a pending signup
+#[event]
+pub struct UsernameReservedEvent {
+    pub reserver: Pubkey,
+    pub username_hash: [u8; 32],
+    pub expires_at: i64,
+}
+
+/// Event emitted when a user profile is created
+#[event]
+pub struct ProfileCreatedEvent {
+    pub user: Pubkey,
+    pub profile_pda: Pubkey,
+    pub username: String,
+    pub age: u8,
+    pub location_city: String,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a profile's public fields change
+#[event]
+pub struct ProfileUpdatedEvent {
+    pub user: Pubkey,
+    pub username: String,
+    pub avatar_url: String,
+    pub age: u8,
+    pub location_city: String,
+    pub bio: String,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a profile's photo gallery is added to, trimmed or reordered
+#[event]
+pub struct PhotosUpdatedEvent {
+    pub user: Pubkey,
+    pub avatar_url: String,
+    pub photo_count: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a match session is created
+#[event]
+pub struct MatchSessionCreatedEvent {
+    pub session_id: u64,
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub created_at: i64,
+}
+
+/// Event emitted when a like is submitted
+#[event]
+pub struct LikeSubmittedEvent {
+    pub session_id: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a super like is recorded on a session
+#[event]
+pub struct SuperLikeSubmittedEvent {
+    pub session_id: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a participant takes back their like
+#[event]
+pub struct LikeWithdrawnEvent {
+    pub session_id: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when mutual interest is detected
+#[event]
+pub struct MutualInterestDetectedEvent {
+    pub session_id: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a session's state is re-encrypted under a fresh nonce
+#[event]
+pub struct SessionNonceRotatedEvent {
+    pub session_id: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a session is moved to the current ciphertext layout
+#[event]
+pub struct SessionMigratedEvent {
+    pub session_id: u64,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a profile account is moved to the current layout
+#[event]
+pub struct ProfileMigratedEvent {
+    pub owner: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a profile account is grown to hold more encrypted data
+#[event]
+pub struct ProfileGrownEvent {
+    pub owner: Pubkey,
+    pub space: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted when older match records are moved into an archive page
+#[event]
+pub struct MatchesArchivedEvent {
+    pub owner: Pubkey,
+    pub page: u32,
+    pub moved: u32,
+    pub archived_match_records: u32,
+}
+
+/// Event emitted when a user reports another user's profile
+#[event]
+pub struct ProfileReportedEvent {
+    pub reporter: Pubkey,
+    pub reported: Pubkey,
+    pub reason_code: u8,
+    pub timestamp: i64,
+}
+
+/// Event emitted when enough distinct verified reporters deactivate a profile
+#[event]
+pub struct ProfileAutoDeactivatedEvent {
+    pub user: Pubkey,
+    pub verified_reports: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a report is rejected by the anti-abuse rules
+#[event]
+pub struct ReportThrottledEvent {
+    pub reporter: Pubkey,
+    pub reported: Pubkey,
+    pub reason: u8,                         // REPORT_THROTTLE_* code
+    pub timestamp: i64,
+}
+
+/// Event emitted when a participant's client marks a session's likes as seen
+#[event]
+pub struct LikeSeenEvent {
+    pub session_id: u64,
+    pub seen_at: i64,
+}
+
+/// Event emitted when an unstarted match is moved to the archive
+#[event]
+pub struct MatchArchivedEvent {
+    pub session_id: u64,
+    pub archived_at: i64,
+}
+
+/// Event emitted when an idle session is finalized without a match
+#[event]
+pub struct SessionExpiredEvent {
+    pub session_id: u64,
+    pub expired_at: i64,
+}
+
+/// Event emitted when a participant revives an archived match
+#[event]
+pub struct MatchUnarchivedEvent {
+    pub session_id: u64,
+    pub unarchived_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a profile owner answers a backend auth challenge
+#[event]
+pub struct ProfileOwnershipProvenEvent {
+    pub user: Pubkey,
+    pub nonce: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Event emitted when a finalized, unmatched session is re-opened
+#[event]
+pub struct RekindleEvent {
+    pub session_id: u64,
+    pub initiated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a mutual match is found
+#[event]
+pub struct MutualMatchFoundEvent {
+    pub session_id: u64,
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub matched_at: i64,
+    pub can_start_conversation: bool,
+    pub total_compute_fees: u64,            // Lamports of MPC fees the session incurred
+    pub confidence: u8,                     // Mean like intent of the pair, 0-100
+    pub compatibility_score: u8,            // Core two-sided compatibility, 0-100
+}
+
+/// Event emitted when no mutual match is found
+#[event]
+pub struct NoMutualMatchEvent {
+    pub session_id: u64,
+    pub finalized_at: i64,
+    pub total_compute_fees: u64,            // Lamports of MPC fees the session incurred
+}
+
+/// Event emitted when a participant submits disclosure consent
+#[event]
+pub struct DisclosureConsentSubmittedEvent {
+    pub session_id: u64,
+    pub user: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted with the re-encrypted post-match disclosures
+#[event]
+pub struct MatchedDataRevealedEvent {
+    pub session_id: u64,
+    pub both_consented: bool,
+    pub user_a_nonce: u128,
+    pub user_a_ciphertexts: [[u8; 32]; 2],  // user_b's data, encrypted to user_a
+    pub user_b_nonce: u128,
+    pub user_b_ciphertexts: [[u8; 32]; 2],  // user_a's data, encrypted to user_b
+    pub timestamp: i64,
+}
+
+/// Event emitted with both icebreaker answers re-encrypted for the matched pair
+#[event]
+pub struct IcebreakersRevealedEvent {
+    pub session_id: u64,
+    pub is_mutual_match: bool,
+    pub user_a_nonce: u128,
+    pub user_a_ciphertexts: [[u8; 32]; 4],  // user_b's answer, encrypted to user_a
+    pub user_b_nonce: u128,
+    pub user_b_ciphertexts: [[u8; 32]; 4],  // user_a's answer, encrypted to user_b
+    pub timestamp: i64,
+}
+
+/// Event emitted when a participant's rating of their match has been processed
+#[event]
+pub struct FeedbackSubmittedEvent {
+    pub session_id: u64,
+    pub rater: Pubkey,
+    pub accepted: bool,                     // false if the rating was outside 1-5
+    pub timestamp: i64,
+}
+
+/// Event emitted when fields feeding compatibility change, so cached scores
+/// for the user's pairs can be invalidated and recomputed
+#[event]
+pub struct MatchingDataChangedEvent {
+    pub user: Pubkey,
+    pub affected_fields: u8,                // MATCHING_FIELD_* bitmask
+    pub timestamp: i64,
+}
+
+/// Event emitted when a profile receives a discovery boost
+#[event]
+pub struct BoostGrantedEvent {
+    pub user: Pubkey,
+    pub boost_until: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the verification authority sets or clears a badge
+#[event]
+pub struct ProfileVerifiedEvent {
+    pub user: Pubkey,
+    pub verified: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when an elapsed boost is cleared
+#[event]
+pub struct BoostExpiredEvent {
+    pub user: Pubkey,
+    pub expired_at: i64,
+}
+
+/// Event emitted when a user pauses or resumes new match sessions
+#[event]
+pub struct MatchingPausedEvent {
+    pub user: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user deactivates their profile
+#[event]
+pub struct ProfileDeactivatedEvent {
+    pub user: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user reactivates their profile
+#[event]
+pub struct ProfileReactivatedEvent {
+    pub user: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user deletes their profile
+#[event]
+pub struct ProfileClosedEvent {
+    pub user: Pubkey,
+    pub profile_pda: Pubkey,
+    pub closed_at: i64,
+}
+
+/// Event emitted when a participant closes a finalized session
+#[event]
+pub struct MatchSessionClosedEvent {
+    pub session_id: u64,
+    pub closed_at: i64,
+}
+
+/// Event emitted when a user closes finalized sessions in bulk
+#[event]
+pub struct SessionsClosedEvent {
+    pub user: Pubkey,
+    pub count: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted by every comp-def init, including re-runs against an existing definition
+#[event]
+pub struct CompDefInitializedEvent {
+    pub offset: u32,
+    pub name: String,
+    pub newly_created: bool,
+}
+
+/// Event emitted when the program config is initialized
+#[event]
+pub struct ConfigInitializedEvent {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the admin proposes a successor
+#[event]
+pub struct AdminProposedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a pending admin transfer is cancelled
+#[event]
+pub struct AdminTransferCancelledEvent {
+    pub admin: Pubkey,
+    pub cancelled_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the pending admin accepts the role
+#[event]
+pub struct AdminChangedEvent {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted after a batch of accounts is folded into platform stats
+#[event]
+pub struct StatsRebuiltEvent {
+    pub processed: u32,
+    pub total_profiles: u64,
+    pub total_sessions: u64,
+    pub total_matches: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted after a moderation sweep deactivates a batch of profiles
+#[event]
+pub struct BulkDeactivatedEvent {
+    pub admin: Pubkey,
+    pub count: u32,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a pairwise compatibility computation completes. Each
+/// breakdown is encrypted to its user's staged matching key
+#[event]
+pub struct CompatibilityCalculatedEvent {
+    pub user_a: Pubkey,
+    pub user_b: Pubkey,
+    pub score: u8,
+    pub user_a_breakdown: [[u8; 32]; 5],
+    pub user_a_breakdown_nonce: u128,
+    pub user_b_breakdown: [[u8; 32]; 5],
+    pub user_b_breakdown_nonce: u128,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a batch compatibility computation completes
+#[event]
+pub struct CompatibilityBatchScoredEvent {
+    pub owner: Pubkey,
+    pub candidate_count: u8,
+    pub scores: [u8; COMPATIBILITY_BATCH_SIZE],
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user authorizes a like relayer
+#[event]
+pub struct LikeDelegateSetEvent {
+    pub user: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user revokes their like relayer
+#[event]
+pub struct LikeDelegateRevokedEvent {
+    pub user: Pubkey,
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user blocks another user
+#[event]
+pub struct UserBlockedEvent {
+    pub user: Pubkey,
+    pub target: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a user lifts a block
+#[event]
+pub struct UserUnblockedEvent {
+    pub user: Pubkey,
+    pub target: Pubkey,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// CONSTANTS
+// ============================================================================
+
+/// Computation definition offsets for MPC operations
+const COMP_DEF_OFFSET_INIT_MATCH_SESSION: u32 = comp_def_offset("init_match_session");
+const COMP_DEF_OFFSET_SUBMIT_LIKE: u32 = comp_def_offset("submit_like");
+const COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH: u32 = comp_def_offset("check_mutual_match");
+const COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY: u32 = comp_def_offset("calculate_compatibility");
+const COMP_DEF_OFFSET_REVEAL_MATCHED_DATA: u32 = comp_def_offset("reveal_matched_data");
+const COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY_BATCH: u32 = comp_def_offset("calculate_compatibility_batch");
+const COMP_DEF_OFFSET_REVEAL_ICEBREAKERS: u32 = comp_def_offset("reveal_icebreakers");
+const COMP_DEF_OFFSET_ROTATE_SESSION_NONCE: u32 = comp_def_offset("rotate_session_nonce");
+const COMP_DEF_OFFSET_SUBMIT_FEEDBACK: u32 = comp_def_offset("submit_feedback");
+const COMP_DEF_OFFSET_WITHDRAW_LIKE: u32 = comp_def_offset("withdraw_like");
+const COMP_DEF_OFFSET_SUBMIT_PASS: u32 = comp_def_offset("submit_pass");
+
+/// Seconds in a day, used by session lifecycle math
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Seconds a username reservation holds before anyone may release it
+pub const USERNAME_RESERVATION_TTL: i64 = 5 * 60;
+
+/// Seconds after which an in-flight computation without a callback may be replaced
+pub const PENDING_COMPUTATION_TIMEOUT: i64 = 10 * 60;
+
+/// Default bounds for personalized session expiry. There is no single
+/// session TTL: `expire_match_session` uses the participants' recommended TTL
+/// within these bounds, so an active pair keeps a session for up to 14 days
+/// and one idle for over a week loses it after 3, rather than a flat 7 days.
+pub const DEFAULT_MIN_SESSION_TTL: i64 = 3 * SECONDS_PER_DAY;
+pub const DEFAULT_MAX_SESSION_TTL: i64 = 14 * SECONDS_PER_DAY;
+
+/// Default time a match can go without a conversation before it is archivable
+pub const DEFAULT_MATCH_ARCHIVE_WINDOW: i64 = 30 * SECONDS_PER_DAY;
+
+/// Seconds after archiving during which a participant can revive the match
+pub const UNARCHIVE_GRACE_PERIOD: i64 = 14 * SECONDS_PER_DAY;
+
+/// Why a session was finalized
+pub const FINALIZATION_REASON_CHECKED: u8 = 0;     // check_mutual_match ran
+pub const FINALIZATION_REASON_ARCHIVED: u8 = 1;    // stale match moved to the archive
+pub const FINALIZATION_REASON_EXPIRED: u8 = 2;     // idle past the expiry window
+pub const FINALIZATION_REASON_PASSED: u8 = 3;      // a participant passed
+
+/// All computation definition offsets, checked for collisions at compile time
+const COMP_DEF_OFFSETS: [u32; 11] = [
+    COMP_DEF_OFFSET_INIT_MATCH_SESSION,
+    COMP_DEF_OFFSET_SUBMIT_LIKE,
+    COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH,
+    COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY,
+    COMP_DEF_OFFSET_REVEAL_MATCHED_DATA,
+    COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY_BATCH,
+    COMP_DEF_OFFSET_REVEAL_ICEBREAKERS,
+    COMP_DEF_OFFSET_ROTATE_SESSION_NONCE,
+    COMP_DEF_OFFSET_WITHDRAW_LIKE,
+    COMP_DEF_OFFSET_SUBMIT_PASS,
+    COMP_DEF_OFFSET_SUBMIT_FEEDBACK,
+];
+
+// A collision would silently route one circuit's computations to another's definition
+const _: () = {
+    let mut i = 0;
+    while i < COMP_DEF_OFFSETS.len() {
+        let mut j = i + 1;
+        while j < COMP_DEF_OFFSETS.len() {
+            assert!(COMP_DEF_OFFSETS[i] != COMP_DEF_OFFSETS[j], "computation definition offsets collide");
+            j += 1;
+        }
+        i += 1;
+    }
+};
+
+/// Optional compatibility components passed to the circuit as `scoring_flags`
+pub const SCORING_FLAG_ZODIAC: u8 = 1 << 0;
+pub const SCORING_FLAG_PERSONALITY: u8 = 1 << 1;
+
+/// Candidates scored per `calculate_compatibility_batch` computation
+pub const COMPATIBILITY_BATCH_SIZE: usize = 8;
+
+/// Seconds before cached batch scores are reported as stale
+pub const COMPATIBILITY_CACHE_TTL: i64 = SECONDS_PER_DAY;
+
+/// Ciphertexts in a user's matching data: 22 preference fields, 19 profile fields
+pub const MATCHING_DATA_CIPHERTEXTS: usize = 22 + 19;
+
+/// Maximum photos in a profile's gallery, avatar included
+pub const MAX_PHOTOS: usize = 6;
+
+/// Maximum length of an avatar URL in bytes
+pub const MAX_AVATAR_URL_LEN: usize = 196;
+
+/// Maximum length of one photo URL, the same as the avatar's
+pub const MAX_PHOTO_URL_LEN: usize = MAX_AVATAR_URL_LEN;
+
+/// Maximum length of a profile bio in bytes
+pub const MAX_BIO_LEN: usize = 256;
+
+/// Stored icebreaker answer size: 16-byte nonce + 4 ciphertexts
+pub const ICEBREAKER_ANSWER_LEN: usize = 16 + 32 * 4;
+
+/// Bits of `MatchingDataChangedEvent::affected_fields`
+pub const MATCHING_FIELD_AGE: u8 = 1 << 0;
+pub const MATCHING_FIELD_LOCATION: u8 = 1 << 1;
+pub const MATCHING_FIELD_PRIVATE_DATA: u8 = 1 << 2;
+pub const MATCHING_FIELD_PREFERENCES: u8 = 1 << 3;
+
+/// Seconds a finalized session must sit idle before it can be rekindled
+pub const REKINDLE_COOLDOWN: i64 = 7 * SECONDS_PER_DAY;
+
+/// Layout of `encrypted_match_data` and `encrypted_intents` written by the
+/// current `MatchSession` and `LikeIntents` circuit structs; bump alongside
+/// the structs and migrate old sessions
+pub const CIPHERTEXT_LAYOUT_VERSION: u8 = 2;
+
+/// Layout of the `UserProfile` account; each appended field bumps it and
+/// `migration` learns to decode the previous layout
+pub const PROFILE_LAYOUT_VERSION: u8 = 14;
+
+/// Layout version of `ProfileView`, independent of the account schema
+pub const PROFILE_VIEW_VERSION: u8 = 2;
+
+/// Profile privacy levels (UserProfile.privacy_level)
+pub const PRIVACY_LEVEL_PUBLIC: u8 = 0;
+pub const PRIVACY_LEVEL_AVATAR_ONLY: u8 = 1;      // City hidden until matched
+pub const PRIVACY_LEVEL_HIDDEN: u8 = 2;           // Avatar and city hidden until matched
+
+/// Inbound like policies (UserProfile.like_policy)
+pub const LIKE_POLICY_ANYONE: u8 = 0;
+pub const LIKE_POLICY_VERIFIED_ONLY: u8 = 1;
+pub const LIKE_POLICY_MUTUAL_AGE_RANGE: u8 = 2;    // Each age inside the other's like range
+
+/// Profile genders (UserProfile.gender); each code is one bit of the
+/// `preferred_genders` matching mask
+pub const GENDER_UNSPECIFIED: u8 = 0;
+pub const GENDER_WOMAN: u8 = 1;
+pub const GENDER_MAN: u8 = 2;
+pub const GENDER_NON_BINARY: u8 = 3;
+pub const MAX_GENDER: u8 = GENDER_NON_BINARY;
+
+/// Profile orientations (UserProfile.orientation)
+pub const ORIENTATION_UNSPECIFIED: u8 = 0;
+pub const ORIENTATION_STRAIGHT: u8 = 1;
+pub const ORIENTATION_GAY: u8 = 2;
+pub const ORIENTATION_BISEXUAL: u8 = 3;
+pub const ORIENTATION_PANSEXUAL: u8 = 4;
+pub const ORIENTATION_ASEXUAL: u8 = 5;
+pub const MAX_ORIENTATION: u8 = ORIENTATION_ASEXUAL;
+
+/// Maximum interests per profile; matches the circuit's MAX_INTERESTS
+pub const MAX_INTERESTS: usize = 10;
+
+/// Maximum users a single blocklist can hold
+pub const MAX_BLOCKED_USERS: usize = 100;
+
+/// Items returned per page by list views (keeps return data under the 1 KiB limit)
+pub const LIST_PAGE_SIZE: usize = 16;
+
+/// One record of encrypted like history: a single 32-byte ciphertext
+pub const ENCRYPTED_LIKE_RECORD_LEN: usize = 32;
+
+/// Bytes of encrypted like history returned per `get_likes_page` call, a
+/// whole number of records so no page splits a ciphertext
+pub const LIKES_PAGE_LEN: usize = 4 * ENCRYPTED_LIKE_RECORD_LEN;
+
+/// One match history record: session_id and matched_at (little-endian), then
+/// the other participant. Stored in the clear, as MutualMatchFoundEvent
+/// already publishes the pair
+pub const MATCH_RECORD_LEN: usize = 8 + 8 + 32;
+
+/// Default number of likes a user may submit per UTC day
+pub const MAX_DAILY_LIKES: u16 = 100;
+
+/// Super likes a user may submit per UTC day, on top of their likes
+pub const MAX_DAILY_SUPER_LIKES: u16 = 1;
+
+/// Weight the `submit_like` circuit applies to a like's intent
+pub const LIKE_WEIGHT_NORMAL: u8 = 1;
+pub const LIKE_WEIGHT_SUPER: u8 = 2;
+
+/// Records that fit in the 296 bytes reserved for `match_history`
+pub const MAX_INLINE_MATCH_RECORDS: u8 = (296 / MATCH_RECORD_LEN) as u8;
+
+/// Records per `MatchArchive` page; one page is one `get_match_archive` page
+pub const MATCH_ARCHIVE_PAGE_RECORDS: usize = LIST_PAGE_SIZE;
+
+/// Reasons carried by `ReportThrottledEvent`
+pub const REPORT_THROTTLE_INACTIVE_REPORTER: u8 = 0;
+pub const REPORT_THROTTLE_UNVERIFIED_REPORTER: u8 = 1;
+pub const REPORT_THROTTLE_COOLDOWN: u8 = 2;
+
+/// Seconds before the same reporter may report the same user again
+pub const REPORT_COOLDOWN: i64 = 30 * SECONDS_PER_DAY;
+
+/// Distinct verified reporters needed before a profile is auto-deactivated
+pub const AUTO_DEACTIVATION_REPORT_THRESHOLD: u32 = 5;
+
+// ============================================================================
+// ERROR CODES
+// ============================================================================
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The computation was aborted")]
+    AbortedComputation,
+    #[msg("Cluster not set")]
+    ClusterNotSet,
+    #[msg("Username too short (minimum 3 characters)")]
+    UsernameTooShort,
+    #[msg("Username too long (maximum 32 characters)")]
+    UsernameTooLong,
+    #[msg("Username can only contain letters, numbers and underscores")]
+    InvalidUsernameFormat,
+    #[msg("Age must be between 18-99")]
+    InvalidAge,
+    #[msg("Private data too large (maximum 1000 bytes)")]
+    DataTooLarge,
+    #[msg("Preferences too large (maximum 500 bytes)")]
+    PreferencesTooLarge,
+    #[msg("Profile already exists")]
+    ProfileAlreadyExists,
+    #[msg("Invalid encrypted data")]
+    InvalidEncryptedData,
+    #[msg("Avatar is required")]
+    AvatarRequired,
+    #[msg("Location information is required")]
+    LocationRequired,
+    #[msg("Invalid encryption key")]
+    InvalidEncryptionKey,
+    #[msg("User is not authorized to perform this action")]
+    UnauthorizedUser,
+    #[msg("Invalid session")]
+    InvalidSession,
+    #[msg("Like delegate is not authorized for this session")]
+    UnauthorizedDelegate,
+    #[msg("Invalid like delegate")]
+    InvalidDelegate,
+    #[msg("Session has not produced a mutual match")]
+    SessionNotMatched,
+    #[msg("Computation offset is already in use; pick a fresh computation_offset")]
+    ComputationOffsetInUse,
+    #[msg("Computation definition account does not match the expected offset")]
+    CompDefOffsetMismatch,
+    #[msg("Invalid admin")]
+    InvalidAdmin,
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("Nonce was already used; fetch the next nonce from the nonce counter")]
+    NonceReused,
+    #[msg("Encrypted age check failed; participants must be 18 or older")]
+    AgeGateFailed,
+    #[msg("Invalid session TTL bounds")]
+    InvalidSessionTtl,
+    #[msg("A computation is already in flight for this session")]
+    ComputationPending,
+    #[msg("Callback does not match the computation queued for this session")]
+    UnexpectedCallback,
+    #[msg("Batch must contain between 1 and 8 candidates")]
+    InvalidBatchSize,
+    #[msg("Account is not a user's staged matching ciphertexts")]
+    InvalidMatchingCiphertexts,
+    #[msg("Icebreaker answer must be empty or a nonce followed by 4 ciphertexts")]
+    InvalidIcebreakerAnswer,
+    #[msg("Both users must set an icebreaker answer first")]
+    IcebreakerMissing,
+    #[msg("This user has paused new match sessions")]
+    MatchingPaused,
+    #[msg("Computation returned a status flag outside the expected range")]
+    UnexpectedStatusFlag,
+    #[msg("Boost duration must be positive")]
+    InvalidBoostDuration,
+    #[msg("Boost is still active")]
+    BoostStillActive,
+    #[msg("Rekindle cooldown has not elapsed")]
+    RekindleCooldown,
+    #[msg("Too many interests; at most 10 are supported")]
+    TooManyInterests,
+    #[msg("Preferred height minimum exceeds the maximum")]
+    InvalidHeightRange,
+    #[msg("Session has been idle past its expiry window")]
+    SessionExpired,
+    #[msg("Session has not been idle past its expiry window")]
+    SessionNotExpired,
+    #[msg("Match archive window must be positive")]
+    InvalidArchiveWindow,
+    #[msg("Only matches with no conversation past the archive window can be archived")]
+    MatchNotArchivable,
+    #[msg("Unarchive grace period has elapsed")]
+    UnarchiveGraceExpired,
+    #[msg("Minimum account age cannot be negative")]
+    InvalidMinAccountAge,
+    #[msg("Profile is too new to match yet")]
+    AccountTooNew,
+    #[msg("Matching ciphertext chunk is out of range")]
+    MatchingDataOutOfRange,
+    #[msg("Account is not a compatibility batch")]
+    InvalidCompatibilityBatch,
+    #[msg("Privacy level must be 0 (public), 1 (avatar only) or 2 (hidden until match)")]
+    InvalidPrivacyLevel,
+    #[msg("Session is not in a valid state for this operation")]
+    InvalidSessionState,
+    #[msg("Feedback for this match was already submitted")]
+    FeedbackAlreadySubmitted,
+    #[msg("User has no profile")]
+    ProfileNotFound,
+    #[msg("Like policy must be 0-2 with a minimum age no greater than the maximum")]
+    InvalidLikePolicy,
+    #[msg("The target's like policy does not accept this like")]
+    LikePolicyViolation,
+    #[msg("Username reservation belongs to another user")]
+    InvalidUsernameReservation,
+    #[msg("Username reservation has expired; reserve it again")]
+    UsernameReservationExpired,
+    #[msg("Username reservation is still active")]
+    UsernameReservationActive,
+    #[msg("Inline match cap must be between 1 and MAX_INLINE_MATCH_RECORDS")]
+    InvalidInlineMatchCap,
+    #[msg("Daily like limit reached; try again tomorrow (UTC)")]
+    DailyLikeLimitReached,
+    #[msg("Daily super like already used; try again tomorrow (UTC)")]
+    DailySuperLikeLimitReached,
+    #[msg("Daily like limit must be at least 1")]
+    InvalidDailyLikeLimit,
+    #[msg("Match history is full; archive older records first")]
+    MatchHistoryFull,
+    #[msg("Profile is already in the requested state")]
+    ProfileStateUnchanged,
+    #[msg("User still has open match sessions")]
+    HasActiveSessions,
+    #[msg("Profile is deactivated")]
+    ProfileInactive,
+    #[msg("A user cannot be paired with themselves")]
+    CannotMatchSelf,
+    #[msg("Counter would overflow")]
+    CounterOverflow,
+    #[msg("Username is already taken")]
+    UsernameTaken,
+    #[msg("Renaming requires the new username's registry account")]
+    UsernameRegistryMissing,
+    #[msg("Profile account is too small for this data, grow it first")]
+    ProfileTooSmall,
+    #[msg("Profile cannot grow past its full layout size")]
+    ProfileGrowthExceeded,
+    #[msg("One of the users has blocked the other")]
+    UserBlocked,
+    #[msg("A user cannot block themselves")]
+    CannotBlockSelf,
+    #[msg("User is already blocked")]
+    AlreadyBlocked,
+    #[msg("User is not blocked")]
+    NotBlocked,
+    #[msg("Blocklist is full")]
+    BlocklistFull,
+    #[msg("A user cannot report themselves")]
+    CannotReportSelf,
+    #[msg("Unknown gender code")]
+    InvalidGender,
+    #[msg("Unknown orientation code")]
+    InvalidOrientation,
+    #[msg("Too many photos (maximum 6)")]
+    TooManyPhotos,
+    #[msg("Photo URL must be https and at most 196 bytes")]
+    InvalidPhotoUrl,
+    #[msg("Photo index out of range or order is not a permutation")]
+    InvalidPhotoIndex,
+    #[msg("The first photo must be the avatar")]
+    PrimaryPhotoMismatch,
+    #[msg("Bio too long (maximum 256 bytes)")]
+    BioTooLong,
+    #[msg("Avatar URL must be https and at most 196 bytes")]
+    InvalidAvatarUrl,
+    #[msg("Match session is already finalized")]
+    SessionAlreadyFinalized,
+    #[msg("Match session is not finalized")]
+    SessionNotFinalized,
+    #[msg("Both participants must like before the match is checked")]
+    SessionNotReady,
+    #[msg("Offset is past the end of the list or not on a record boundary")]
+    OffsetOutOfRange,
+    #[msg("Invalid verification authority")]
+    InvalidVerificationAuthority,
+}
+
+// ============================================================================
+// HELPER FUNCTIONS
+// ============================================================================
+
+/// Session-level operations gated by `require_session_state`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SessionOperation {
+    SubmitLike,
+    WithdrawLike,
+    SubmitPass,
+    CheckMutualMatch,
+    RotateNonce,
+    Rekindle,
+    StartConversation,
+    Archive,
+    Unarchive,
+    SubmitFeedback,
+    Expire,
+}
+
+/// Precondition matrix for the session lifecycle:
+///
+/// | operation                                 | is_finalized | match_found | finalization_reason |
+/// |-------------------------------------------|--------------|-------------|---------------------|
+/// | SubmitLike, CheckMutualMatch, RotateNonce | false        | any         | any                 |
+/// | WithdrawLike, SubmitPass, Expire          | false        | any         | any                 |
+/// | Rekindle                                  | true         | false       | any                 |
+/// | StartConversation, Archive                | true         | true        | CHECKED             |
+/// | Unarchive                                 | true         | true        | ARCHIVED            |
+/// | SubmitFeedback                            | true         | true        | any                 |
+///
+/// Operations that need an open session fail with `SessionAlreadyFinalized`
+/// once it is finalized; any other combination fails with `InvalidSessionState`.
+fn require_session_state(session: &MatchPairSession, operation: SessionOperation) -> Result<()> {
+    let allowed = match operation {
+        SessionOperation::SubmitLike |
+        SessionOperation::WithdrawLike |
+        SessionOperation::SubmitPass |
+        SessionOperation::CheckMutualMatch |
+        SessionOperation::RotateNonce |
+        SessionOperation::Expire => {
+            require!(!session.is_finalized, ErrorCode::SessionAlreadyFinalized);
+            true
+        },
+        SessionOperation::Rekindle => session.is_finalized && !session.match_found,
+        SessionOperation::StartConversation | SessionOperation::Archive => {
+            session.is_finalized &&
+            session.match_found &&
+            session.finalization_reason == FINALIZATION_REASON_CHECKED
+        },
+        SessionOperation::Unarchive => {
+            session.is_finalized &&
+            session.match_found &&
+            session.finalization_reason == FINALIZATION_REASON_ARCHIVED
+        },
+        SessionOperation::SubmitFeedback => session.is_finalized && session.match_found,
+    };
+    require!(allowed, ErrorCode::InvalidSessionState);
+    Ok(())
+}
+
+/// Loads a registered user's profile from its PDA, failing with
+/// `ProfileNotFound` when the account was never created or is not a profile
+fn load_user_profile(profile_info: &AccountInfo, owner: &Pubkey) -> Result<UserProfile> {
+    require!(
+        *profile_info.owner == crate::ID && !profile_info.data_is_empty(),
+        ErrorCode::ProfileNotFound
+    );
+    let data = profile_info.try_borrow_data()?;
+    let profile = UserProfile::try_deserialize(&mut &data[..])
+        .map_err(|_| error!(ErrorCode::ProfileNotFound))?;
+    require_keys_eq!(profile.owner, *owner, ErrorCode::ProfileNotFound);
+    Ok(profile)
+}
+
+/// Loads a user's staged matching ciphertexts passed as a remaining account,
+/// checking the owning program, the account type and its PDA
+fn load_matching_ciphertexts(data_info: &AccountInfo) -> Result<Box<MatchingCiphertexts>> {
+    require_keys_eq!(*data_info.owner, crate::ID, ErrorCode::InvalidMatchingCiphertexts);
+    let data = data_info.try_borrow_data()?;
+    require!(
+        data.starts_with(MatchingCiphertexts::DISCRIMINATOR),
+        ErrorCode::InvalidMatchingCiphertexts
+    );
+    let matching_ciphertexts = Box::new(MatchingCiphertexts::try_deserialize(&mut &data[..])?);
+    let expected = Pubkey::create_program_address(
+        &[b"matching_ciphertexts", matching_ciphertexts.owner.as_ref(), &[matching_ciphertexts.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| ErrorCode::InvalidMatchingCiphertexts)?;
+    require_keys_eq!(data_info.key(), expected, ErrorCode::InvalidMatchingCiphertexts);
+    Ok(matching_ciphertexts)
+}
+
+/// Whether the blocklist PDA behind `blocklist_info` lists `user`; users who
+/// never blocked anyone have no blocklist account
+fn blocklist_contains(blocklist_info: &AccountInfo, user: &Pubkey) -> Result<bool> {
+    if *blocklist_info.owner != crate::ID || blocklist_info.data_is_empty() {
+        return Ok(false);
+    }
+    let blocklist = Blocklist::try_deserialize(&mut &blocklist_info.try_borrow_data()?[..])?;
+    Ok(blocklist.contains(user))
+}
+
+/// Fails with `UserBlocked` when either user has blocked the other
+fn require_not_blocked(
+    user_a_blocklist: &AccountInfo,
+    user_a: &Pubkey,
+    user_b_blocklist: &AccountInfo,
+    user_b: &Pubkey,
+) -> Result<()> {
+    require!(
+        !blocklist_contains(user_a_blocklist, user_b)? && !blocklist_contains(user_b_blocklist, user_a)?,
+        ErrorCode::UserBlocked
+    );
+    Ok(())
+}
+
+/// Grows a program-owned account to `len`, topping up rent from `payer` first
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<()> {
+    if account.data_len() >= len {
+        return Ok(());
+    }
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(len)?;
+    Ok(())
+}
+
+/// Ensures the computation PDA for `computation_offset` has not been created yet,
+/// so a reused offset fails with a clear error instead of inside the Arcium CPI
+fn require_fresh_computation_offset(computation_account: &AccountInfo) -> Result<()> {
+    require!(
+        computation_account.lamports() == 0 && computation_account.data_is_empty(),
+        ErrorCode::ComputationOffsetInUse
+    );
+    Ok(())
+}
+
+/// A session's participants in canonical order. Session PDAs are seeded by
+/// the ordered pair, so each unordered pair has exactly one session account;
+/// `session_id` is kept for display only.
+pub fn session_pair(user_a: &Pubkey, user_b: &Pubkey) -> (Pubkey, Pubkey) {
+    if user_a <= user_b {
+        (*user_a, *user_b)
+    } else {
+        (*user_b, *user_a)
+    }
+}
+
+/// Case-insensitive key for a username: the SHA-256 of its lowercase form,
+/// so "Alice" and "alice" share one reservation and one registry entry
+pub fn username_hash(username: &str) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hash(username.to_lowercase().as_bytes()).to_bytes()
+}
+
+/// Claims a registry entry for `owner`; an entry held by anyone else means
+/// the username is taken
+fn claim_username(registry: &mut UsernameRegistry, owner: Pubkey, username: &str, bump: u8) -> Result<()> {
+    require!(
+        registry.owner == Pubkey::default() || registry.owner == owner,
+        ErrorCode::UsernameTaken
+    );
+    registry.owner = owner;
+    registry.username_hash = username_hash(username);
+    registry.bump = bump;
+    Ok(())
+}
+
+/// Closes `owner`'s registry entry, refunding `destination`. Profiles created
+/// before the registry have no entry, and their name may since have been
+/// claimed by someone else; both cases are left alone.
+fn release_username<'info>(
+    registry: &AccountInfo<'info>,
+    owner: Pubkey,
+    destination: AccountInfo<'info>,
+) -> Result<()> {
+    if registry.data_is_empty() {
+        return Ok(());
+    }
+    let entry = Account::<UsernameRegistry>::try_from(registry)?;
+    if entry.owner != owner {
+        return Ok(());
+    }
+    entry.close(destination)
+}
+
+/// Recommends how long a user's sessions should stay open before expiring.
+/// Recently active users start from the upper bound, idle users from the lower
+/// bound, and every confirmed match adds half a day; the result is clamped.
+pub fn recommended_session_ttl(
+    last_active_at: i64,
+    total_matches: u32,
+    now: i64,
+    min_ttl: i64,
+    max_ttl: i64,
+) -> i64 {
+    let idle = now.saturating_sub(last_active_at).max(0);
+    let base = if idle <= SECONDS_PER_DAY {
+        max_ttl
+    } else if idle <= 7 * SECONDS_PER_DAY {
+        min_ttl + (max_ttl - min_ttl) / 2
+    } else {
+        min_ttl
+    };
+    let match_bonus = (total_matches as i64).saturating_mul(SECONDS_PER_DAY / 2);
+    base.saturating_add(match_bonus).clamp(min_ttl, max_ttl)
+}
+
+/// Idle time after which a session expires: the longer of its participants'
+/// recommended TTLs, so neither loses a session sooner than they would alone
+fn session_idle_ttl(user_a_profile: &UserProfile, user_b_profile: &UserProfile, config: &ProgramConfig, now: i64) -> i64 {
+    let ttl = |profile: &UserProfile| recommended_session_ttl(
+        profile.last_active_at(),
+        profile.total_matches,
+        now,
+        config.min_session_ttl,
+        config.max_session_ttl,
+    );
+    ttl(user_a_profile).max(ttl(user_b_profile))
+}
+
+/// Kilometres per degree of latitude, the grid cell size used by `quantize_coordinates`
+pub const KM_PER_DEGREE: f64 = 111.32;
+
+/// Quantizes a position into the ~1 km grid the circuits compare squared
+/// distances on; clients encrypt the result as `grid_x`/`grid_y`. Longitude is
+/// not scaled by latitude, so east-west distances are overestimated away from
+/// the equator, never underestimated. Cells are offset by one so that (0, 0)
+/// keeps meaning "no coordinates".
+pub fn quantize_coordinates(latitude: f64, longitude: f64) -> (u16, u16) {
+    let cell = |degrees: f64| (degrees * KM_PER_DEGREE).round() as u16 + 1;
+    let grid_x = cell(longitude.clamp(-180.0, 180.0) + 180.0);
+    let grid_y = cell(latitude.clamp(-90.0, 90.0) + 90.0);
+    (grid_x, grid_y)
+}
+
+/// Slices one page out of `items`; a cursor past the end yields an empty page
+pub fn paginate<T: Clone>(items: &[T], cursor: Option<u32>, page_size: usize) -> Paginated<T> {
+    let page = cursor.unwrap_or(0) as usize;
+    let start = page.saturating_mul(page_size).min(items.len());
+    let end = start.saturating_add(page_size).min(items.len());
+    let next_cursor = if end < items.len() {
+        Some(page as u32 + 1)
+    } else {
+        None
+    };
+
+    Paginated {
+        items: items[start..end].to_vec(),
+        next_cursor,
+    }
+}
+
+/// Slices up to `page_len` bytes starting at byte `offset`, which must fall on
+/// a `record_len` boundary. Only offset 0 is valid past the end, so an empty
+/// history still yields an empty first page.
+pub fn byte_page(bytes: &[u8], offset: u32, page_len: usize, record_len: usize) -> Result<BytePage> {
+    let start = offset as usize;
+    require!(start < bytes.len() || start == 0, ErrorCode::OffsetOutOfRange);
+    require!(start % record_len == 0, ErrorCode::OffsetOutOfRange);
+    let end = start.saturating_add(page_len).min(bytes.len());
+    let next_offset = if end < bytes.len() { Some(end as u32) } else { None };
+
+    Ok(BytePage {
+        bytes: bytes[start..end].to_vec(),
+        next_offset,
+    })
+}
+
+/// The u64 identifier the circuits use for a user: the first 8 bytes of their key
+fn user_mpc_id(user: &Pubkey) -> u64 {
+    u64::from_le_bytes(user.as_ref()[0..8].try_into().unwrap())
+}
+
+/// Runs every `create_profile` input check; shared with `validate_profile_input`
+fn validate_profile_data(profile_data: &CreateProfileData) -> Result<()> {
+    validate_public_fields(
+        &profile_data.username,
+        &profile_data.avatar_url,
+        profile_data.age,
+        &profile_data.location_city,
+    )?;
+    require!(profile_data.encrypted_private_data.len() <= 1000, ErrorCode::DataTooLarge);
+    require!(profile_data.encrypted_preferences.len() <= 500, ErrorCode::PreferencesTooLarge);
+    validate_encrypted_blobs(&profile_data.encrypted_private_data, &profile_data.encrypted_preferences)?;
+    require!(profile_data.encryption_pubkey != [0u8; 32], ErrorCode::InvalidEncryptionKey);
+    require!(profile_data.gender <= MAX_GENDER, ErrorCode::InvalidGender);
+    require!(profile_data.orientation <= MAX_ORIENTATION, ErrorCode::InvalidOrientation);
+    require!(profile_data.photo_urls.len() <= MAX_PHOTOS, ErrorCode::TooManyPhotos);
+    for url in &profile_data.photo_urls {
+        validate_photo_url(url)?;
+    }
+    require!(
+        profile_data.photo_urls.first().map_or(true, |first| *first == profile_data.avatar_url),
+        ErrorCode::PrimaryPhotoMismatch
+    );
+    validate_bio(&profile_data.bio)?;
+    Ok(())
+}
+
+/// Stamps a gallery change and emits `PhotosUpdatedEvent`
+fn emit_photos_updated(user_profile: &mut UserProfile) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    user_profile.last_updated = timestamp;
+    emit!(PhotosUpdatedEvent {
+        user: user_profile.owner,
+        avatar_url: user_profile.avatar_url.clone(),
+        photo_count: user_profile.photo_urls.len() as u8,
+        timestamp,
+    });
+    Ok(())
+}
+
+/// Whether `url` is an https URL with a host and fits `max_len` bytes
+fn is_https_url(url: &str, max_len: usize) -> bool {
+    let host = url
+        .strip_prefix("https://")
+        .and_then(|rest| rest.split(['/', '?', '#']).next())
+        .unwrap_or("");
+    !host.is_empty() && url.len() <= max_len
+}
+
+/// Checks an avatar URL; frontends load it directly, so only https is allowed
+fn validate_avatar_url(url: &str) -> Result<()> {
+    require!(is_https_url(url, MAX_AVATAR_URL_LEN), ErrorCode::InvalidAvatarUrl);
+    Ok(())
+}
+
+/// Checks one gallery photo URL with the avatar's rules
+fn validate_photo_url(url: &str) -> Result<()> {
+    require!(is_https_url(url, MAX_PHOTO_URL_LEN), ErrorCode::InvalidPhotoUrl);
+    Ok(())
+}
+
+/// Checks a profile bio; an empty bio is allowed
+fn validate_bio(bio: &str) -> Result<()> {
+    require!(bio.len() <= MAX_BIO_LEN, ErrorCode::BioTooLong);
+    Ok(())
+}
+
+/// Checks the public profile fields shared by create_profile and update_profile
+fn validate_public_fields(username: &str, avatar_url: &str, age: u8, location_city: &str) -> Result<()> {
+    require!(username.len() >= 3, ErrorCode::UsernameTooShort);
+    require!(username.len() <= 32, ErrorCode::UsernameTooLong);
+    require!(age >= 18 && age <= 99, ErrorCode::InvalidAge);
+    require!(!avatar_url.is_empty(), ErrorCode::AvatarRequired);
+    validate_avatar_url(avatar_url)?;
+    require!(!location_city.is_empty(), ErrorCode::LocationRequired);
+    require!(
+        username.chars().all(|c| c.is_alphanumeric() || c == '_'),
+        ErrorCode::InvalidUsernameFormat
+    );
+    Ok(())
+}
+
+/// Rejects obviously-degenerate ciphertext blobs: all-zero data, or the same
+/// bytes submitted for both private data and preferences
+fn validate_encrypted_blobs(encrypted_private_data: &[u8], encrypted_preferences: &[u8]) -> Result<()> {
+    for blob in [encrypted_private_data, encrypted_preferences] {
+        require!(
+            blob.is_empty() || blob.iter().any(|b| *b != 0),
+            ErrorCode::InvalidEncryptedData
+        );
+    }
+    require!(
+        encrypted_private_data.is_empty() || encrypted_private_data != encrypted_preferences,
+        ErrorCode::InvalidEncryptedData
+    );
+    Ok(())
+}
+
+/// Ensures the computation definition account being initialized is the PDA
+/// derived from `expected_offset`
+fn require_comp_def_offset(comp_def_account: &AccountInfo, expected_offset: u32) -> Result<()> {
+    require_keys_eq!(
+        comp_def_account.key(),
+        derive_comp_def_pda!(expected_offset),
+        ErrorCode::CompDefOffsetMismatch
+    );
+    Ok(())
+}
+
+// ============================================================================
+// COMPUTATION RESULT HANDLERS
+// ============================================================================
+// Shared by the Arcium callbacks and the `mock-mpc` backend so both apply
+// identical state transitions.
+
+/// One side of a session being opened: the user and their profile and
+/// blocklist accounts
+struct SessionParticipant<'a, 'info> {
+    user: Pubkey,
+    profile: &'a AccountInfo<'info>,
+    blocklist: &'a AccountInfo<'info>,
+}
+
+/// Checks that `payer` may open a session between `user_a` and `user_b` and
+/// counts it as open on both profiles
+fn register_session_participants(
+    payer: Pubkey,
+    user_a: SessionParticipant<'_, '_>,
+    user_b: SessionParticipant<'_, '_>,
+    min_account_age: i64,
+    now: i64,
+) -> Result<()> {
+    // Only a participant may open their own session
+    require!(payer == user_a.user || payer == user_b.user, ErrorCode::UnauthorizedUser);
+    require_keys_neq!(user_a.user, user_b.user, ErrorCode::CannotMatchSelf);
+    require_not_blocked(user_a.blocklist, &user_a.user, user_b.blocklist, &user_b.user)?;
+
+    // A paused user can still start sessions, but nobody else can target them.
+    // Both profiles must also be older than the configured minimum.
+    let user_a_profile = load_user_profile(user_a.profile, &user_a.user)?;
+    let user_b_profile = load_user_profile(user_b.profile, &user_b.user)?;
+    for profile in [&user_a_profile, &user_b_profile] {
+        require!(
+            !profile.matching_paused || profile.owner == payer,
+            ErrorCode::MatchingPaused
+        );
+        require!(
+            now.saturating_sub(profile.created_at) >= min_account_age,
+            ErrorCode::AccountTooNew
+        );
+    }
+
+    // Count the open session against both participants
+    let participants = [
+        (user_a.profile, user_a_profile),
+        (user_b.profile, user_b_profile),
+    ];
+    for (profile_info, mut profile) in participants {
+        profile.open_sessions = profile.open_sessions.saturating_add(1);
+        profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+    }
+    Ok(())
+}
+
+/// Accounts `open_match_session` works on, borrowed together
+struct SessionInitParts<'a, 'info> {
+    payer: &'a Signer<'info>,
+    nonce_counter: &'a mut Account<'info, NonceCounter>,
+    user_a_profile: &'a UncheckedAccount<'info>,
+    user_b_profile: &'a UncheckedAccount<'info>,
+    user_a_blocklist: &'a UncheckedAccount<'info>,
+    user_b_blocklist: &'a UncheckedAccount<'info>,
+    config: &'a Account<'info, ProgramConfig>,
+    match_pair_session: &'a mut Account<'info, MatchPairSession>,
+    platform_stats: &'a mut Account<'info, PlatformStats>,
+    computation_account: &'a UncheckedAccount<'info>,
+    pool_account: &'a Account<'info, FeePool>,
+}
+
+/// Contexts that open a session: `InitMatchSession` and `InitMatchSessionAuto`,
+/// which differ only in where the session id comes from
+trait SessionInitAccounts<'info>: QueueCompAccs<'info> {
+    fn parts(&mut self) -> SessionInitParts<'_, 'info>;
+}
+
+impl<'info> SessionInitAccounts<'info> for InitMatchSession<'info> {
+    fn parts(&mut self) -> SessionInitParts<'_, 'info> {
+        SessionInitParts {
+            payer: &self.payer,
+            nonce_counter: &mut self.nonce_counter,
+            user_a_profile: &self.user_a_profile,
+            user_b_profile: &self.user_b_profile,
+            user_a_blocklist: &self.user_a_blocklist,
+            user_b_blocklist: &self.user_b_blocklist,
+            config: &self.config,
+            match_pair_session: &mut self.match_pair_session,
+            platform_stats: &mut self.platform_stats,
+            computation_account: &self.computation_account,
+            pool_account: &self.pool_account,
+        }
+    }
+}
+
+impl<'info> SessionInitAccounts<'info> for InitMatchSessionAuto<'info> {
+    fn parts(&mut self) -> SessionInitParts<'_, 'info> {
+        SessionInitParts {
+            payer: &self.payer,
+            nonce_counter: &mut self.nonce_counter,
+            user_a_profile: &self.user_a_profile,
+            user_b_profile: &self.user_b_profile,
+            user_a_blocklist: &self.user_a_blocklist,
+            user_b_blocklist: &self.user_b_blocklist,
+            config: &self.config,
+            match_pair_session: &mut self.match_pair_session,
+            platform_stats: &mut self.platform_stats,
+            computation_account: &self.computation_account,
+            pool_account: &self.pool_account,
+        }
+    }
+}
+
+/// Opens the session between `user_a` and `user_b` under `session_id` and
+/// queues its `init_match_session` computation
+fn open_match_session<'info, T: SessionInitAccounts<'info>>(
+    accounts: &mut T,
+    bump: u8,
+    computation_offset: u64,
+    session_id: u64,
+    user_a: Pubkey,
+    user_b: Pubkey,
+    nonce: u128,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let parts = accounts.parts();
+
+    // Consume the payer's nonce so session ciphertexts never reuse one
+    parts.nonce_counter.consume(nonce)?;
+
+    register_session_participants(
+        parts.payer.key(),
+        SessionParticipant {
+            user: user_a,
+            profile: parts.user_a_profile,
+            blocklist: parts.user_a_blocklist,
+        },
+        SessionParticipant {
+            user: user_b,
+            profile: parts.user_b_profile,
+            blocklist: parts.user_b_blocklist,
+        },
+        parts.config.min_account_age_secs,
+        clock.unix_timestamp,
+    )?;
+
+    reset_match_session(
+        parts.match_pair_session,
+        session_id,
+        user_a,
+        user_b,
+        nonce,
+        clock.unix_timestamp,
+        bump,
+    );
+    parts.match_pair_session.begin_computation(
+        COMP_DEF_OFFSET_INIT_MATCH_SESSION,
+        computation_offset,
+        clock.unix_timestamp,
+    )?;
+
+    parts.platform_stats.total_sessions = parts.platform_stats.total_sessions.saturating_add(1);
+
+    // Convert public keys to u64 IDs for MPC computation
+    let user_a_id = user_mpc_id(&user_a);
+    let user_b_id = user_mpc_id(&user_b);
+
+    // Prepare arguments for MPC computation
+    let args = vec![
+        Argument::PlaintextU128(nonce),
+        Argument::PlaintextU64(user_a_id),
+        Argument::PlaintextU64(user_b_id),
+        Argument::PlaintextU64(clock.unix_timestamp as u64),
+    ];
+
+    require_fresh_computation_offset(&parts.computation_account.to_account_info())?;
+
+    // Queue the encrypted computation
+    let session_key = parts.match_pair_session.key();
+    let computation_key = parts.computation_account.key();
+    let fee_pool_before = parts.pool_account.to_account_info().lamports();
+    #[cfg(not(feature = "mock-mpc"))]
+    queue_computation(
+        accounts,
+        computation_offset,
+        args,
+        vec![
+            CallbackAccount {
+                pubkey: session_key,
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: computation_key,
+                is_writable: false,
+            },
+        ],
+        None,
+    )?;
+    let parts = accounts.parts();
+    let fee_pool_after = parts.pool_account.to_account_info().lamports();
+    parts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+    // Mock backend: run the plaintext circuit and apply its result inline
+    #[cfg(feature = "mock-mpc")]
+    {
+        let _ = args;
+        let (mock_session, mock_intents) = mock_mpc::init_match_session(user_a_id, user_b_id, clock.unix_timestamp as u64);
+        parts.match_pair_session.complete_computation(session_key, COMP_DEF_OFFSET_INIT_MATCH_SESSION, computation_key)?;
+        apply_init_match_session(
+            parts.match_pair_session,
+            SessionCiphertexts {
+                match_data: mock_session.encode(),
+                nonce: nonce.wrapping_add(1),
+                intents: mock_intents.encode(),
+                intents_nonce: nonce.wrapping_add(1),
+            },
+        )?;
+    }
+
+    // Emit session creation event
+    emit!(MatchSessionCreatedEvent {
+        session_id,
+        user_a,
+        user_b,
+        created_at: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Sets every field of a newly created session; the encrypted state follows
+/// from the `init_match_session` computation
+fn reset_match_session(
+    match_session: &mut MatchPairSession,
+    session_id: u64,
+    user_a: Pubkey,
+    user_b: Pubkey,
+    nonce: u128,
+    now: i64,
+    bump: u8,
+) {
+    match_session.session_id = session_id;
+    match_session.user_a = user_a;
+    match_session.user_b = user_b;
+    match_session.nonce = nonce;
+    match_session.created_at = now;
+    match_session.last_updated = now;
+    match_session.is_finalized = false;
+    match_session.match_found = false;
+    match_session.matched_at = 0;
+    match_session.user_a_seen_at = 0;
+    match_session.user_b_seen_at = 0;
+    match_session.conversation_started_at = 0;
+    match_session.user_a_rated = false;
+    match_session.user_b_rated = false;
+    match_session.total_compute_fees = 0;
+    match_session.intents_nonce = 0;
+    match_session.encrypted_intents = [[0u8; 32]; 2];
+    match_session.match_confidence = 0;
+    match_session.last_processed_computation = Pubkey::default();
+    match_session.is_ready_for_verification = false;
+    match_session.finalization_reason = FINALIZATION_REASON_CHECKED;
+    match_session.ciphertext_layout_version = CIPHERTEXT_LAYOUT_VERSION;
+    match_session.bump = bump;
+}
+
+/// The encrypted `MatchSession` and `LikeIntents` a circuit hands back
+struct SessionCiphertexts {
+    match_data: [[u8; 32]; 6],
+    nonce: u128,
+    intents: [[u8; 32]; 2],
+    intents_nonce: u128,
+}
+
+/// Stores the freshly initialized encrypted session state
+fn apply_init_match_session(match_session: &mut MatchPairSession, state: SessionCiphertexts) -> Result<()> {
+    match_session.store_ciphertexts(state);
+
+    msg!("Match session initialized with encrypted data");
+    Ok(())
+}
+
+/// A client's `UserLikeAction` ciphertexts with the key and nonce they were
+/// encrypted under
+struct EncryptedLikeAction {
+    user_id: [u8; 32],
+    target_id: [u8; 32],
+    like_action: [u8; 32],
+    timestamp: [u8; 32],
+    intent_strength: [u8; 32],
+    pub_key: [u8; 32],
+    nonce: u128,
+}
+
+/// Queues the `submit_like` circuit for a like of the given weight; shared by
+/// `submit_like` and `submit_super_like`, which differ only in the weight and
+/// the quota they draw from
+fn queue_submit_like(
+    ctx: Context<SubmitLike>,
+    computation_offset: u64,
+    action: EncryptedLikeAction,
+    like_weight: u8,
+) -> Result<()> {
+    let EncryptedLikeAction {
+        user_id: encrypted_user_id,
+        target_id: encrypted_target_id,
+        like_action: encrypted_like_action,
+        timestamp: encrypted_timestamp,
+        intent_strength: encrypted_intent_strength,
+        pub_key,
+        nonce,
+    } = action;
+    let match_session = &ctx.accounts.match_pair_session;
+    let signer = ctx.accounts.user.key();
+    require_session_state(match_session, SessionOperation::SubmitLike)?;
+
+    // The encrypted target id cannot be checked here, but a session
+    // opened before self-pairs were rejected must not take likes
+    require_keys_neq!(match_session.user_a, match_session.user_b, ErrorCode::CannotMatchSelf);
+
+    // Validate user authorization: a participant or their authorized relayer
+    let liker = if signer == match_session.user_a || signer == match_session.user_b {
+        signer
+    } else {
+        let like_delegate = ctx
+            .accounts
+            .like_delegate
+            .as_ref()
+            .ok_or(ErrorCode::UnauthorizedUser)?;
+        require_keys_eq!(like_delegate.delegate, signer, ErrorCode::UnauthorizedDelegate);
+        require!(
+            like_delegate.owner == match_session.user_a ||
+            like_delegate.owner == match_session.user_b,
+            ErrorCode::UnauthorizedDelegate
+        );
+        like_delegate.owner
+    };
+    require_not_blocked(
+        &ctx.accounts.user_a_blocklist,
+        &match_session.user_a,
+        &ctx.accounts.user_b_blocklist,
+        &match_session.user_b,
+    )?;
+
+    // The target's like policy decides whether this liker may like them
+    let (liker_profile, target_profile) = if liker == match_session.user_a {
+        (&ctx.accounts.user_a_profile, &ctx.accounts.user_b_profile)
+    } else {
+        (&ctx.accounts.user_b_profile, &ctx.accounts.user_a_profile)
+    };
+    require!(
+        target_profile.accepts_like_from(liker_profile),
+        ErrorCode::LikePolicyViolation
+    );
+    let (liker_profile_key, target_profile_key) = (liker_profile.key(), target_profile.key());
+
+    // Consume the liker's nonce so like ciphertexts never reuse one
+    let nonce_counter = &mut ctx.accounts.nonce_counter;
+    require_keys_eq!(nonce_counter.owner, liker, ErrorCode::UnauthorizedUser);
+    nonce_counter.consume(nonce)?;
+
+    // A session idle past the expiry window takes no more likes, even
+    // before anyone has expired it
+    let now = Clock::get()?.unix_timestamp;
+    let idle_ttl = session_idle_ttl(
+        &ctx.accounts.user_a_profile,
+        &ctx.accounts.user_b_profile,
+        &ctx.accounts.config,
+        now,
+    );
+    require!(
+        !ctx.accounts.match_pair_session.is_expired(now, idle_ttl),
+        ErrorCode::SessionExpired
+    );
+
+    // Count the like against the liker's daily quota, relayed or not; super
+    // likes draw from their own, smaller quota
+    let like_quota = &mut ctx.accounts.like_quota;
+    like_quota.owner = liker;
+    like_quota.bump = ctx.bumps.like_quota;
+    if like_weight == LIKE_WEIGHT_SUPER {
+        like_quota.consume_super_like(now)?;
+    } else {
+        like_quota.consume(now, ctx.accounts.config.max_daily_likes)?;
+    }
+
+    // Record the in-flight computation so its callback can be verified,
+    // and refresh activity so a racing expiry sees the session as live
+    let match_session = &mut ctx.accounts.match_pair_session;
+    match_session.begin_computation(COMP_DEF_OFFSET_SUBMIT_LIKE, computation_offset, now)?;
+    match_session.last_updated = now;
+    let match_session = &ctx.accounts.match_pair_session;
+
+    // Prepare encrypted arguments for MPC computation
+    let args = vec![
+        Argument::ArcisPubkey(pub_key),
+        Argument::PlaintextU128(nonce),
+        Argument::EncryptedU8(encrypted_user_id),
+        Argument::EncryptedU8(encrypted_target_id), 
+        Argument::EncryptedU8(encrypted_like_action),
+        Argument::EncryptedU8(encrypted_timestamp),
+        Argument::EncryptedU8(encrypted_intent_strength),
+        Argument::PlaintextU128(match_session.nonce),
+        Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
+        Argument::PlaintextU128(match_session.intents_nonce),
+        Argument::Account(match_session.key(), MatchPairSession::ENCRYPTED_INTENTS_OFFSET, 32 * 2),
+        Argument::PlaintextU8(like_weight),
+    ];
+
+    require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+    // Queue encrypted like computation
+    let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+    #[cfg(not(feature = "mock-mpc"))]
+    queue_computation(
+        ctx.accounts,
+        computation_offset,
+        args,
+        vec![
+            CallbackAccount {
+                pubkey: match_session.key(),
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: liker_profile_key,
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: target_profile_key,
+                is_writable: true,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.computation_account.key(),
+                is_writable: false,
+            },
+        ],
+        None,
+    )?;
+    let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+    ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+    // Mock backend: run the plaintext circuit and apply its result inline
+    #[cfg(feature = "mock-mpc")]
+    {
+        let _ = args;
+        let like_action = mock_mpc::LikeAction {
+            user_id: mock_mpc::decode_value(&encrypted_user_id) as u64,
+            target_id: mock_mpc::decode_value(&encrypted_target_id) as u64,
+            like_action: mock_mpc::decode_value(&encrypted_like_action) != 0,
+            timestamp: mock_mpc::decode_value(&encrypted_timestamp) as u64,
+            intent_strength: mock_mpc::decode_value(&encrypted_intent_strength) as u8,
+        };
+        let session_key = ctx.accounts.match_pair_session.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        let current = mock_mpc::MatchSessionState::decode(&match_session.encrypted_match_data);
+        let intents = mock_mpc::LikeIntentsState::decode(&match_session.encrypted_intents);
+        let (updated, updated_intents, status_flag) =
+            mock_mpc::submit_like(&like_action, like_weight, current, intents);
+        match_session.complete_computation(session_key, COMP_DEF_OFFSET_SUBMIT_LIKE, ctx.accounts.computation_account.key())?;
+        let next_nonce = match_session.nonce.wrapping_add(1);
+        let next_intents_nonce = match_session.intents_nonce.wrapping_add(1);
+        let (liker_profile, target_profile) = if liker == match_session.user_a {
+            (&mut ctx.accounts.user_a_profile, &mut ctx.accounts.user_b_profile)
+        } else {
+            (&mut ctx.accounts.user_b_profile, &mut ctx.accounts.user_a_profile)
+        };
+        apply_submit_like(
+            match_session,
+            liker_profile,
+            target_profile,
+            SessionCiphertexts {
+                match_data: updated.encode(),
+                nonce: next_nonce,
+                intents: updated_intents.encode(),
+                intents_nonce: next_intents_nonce,
+            },
+            status_flag,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Stores the updated encrypted session state after a like and counts it on
+/// both profiles
+fn apply_submit_like(
+    match_session: &mut MatchPairSession,
+    liker_profile: &mut UserProfile,
+    target_profile: &mut UserProfile,
+    state: SessionCiphertexts,
+    status_flag: u8,
+) -> Result<()> {
+    // Any status outside {0, 1, 2, 4} means the circuit and program disagree
+    if status_flag == 3 || status_flag > 4 {
+        msg!("Warning: unexpected submit_like status flag {}", status_flag);
+        return Err(ErrorCode::UnexpectedStatusFlag.into());
+    }
+
+    // The profiles were fixed when the like was queued; they must be this
+    // session's two participants
+    require!(
+        (liker_profile.owner == match_session.user_a && target_profile.owner == match_session.user_b) ||
+        (liker_profile.owner == match_session.user_b && target_profile.owner == match_session.user_a),
+        ErrorCode::UnauthorizedUser
+    );
+
+    match_session.store_ciphertexts(state);
+    match_session.last_updated = Clock::get()?.unix_timestamp;
+
+    if status_flag != 0 {
+        liker_profile.total_likes_given = liker_profile
+            .total_likes_given
+            .checked_add(1)
+            .ok_or(ErrorCode::CounterOverflow)?;
+        target_profile.total_likes_received = target_profile
+            .total_likes_received
+            .checked_add(1)
+            .ok_or(ErrorCode::CounterOverflow)?;
+    }
+
+    match status_flag {
+        1 => {
+            emit!(LikeSubmittedEvent {
+                session_id: match_session.session_id,
+                timestamp: match_session.last_updated,
+            });
+            msg!("Like action recorded successfully");
+        },
+        2 => {
+            match_session.is_ready_for_verification = true;
+            msg!("Mutual interest detected! Session ready for verification");
+            
+            emit!(MutualInterestDetectedEvent {
+                session_id: match_session.session_id,
+                timestamp: match_session.last_updated,
+            });
+        },
+        4 => {
+            emit!(SuperLikeSubmittedEvent {
+                session_id: match_session.session_id,
+                timestamp: match_session.last_updated,
+            });
+            msg!("Super like recorded successfully");
+        },
+        _ => {
+            msg!("Like action not processed (duplicate or not a participant)");
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores the encrypted session state after a withdrawal attempt
+fn apply_withdraw_like(
+    match_session: &mut MatchPairSession,
+    state: SessionCiphertexts,
+    status_flag: u8,
+) -> Result<()> {
+    // Any status outside {0, 1} means the circuit and program disagree
+    if status_flag > 1 {
+        msg!("Warning: unexpected withdraw_like status flag {}", status_flag);
+        return Err(ErrorCode::UnexpectedStatusFlag.into());
+    }
+
+    match_session.store_ciphertexts(state);
+    match_session.last_updated = Clock::get()?.unix_timestamp;
+
+    if status_flag == 1 {
+        emit!(LikeWithdrawnEvent {
+            session_id: match_session.session_id,
+            timestamp: match_session.last_updated,
+        });
+        msg!("Like withdrawn");
+    } else {
+        msg!("Nothing to withdraw (no like on record or interest already mutual)");
+    }
+
+    Ok(())
+}
+
+/// Stores the session state re-encrypted under a fresh nonce
+fn apply_rotate_session_nonce(
+    match_session: &mut MatchPairSession,
+    ciphertexts: [[u8; 32]; 6],
+    nonce: u128,
+) -> Result<()> {
+    match_session.encrypted_match_data = ciphertexts;
+    match_session.nonce = nonce;
+
+    emit!(SessionNonceRotatedEvent {
+        session_id: match_session.session_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Finalizes the session as a no-match when the pass was applied
+fn apply_submit_pass(
+    match_session: &mut MatchPairSession,
+    platform_stats: &mut PlatformStats,
+    user_a_profile: &mut UserProfile,
+    user_b_profile: &mut UserProfile,
+    status_flag: u8,
+) -> Result<()> {
+    match status_flag {
+        // The circuit's STATUS_PASSED, distinct from check_mutual_match's 3 (vetoed)
+        5 => {
+            let now = Clock::get()?.unix_timestamp;
+            match_session.is_finalized = true;
+            match_session.match_found = false;
+            match_session.finalization_reason = FINALIZATION_REASON_PASSED;
+            match_session.last_updated = now;
+            platform_stats.finalized_sessions = platform_stats.finalized_sessions.saturating_add(1);
+            user_a_profile.open_sessions = user_a_profile.open_sessions.saturating_sub(1);
+            user_b_profile.open_sessions = user_b_profile.open_sessions.saturating_sub(1);
+
+            emit!(NoMutualMatchEvent {
+                session_id: match_session.session_id,
+                finalized_at: now,
+                total_compute_fees: match_session.total_compute_fees,
+            });
+            msg!("No mutual match - a participant passed");
+        },
+        0 => {
+            msg!("Pass not processed (not a participant or interest already mutual)");
+        },
+        // Any other status means the circuit and program disagree
+        _ => {
+            msg!("Warning: unexpected submit_pass status flag {}", status_flag);
+            return Err(ErrorCode::UnexpectedStatusFlag.into());
+        },
+    }
+
+    Ok(())
+}
+
+/// The revealed fields of the `check_mutual_match` result the program acts on
+struct MatchVerdict {
+    is_mutual_match: bool,
+    session_status: u8,
+    confidence: u8,
+    compatibility_score: u8,
+}
+
+/// Finalizes the session with the revealed match result
+fn apply_check_mutual_match(
+    match_session: &mut MatchPairSession,
+    platform_stats: &mut PlatformStats,
+    user_a_profile: &mut UserProfile,
+    user_b_profile: &mut UserProfile,
+    verdict: MatchVerdict,
+) -> Result<()> {
+    let MatchVerdict { is_mutual_match, session_status, confidence, compatibility_score } = verdict;
+    match_session.is_finalized = true;
+    match_session.finalization_reason = FINALIZATION_REASON_CHECKED;
+    platform_stats.finalized_sessions = platform_stats.finalized_sessions.saturating_add(1);
+    user_a_profile.open_sessions = user_a_profile.open_sessions.saturating_sub(1);
+    user_b_profile.open_sessions = user_b_profile.open_sessions.saturating_sub(1);
+
+    if is_mutual_match {
+        match_session.match_found = true;
+        match_session.matched_at = Clock::get()?.unix_timestamp;
+        match_session.match_confidence = confidence;
+        platform_stats.total_matches = platform_stats.total_matches.saturating_add(1);
+        for profile in [&mut *user_a_profile, &mut *user_b_profile] {
+            profile.total_matches = profile.total_matches.checked_add(1).ok_or(ErrorCode::CounterOverflow)?;
+        }
+        let (session_id, matched_at) = (match_session.session_id, match_session.matched_at);
+        user_a_profile.push_match_record(session_id, matched_at, match_session.user_b)?;
+        user_b_profile.push_match_record(session_id, matched_at, match_session.user_a)?;
+        
+        emit!(MutualMatchFoundEvent {
+            session_id: match_session.session_id,
+            user_a: match_session.user_a,
+            user_b: match_session.user_b,
+            matched_at: Clock::get()?.unix_timestamp,
+            can_start_conversation: true,
+            total_compute_fees: match_session.total_compute_fees,
+            confidence,
+            compatibility_score,
+        });
+
+        msg!("Mutual match confirmed! Both users liked each other!");
+    } else {
+        match_session.match_found = false;
+        
+        emit!(NoMutualMatchEvent {
+            session_id: match_session.session_id,
+            finalized_at: Clock::get()?.unix_timestamp,
+            total_compute_fees: match_session.total_compute_fees,
+        });
+
+        let status_msg = match session_status {
+            0 => "Pending - only one user has acted",
+            2 => "No match - both users passed or no actions",
+            3 => "No match - a dealbreaker vetoed the mutual like",
+            _ => "Unknown status"
+        };
+        msg!("No mutual match found - {}", status_msg);
+    }
+
+    msg!("Match session finalized - session_id: {}", match_session.session_id);
+
+    Ok(())
+}
+
+/// Publishes a pair's revealed score with each user's encrypted breakdown,
+/// given as (nonce, ciphertexts)
+fn apply_calculate_compatibility(
+    user_a: Pubkey,
+    user_b: Pubkey,
+    score: u8,
+    age_gate_passed: bool,
+    user_a_breakdown: (u128, [[u8; 32]; 5]),
+    user_b_breakdown: (u128, [[u8; 32]; 5]),
+) -> Result<()> {
+    require!(age_gate_passed, ErrorCode::AgeGateFailed);
+
+    emit!(CompatibilityCalculatedEvent {
+        user_a,
+        user_b,
+        score,
+        user_a_breakdown: user_a_breakdown.1,
+        user_a_breakdown_nonce: user_a_breakdown.0,
+        user_b_breakdown: user_b_breakdown.1,
+        user_b_breakdown_nonce: user_b_breakdown.0,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Caches a batch's revealed scores
+fn apply_calculate_compatibility_batch(
+    batch: &mut CompatibilityBatch,
+    scores: [u8; COMPATIBILITY_BATCH_SIZE],
+    age_gate_passed: bool,
+) -> Result<()> {
+    require!(age_gate_passed, ErrorCode::AgeGateFailed);
+
+    batch.scores = scores;
+    batch.scored_at = Clock::get()?.unix_timestamp;
+
+    emit!(CompatibilityBatchScoredEvent {
+        owner: batch.owner,
+        candidate_count: batch.candidate_count,
+        scores,
+        timestamp: batch.scored_at,
+    });
+
+    Ok(())
+}
+
+/// Publishes each participant's disclosed fields, re-encrypted to the other
+/// and given as (nonce, ciphertexts)
+fn apply_reveal_matched_data(
+    match_session: &MatchPairSession,
+    for_user_a: (u128, [[u8; 32]; 2]),
+    for_user_b: (u128, [[u8; 32]; 2]),
+    both_consented: bool,
+) -> Result<()> {
+    emit!(MatchedDataRevealedEvent {
+        session_id: match_session.session_id,
+        both_consented,
+        user_a_nonce: for_user_a.0,
+        user_a_ciphertexts: for_user_a.1,
+        user_b_nonce: for_user_b.0,
+        user_b_ciphertexts: for_user_b.1,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    if both_consented {
+        msg!("Both users consented - private data revealed to the matched pair");
+    } else {
+        msg!("Disclosure withheld - consent missing from at least one user");
+    }
+
+    Ok(())
+}
+
+/// Publishes each participant's icebreaker answer, re-encrypted to the other
+/// and given as (nonce, ciphertexts)
+fn apply_reveal_icebreakers(
+    match_session: &MatchPairSession,
+    for_user_a: (u128, [[u8; 32]; 4]),
+    for_user_b: (u128, [[u8; 32]; 4]),
+    is_mutual_match: bool,
+) -> Result<()> {
+    emit!(IcebreakersRevealedEvent {
+        session_id: match_session.session_id,
+        is_mutual_match,
+        user_a_nonce: for_user_a.0,
+        user_a_ciphertexts: for_user_a.1,
+        user_b_nonce: for_user_b.0,
+        user_b_ciphertexts: for_user_b.1,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Stores the updated reputation aggregate, given as (nonce, ciphertexts),
+/// if the rating was accepted, and releases the reputation for the next one
+fn apply_submit_feedback(
+    match_session: &mut MatchPairSession,
+    reputation: &mut Reputation,
+    encrypted_reputation: (u128, [[u8; 32]; 2]),
+    accepted: bool,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    reputation.pending_since = 0;
+
+    let rater = if reputation.owner == match_session.user_a {
+        match_session.user_b
+    } else {
+        match_session.user_a
+    };
+
+    // A rejected rating leaves the aggregate as it was and can be resubmitted
+    if accepted {
+        reputation.encrypted_reputation = encrypted_reputation.1;
+        reputation.nonce = encrypted_reputation.0;
+        reputation.initialized = true;
+        reputation.updated_at = now;
+        if rater == match_session.user_a {
+            match_session.user_a_rated = true;
+        } else {
+            match_session.user_b_rated = true;
+        }
+    }
+
+    emit!(FeedbackSubmittedEvent {
+        session_id: match_session.session_id,
+        rater,
+        accepted,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+// ============================================================================
+// MAIN PROGRAM MODULE
+// ============================================================================
+
+#[arcium_program]
+pub mod contract {
+    use super::*;
+
+    // ========================================================================
+    // PROFILE MANAGEMENT FUNCTIONS
+    // ========================================================================
+
+    /// Reserves a username for the caller ahead of `create_profile`. Only the
+    /// hash is submitted, so the name itself stays out of the mempool until
+    /// the reservation is consumed.
+    pub fn reserve_username(ctx: Context<ReserveUsername>, username_hash: [u8; 32]) -> Result<()> {
+        let reservation = &mut ctx.accounts.username_reservation;
+        reservation.reserver = ctx.accounts.user.key();
+        reservation.username_hash = username_hash;
+        reservation.expires_at = Clock::get()?.unix_timestamp + USERNAME_RESERVATION_TTL;
+        reservation.bump = ctx.bumps.username_reservation;
+
+        emit!(UsernameReservedEvent {
+            reserver: reservation.reserver,
+            username_hash,
+            expires_at: reservation.expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Releases a username reservation, refunding its rent to the reserver.
+    /// The reserver may release at any time; anyone else once it has expired.
+    pub fn release_username_reservation(ctx: Context<ReleaseUsernameReservation>) -> Result<()> {
+        let reservation = &ctx.accounts.username_reservation;
+        require!(
+            ctx.accounts.caller.key() == reservation.reserver ||
+            reservation.is_expired(Clock::get()?.unix_timestamp),
+            ErrorCode::UsernameReservationActive
+        );
+        Ok(())
+    }
+
+    /// Creates a new user profile with encrypted sensitive data, consuming
+    /// the caller's reservation of the username
+    pub fn create_profile(
+        ctx: Context<CreateProfile>,
+        profile_data: CreateProfileData,
+    ) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let clock = Clock::get()?;
+        
+        // Input validation
+        validate_profile_data(&profile_data)?;
+
+        // Only the signer's own, unexpired reservation can be consumed
+        let reservation = &ctx.accounts.username_reservation;
+        require_keys_eq!(reservation.reserver, ctx.accounts.user.key(), ErrorCode::InvalidUsernameReservation);
+        require!(!reservation.is_expired(clock.unix_timestamp), ErrorCode::UsernameReservationExpired);
+
+        claim_username(
+            &mut ctx.accounts.username_registry,
+            ctx.accounts.user.key(),
+            &profile_data.username,
+            ctx.bumps.username_registry,
+        )?;
+
+        // Set account metadata
+        user_profile.owner = ctx.accounts.user.key();
+        user_profile.bump = ctx.bumps.user_profile;
+        user_profile.created_at = clock.unix_timestamp;
+        user_profile.last_updated = clock.unix_timestamp;
+        user_profile.last_seen = clock.unix_timestamp;
+        user_profile.profile_version = profile_data.profile_version;
+        
+        // Set public profile information
+        user_profile.username = profile_data.username.clone();
+        user_profile.avatar_url = profile_data.avatar_url;
+        user_profile.age = profile_data.age;
+        user_profile.location_city = profile_data.location_city.clone();
+        user_profile.is_active = true;
+        
+        // Set encryption data
+        user_profile.encryption_pubkey = profile_data.encryption_pubkey;
+        user_profile.encrypted_private_data = profile_data.encrypted_private_data;
+        user_profile.encrypted_preferences = profile_data.encrypted_preferences;
+        
+        // Initialize interaction history
+        user_profile.encrypted_likes_given = Vec::new();
+        user_profile.encrypted_likes_received = Vec::new();
+        user_profile.match_history = Vec::new();
+        user_profile.total_likes_given = 0;
+        user_profile.total_likes_received = 0;
+        user_profile.total_matches = 0;
+        user_profile.icebreaker_answer = Vec::new();
+        user_profile.matching_paused = false;
+        user_profile.boost_until = 0;
+        user_profile.privacy_level = PRIVACY_LEVEL_PUBLIC;
+        user_profile.like_policy = LIKE_POLICY_ANYONE;
+        user_profile.like_age_min = 0;
+        user_profile.like_age_max = 0;
+        user_profile.layout_version = PROFILE_LAYOUT_VERSION;
+        user_profile.archived_match_records = 0;
+        user_profile.open_sessions = 0;
+        user_profile.gender = profile_data.gender;
+        user_profile.orientation = profile_data.orientation;
+        user_profile.photo_urls = profile_data.photo_urls;
+        user_profile.bio = profile_data.bio;
+
+        // Update platform stats
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.total_profiles = platform_stats.total_profiles.saturating_add(1);
+        platform_stats.active_profiles = platform_stats.active_profiles.saturating_add(1);
+        
+        // Emit profile creation event
+        emit!(ProfileCreatedEvent {
+            user: ctx.accounts.user.key(),
+            profile_pda: user_profile.key(),
+            username: profile_data.username,
+            age: profile_data.age,
+            location_city: profile_data.location_city,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        msg!("Profile created successfully for user: {}", ctx.accounts.user.key());
+        Ok(())
+    }
+
+    /// Changes the owner's public profile fields in place, keeping the
+    /// encrypted data and interaction history
+    pub fn update_profile(ctx: Context<UpdateProfile>, update: UpdateProfileData) -> Result<()> {
+        let profile_info = ctx.accounts.user_profile.to_account_info();
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+
+        let username = update.username.unwrap_or_else(|| user_profile.username.clone());
+        let avatar_url = update.avatar_url.unwrap_or_else(|| user_profile.avatar_url.clone());
+        let age = update.age.unwrap_or(user_profile.age);
+        let location_city = update.location_city.unwrap_or_else(|| user_profile.location_city.clone());
+        validate_public_fields(&username, &avatar_url, age, &location_city)?;
+        let bio = update.bio.unwrap_or_else(|| user_profile.bio.clone());
+        validate_bio(&bio)?;
+
+        // A rename moves the registry entry, unless only the case changed
+        if username_hash(&username) != username_hash(&user_profile.username) {
+            let new_registry = ctx
+                .accounts
+                .new_username_registry
+                .as_mut()
+                .ok_or(ErrorCode::UsernameRegistryMissing)?;
+            let bump = ctx.bumps.new_username_registry.ok_or(ErrorCode::UsernameRegistryMissing)?;
+            claim_username(new_registry, user_profile.owner, &username, bump)?;
+            release_username(
+                &ctx.accounts.old_username_registry.to_account_info(),
+                user_profile.owner,
+                ctx.accounts.user.to_account_info(),
+            )?;
+        }
+
+        let clock = Clock::get()?;
+        let mut affected_fields = 0;
+        if age != user_profile.age {
+            affected_fields |= MATCHING_FIELD_AGE;
+        }
+        if location_city != user_profile.location_city {
+            affected_fields |= MATCHING_FIELD_LOCATION;
+        }
+        if affected_fields != 0 {
+            emit!(MatchingDataChangedEvent {
+                user: user_profile.owner,
+                affected_fields,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        user_profile.username = username;
+        user_profile.set_avatar(avatar_url);
+        user_profile.age = age;
+        user_profile.location_city = location_city;
+        user_profile.bio = bio;
+        user_profile.last_updated = clock.unix_timestamp;
+
+        // A longer avatar also lengthens the gallery's first photo
+        grow_account(
+            &profile_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            user_profile.account_len(),
+        )?;
+
+        emit!(ProfileUpdatedEvent {
+            user: user_profile.owner,
+            username: user_profile.username.clone(),
+            avatar_url: user_profile.avatar_url.clone(),
+            age: user_profile.age,
+            location_city: user_profile.location_city.clone(),
+            bio: user_profile.bio.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Profile updated for user: {}", user_profile.owner);
+        Ok(())
+    }
+
+    /// Appends a photo to the caller's gallery, growing the profile account to
+    /// fit it. A profile with only an avatar starts its gallery with the avatar.
+    pub fn add_photo(ctx: Context<ManagePhotos>, photo_url: String) -> Result<()> {
+        let profile_info = ctx.accounts.user_profile.to_account_info();
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        validate_photo_url(&photo_url)?;
+
+        if user_profile.photo_urls.is_empty() {
+            let avatar_url = user_profile.avatar_url.clone();
+            user_profile.photo_urls.push(avatar_url);
+        }
+        require!(user_profile.photo_urls.len() < MAX_PHOTOS, ErrorCode::TooManyPhotos);
+        user_profile.photo_urls.push(photo_url);
+
+        grow_account(
+            &profile_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            user_profile.account_len(),
+        )?;
+        emit_photos_updated(user_profile)
+    }
+
+    /// Removes the photo at `index`; removing the first photo promotes the
+    /// next one to avatar. The last photo cannot be removed.
+    pub fn remove_photo(ctx: Context<ManagePhotos>, index: u8) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        require!((index as usize) < user_profile.photo_urls.len(), ErrorCode::InvalidPhotoIndex);
+        require!(user_profile.photo_urls.len() > 1, ErrorCode::AvatarRequired);
+
+        user_profile.photo_urls.remove(index as usize);
+        user_profile.avatar_url = user_profile.photo_urls[0].clone();
+        emit_photos_updated(user_profile)
+    }
+
+    /// Reorders the gallery: `order[i]` is the current index of the photo
+    /// that moves to position `i`. The new first photo becomes the avatar.
+    pub fn reorder_photos(ctx: Context<ManagePhotos>, order: Vec<u8>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+
+        let count = user_profile.photo_urls.len();
+        let mut seen = [false; MAX_PHOTOS];
+        require!(order.len() == count, ErrorCode::InvalidPhotoIndex);
+        for &index in &order {
+            let index = index as usize;
+            require!(index < count && !seen[index], ErrorCode::InvalidPhotoIndex);
+            seen[index] = true;
+        }
+
+        let reordered: Vec<String> = order
+            .iter()
+            .map(|&index| user_profile.photo_urls[index as usize].clone())
+            .collect();
+        user_profile.photo_urls = reordered;
+        if let Some(first) = user_profile.photo_urls.first() {
+            user_profile.avatar_url = first.clone();
+        }
+        emit_photos_updated(user_profile)
+    }
+
+    /// Hides the owner's profile while they take a break from the app
+    pub fn deactivate_profile(ctx: Context<SetProfileActive>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        require!(user_profile.is_active, ErrorCode::ProfileStateUnchanged);
+
+        let clock = Clock::get()?;
+        user_profile.is_active = false;
+        user_profile.last_updated = clock.unix_timestamp;
+
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.active_profiles = platform_stats.active_profiles.saturating_sub(1);
+
+        emit!(ProfileDeactivatedEvent {
+            user: user_profile.owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Brings a deactivated profile back
+    pub fn reactivate_profile(ctx: Context<SetProfileActive>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        require!(!user_profile.is_active, ErrorCode::ProfileStateUnchanged);
+
+        let clock = Clock::get()?;
+        user_profile.is_active = true;
+        user_profile.last_updated = clock.unix_timestamp;
+
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.active_profiles = platform_stats.active_profiles.saturating_add(1);
+
+        emit!(ProfileReactivatedEvent {
+            user: user_profile.owner,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Deletes the owner's profile and returns its rent. Refused while any
+    /// session involving the user is still open, since finalizing it needs
+    /// both profiles
+    pub fn close_profile(ctx: Context<CloseProfile>) -> Result<()> {
+        let user_profile = &ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        require!(user_profile.open_sessions == 0, ErrorCode::HasActiveSessions);
+
+        release_username(
+            &ctx.accounts.username_registry.to_account_info(),
+            user_profile.owner,
+            ctx.accounts.user.to_account_info(),
+        )?;
+
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.total_profiles = platform_stats.total_profiles.saturating_sub(1);
+        if user_profile.is_active {
+            platform_stats.active_profiles = platform_stats.active_profiles.saturating_sub(1);
+        }
+
+        emit!(ProfileClosedEvent {
+            user: user_profile.owner,
+            profile_pda: user_profile.key(),
+            closed_at: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Profile closed for user: {}", user_profile.owner);
+        Ok(())
+    }
+
+    /// Pauses or resumes new match sessions targeting the user; existing
+    /// sessions and discovery are unaffected
+    pub fn set_matching_paused(ctx: Context<SetMatchingPaused>, paused: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.matching_paused = paused;
+        user_profile.last_updated = clock.unix_timestamp;
+
+        emit!(MatchingPausedEvent {
+            user: ctx.accounts.user.key(),
+            paused,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Records the owner as seen now, so clients can show who was online
+    /// recently. Called often, so it emits nothing and leaves last_updated alone.
+    pub fn heartbeat(ctx: Context<Heartbeat>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        user_profile.last_seen = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Answers a backend-issued challenge: the profile owner signs over
+    /// `nonce`, and the backend matches the emitted event to its challenge
+    pub fn prove_profile_ownership(ctx: Context<ProveProfileOwnership>, nonce: [u8; 32]) -> Result<()> {
+        emit!(ProfileOwnershipProvenEvent {
+            user: ctx.accounts.user_profile.owner,
+            nonce,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replaces the user's encrypted matching preferences
+    pub fn set_preferences(ctx: Context<SetPreferences>, encrypted_preferences: Vec<u8>) -> Result<()> {
+        require!(encrypted_preferences.len() <= 500, ErrorCode::PreferencesTooLarge);
+
+        let clock = Clock::get()?;
+        let space = ctx.accounts.user_profile.to_account_info().data_len();
+        let user_profile = &mut ctx.accounts.user_profile;
+        validate_encrypted_blobs(&user_profile.encrypted_private_data, &encrypted_preferences)?;
+
+        if user_profile.encrypted_preferences != encrypted_preferences {
+            require!(
+                8 + user_profile.required_space() - user_profile.encrypted_preferences.len() +
+                    encrypted_preferences.len() <= space,
+                ErrorCode::ProfileTooSmall
+            );
+            user_profile.encrypted_preferences = encrypted_preferences;
+            emit!(MatchingDataChangedEvent {
+                user: user_profile.owner,
+                affected_fields: MATCHING_FIELD_PREFERENCES,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+        user_profile.last_updated = clock.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Sets (or clears, when empty) the user's encrypted icebreaker answer
+    pub fn set_icebreaker_answer(
+        ctx: Context<SetIcebreakerAnswer>,
+        icebreaker_answer: Vec<u8>,
+    ) -> Result<()> {
+        require!(
+            icebreaker_answer.is_empty() || icebreaker_answer.len() == ICEBREAKER_ANSWER_LEN,
+            ErrorCode::InvalidIcebreakerAnswer
+        );
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.icebreaker_answer = icebreaker_answer;
+        user_profile.last_updated = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Runs the `create_profile` checks only, so clients can show inline
+    /// errors before paying rent; touches no accounts
+    pub fn validate_profile_input(
+        _ctx: Context<ValidateProfileInput>,
+        profile_data: CreateProfileData,
+    ) -> Result<()> {
+        validate_profile_data(&profile_data)
+    }
+
+    /// Checks plaintext matching preferences against the circuit's fixed
+    /// bounds before the client encrypts them; touches no accounts
+    pub fn validate_matching_preferences(
+        _ctx: Context<ValidateProfileInput>,
+        preferences: MatchingPreferences,
+    ) -> Result<()> {
+        preferences.validate()
+    }
+
+    /// Returns the public profile plus derived discovery flags, redacted per
+    /// the owner's privacy level unless the caller is the owner or passes a
+    /// session proving they matched with them
+    pub fn get_profile_view(ctx: Context<GetProfileView>) -> Result<ProfileView> {
+        let user_profile = &ctx.accounts.user_profile;
+        let full_access = match &ctx.accounts.caller {
+            Some(caller) => {
+                let caller = caller.key();
+                let matched = ctx.accounts.match_proof.as_ref().is_some_and(|session| {
+                    session.is_finalized &&
+                        session.match_found &&
+                        ((session.user_a == caller && session.user_b == user_profile.owner) ||
+                            (session.user_b == caller && session.user_a == user_profile.owner))
+                });
+                caller == user_profile.owner || matched
+            }
+            None => false,
+        };
+
+        Ok(user_profile.to_view_for(full_access))
+    }
+
+    /// Sets which public fields non-matched viewers can see
+    pub fn set_profile_private(ctx: Context<SetProfilePrivacy>, privacy_level: u8) -> Result<()> {
+        require!(privacy_level <= PRIVACY_LEVEL_HIDDEN, ErrorCode::InvalidPrivacyLevel);
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.privacy_level = privacy_level;
+        user_profile.last_updated = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Sets who may like the caller's profile; the age bounds are used by the
+    /// mutual-age-range policy, 0 leaving that side unbounded
+    pub fn set_like_policy(
+        ctx: Context<SetLikePolicy>,
+        like_policy: u8,
+        like_age_min: u8,
+        like_age_max: u8,
+    ) -> Result<()> {
+        require!(like_policy <= LIKE_POLICY_MUTUAL_AGE_RANGE, ErrorCode::InvalidLikePolicy);
+        require!(
+            like_age_min == 0 || like_age_max == 0 || like_age_min <= like_age_max,
+            ErrorCode::InvalidLikePolicy
+        );
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.like_policy = like_policy;
+        user_profile.like_age_min = like_age_min;
+        user_profile.like_age_max = like_age_max;
+        user_profile.last_updated = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Moves the caller's profile to the current account layout: decodes
+    /// whichever layout it was written in, grows the account and rewrites it
+    /// with the appended fields defaulted. No-op when already current.
+    pub fn migrate_profile(ctx: Context<MigrateProfile>) -> Result<()> {
+        let profile_info = ctx.accounts.user_profile.to_account_info();
+        require_keys_eq!(*profile_info.owner, crate::ID, ErrorCode::ProfileNotFound);
+
+        let (from_version, mut user_profile) = migration::decode_profile(&profile_info.try_borrow_data()?)?;
+        require_keys_eq!(user_profile.owner, ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
+        if from_version >= PROFILE_LAYOUT_VERSION {
+            return Ok(());
+        }
+
+        grow_account(
+            &profile_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            user_profile.account_len(),
+        )?;
+        user_profile.layout_version = PROFILE_LAYOUT_VERSION;
+        user_profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ProfileMigratedEvent {
+            owner: user_profile.owner,
+            from_version,
+            to_version: PROFILE_LAYOUT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Reallocates the caller's profile `additional_space` bytes larger so its
+    /// encrypted vectors can grow, paying the extra rent. Profiles start at
+    /// `UserProfile::COMPACT_SPACE` plus their submitted data and can grow up
+    /// to the full layout.
+    pub fn grow_profile(ctx: Context<GrowProfile>, additional_space: u32) -> Result<()> {
+        let profile_info = ctx.accounts.user_profile.to_account_info();
+        let space = migration::self_describing_len(profile_info.data_len() + additional_space as usize);
+        require!(space <= 8 + UserProfile::INIT_SPACE, ErrorCode::ProfileGrowthExceeded);
+
+        grow_account(
+            &profile_info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+        )?;
+
+        emit!(ProfileGrownEvent {
+            owner: ctx.accounts.user_profile.owner,
+            space: space as u32,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the profile's public match counter without fetching any matches
+    pub fn get_match_history_count(ctx: Context<GetProfileView>) -> Result<u32> {
+        Ok(ctx.accounts.user_profile.total_matches)
+    }
+
+    /// Returns `LIKES_PAGE_LEN` bytes of the profile's encrypted likes given
+    /// (or received) from byte `offset`, a multiple of
+    /// `ENCRYPTED_LIKE_RECORD_LEN`, for clients whose RPC responses are too
+    /// small for the whole history
+    pub fn get_likes_page(ctx: Context<GetProfileView>, received: bool, offset: u32) -> Result<BytePage> {
+        let user_profile = &ctx.accounts.user_profile;
+        let history = if received {
+            &user_profile.encrypted_likes_received
+        } else {
+            &user_profile.encrypted_likes_given
+        };
+        byte_page(history, offset, LIKES_PAGE_LEN, ENCRYPTED_LIKE_RECORD_LEN)
+    }
+
+    /// Moves the oldest records of the caller's match history to the current
+    /// `MatchArchive` page so that fewer than `inline_match_cap` stay inline,
+    /// leaving room for the next confirmed match
+    pub fn archive_match_records(ctx: Context<ArchiveMatchRecords>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let match_archive = &mut ctx.accounts.match_archive;
+        let page = user_profile.archived_match_records / MATCH_ARCHIVE_PAGE_RECORDS as u32;
+        let inline = user_profile.inline_match_records();
+
+        if match_archive.owner == Pubkey::default() {
+            match_archive.owner = user_profile.owner;
+            match_archive.page = page;
+            match_archive.records = Vec::new();
+            match_archive.bump = ctx.bumps.match_archive;
+        }
+
+        // Spill enough of the oldest records to leave room under the cap,
+        // never past the end of this page; a page always has room for one,
+        // so a full blob (cap lowered after it filled) still drains
+        let cap = ctx.accounts.config.inline_match_cap as usize;
+        let room = MATCH_ARCHIVE_PAGE_RECORDS - match_archive.records.len();
+        let moved = (inline + 1).saturating_sub(cap).min(room);
+        if moved > 0 {
+            let spilled: Vec<u8> = user_profile.match_history
+                .drain(..moved * MATCH_RECORD_LEN)
+                .collect();
+            match_archive.records.extend(
+                spilled.chunks_exact(MATCH_RECORD_LEN).map(|chunk| <[u8; MATCH_RECORD_LEN]>::try_from(chunk).unwrap())
+            );
+            user_profile.archived_match_records = user_profile.archived_match_records.saturating_add(moved as u32);
+
+            emit!(MatchesArchivedEvent {
+                owner: user_profile.owner,
+                page,
+                moved: moved as u32,
+                archived_match_records: user_profile.archived_match_records,
+            });
+        }
+
+        user_profile.last_updated = Clock::get()?.unix_timestamp;
+
+        msg!("Match history: {} inline, {} archived", user_profile.inline_match_records(), user_profile.archived_match_records);
+        Ok(())
+    }
+
+    /// Returns one archive page of the caller's match history, oldest first;
+    /// the cursor is the page index
+    pub fn get_match_archive(ctx: Context<GetMatchArchive>, cursor: Option<u32>) -> Result<Paginated<[u8; MATCH_RECORD_LEN]>> {
+        let page = cursor.unwrap_or(0);
+        let next_page_start = (page as usize + 1) * MATCH_ARCHIVE_PAGE_RECORDS;
+        let next_cursor = if next_page_start < ctx.accounts.user_profile.archived_match_records as usize {
+            Some(page + 1)
+        } else {
+            None
+        };
+
+        Ok(Paginated {
+            items: ctx.accounts.match_archive.records.clone(),
+            next_cursor,
+        })
+    }
+
+    /// Returns one page of the caller's blocklist; only the owner can read it
+    pub fn get_blocklist(ctx: Context<GetBlocklist>, cursor: Option<u32>) -> Result<Paginated<Pubkey>> {
+        Ok(paginate(&ctx.accounts.blocklist.blocked, cursor, LIST_PAGE_SIZE))
+    }
+
+    /// Whether a block exists in either direction between the caller and
+    /// `target`, without exposing anyone's full blocklist
+    pub fn is_blocked(ctx: Context<IsBlocked>, target: Pubkey) -> Result<bool> {
+        let user = ctx.accounts.user.key();
+        let blocked_by_user = ctx.accounts.user_blocklist
+            .as_ref()
+            .is_some_and(|blocklist| blocklist.contains(&target));
+        let blocked_by_target = ctx.accounts.target_blocklist
+            .as_ref()
+            .is_some_and(|blocklist| blocklist.contains(&user));
+        Ok(blocked_by_user || blocked_by_target)
+    }
+
+    /// Returns the recommended session TTL (seconds) for this user's activity
+    pub fn get_recommended_session_expiry(ctx: Context<GetRecommendedSessionExpiry>) -> Result<i64> {
+        let user_profile = &ctx.accounts.user_profile;
+        let config = &ctx.accounts.config;
+
+        Ok(recommended_session_ttl(
+            user_profile.last_active_at(),
+            user_profile.total_matches,
+            Clock::get()?.unix_timestamp,
+            config.min_session_ttl,
+            config.max_session_ttl,
+        ))
+    }
+
+    /// Reports whether the profile is currently boosted
+    pub fn get_boost_status(ctx: Context<BoostStatusAccounts>) -> Result<BoostStatus> {
+        let user_profile = &ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        Ok(BoostStatus {
+            is_boosted: user_profile.is_boosted(now),
+            boost_until: user_profile.boost_until,
+            remaining_seconds: user_profile.boost_until.saturating_sub(now).max(0),
+        })
+    }
+
+    /// Returns every public counter and flag of a profile in one call
+    pub fn get_user_stats(ctx: Context<GetUserStats>) -> Result<UserStats> {
+        let now = Clock::get()?.unix_timestamp;
+        Ok(ctx.accounts.user_profile.to_stats(now))
+    }
+
+    /// Clears an elapsed boost so indexers see the expiry; callable by anyone
+    pub fn expire_boost(ctx: Context<BoostStatusAccounts>) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            user_profile.boost_until != 0 && !user_profile.is_boosted(now),
+            ErrorCode::BoostStillActive
+        );
+
+        emit!(BoostExpiredEvent {
+            user: user_profile.owner,
+            expired_at: user_profile.boost_until,
+        });
+        user_profile.boost_until = 0;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // ADMIN FUNCTIONS
+    // ========================================================================
+
+    /// Creates the program config and platform stats; the signer, who must be
+    /// the upgrade authority, becomes admin
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.pending_admin = Pubkey::default();
+        config.min_session_ttl = DEFAULT_MIN_SESSION_TTL;
+        config.max_session_ttl = DEFAULT_MAX_SESSION_TTL;
+        config.match_archive_window = DEFAULT_MATCH_ARCHIVE_WINDOW;
+        config.min_account_age_secs = 0;
+        config.inline_match_cap = MAX_INLINE_MATCH_RECORDS;
+        config.max_daily_likes = MAX_DAILY_LIKES;
+        config.verification_authority = ctx.accounts.admin.key();
+        config.bump = ctx.bumps.config;
+
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.total_profiles = 0;
+        platform_stats.active_profiles = 0;
+        platform_stats.total_sessions = 0;
+        platform_stats.finalized_sessions = 0;
+        platform_stats.total_matches = 0;
+        platform_stats.last_rebuilt_at = 0;
+        platform_stats.bump = ctx.bumps.platform_stats;
+
+        emit!(ConfigInitializedEvent {
+            admin: config.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Program config initialized with admin: {}", config.admin);
+        Ok(())
+    }
+
+    /// Sets the bounds used for personalized session expiry
+    pub fn set_session_ttl_bounds(
+        ctx: Context<AdminConfig>,
+        min_session_ttl: i64,
+        max_session_ttl: i64,
+    ) -> Result<()> {
+        require!(
+            min_session_ttl > 0 && min_session_ttl <= max_session_ttl,
+            ErrorCode::InvalidSessionTtl
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.min_session_ttl = min_session_ttl;
+        config.max_session_ttl = max_session_ttl;
+
+        msg!("Session TTL bounds set to {}..{} seconds", min_session_ttl, max_session_ttl);
+        Ok(())
+    }
+
+    /// Sets how long an unstarted match stays active before it can be archived
+    pub fn set_match_archive_window(ctx: Context<AdminConfig>, match_archive_window: i64) -> Result<()> {
+        require!(match_archive_window > 0, ErrorCode::InvalidArchiveWindow);
+
+        ctx.accounts.config.match_archive_window = match_archive_window;
+
+        msg!("Match archive window set to {} seconds", match_archive_window);
+        Ok(())
+    }
+
+    /// Sets how long a profile must exist before it can start or receive sessions
+    pub fn set_min_account_age(ctx: Context<AdminConfig>, min_account_age_secs: i64) -> Result<()> {
+        require!(min_account_age_secs >= 0, ErrorCode::InvalidMinAccountAge);
+
+        ctx.accounts.config.min_account_age_secs = min_account_age_secs;
+
+        msg!("Minimum account age set to {} seconds", min_account_age_secs);
+        Ok(())
+    }
+
+    /// Sets how many match records stay inline before older ones are archived
+    pub fn set_inline_match_cap(ctx: Context<AdminConfig>, inline_match_cap: u8) -> Result<()> {
+        require!(
+            (1..=MAX_INLINE_MATCH_RECORDS).contains(&inline_match_cap),
+            ErrorCode::InvalidInlineMatchCap
+        );
+
+        ctx.accounts.config.inline_match_cap = inline_match_cap;
+
+        msg!("Inline match cap set to {} records", inline_match_cap);
+        Ok(())
+    }
+
+    /// Sets how many likes each user may submit per UTC day
+    pub fn set_max_daily_likes(ctx: Context<AdminConfig>, max_daily_likes: u16) -> Result<()> {
+        require!(max_daily_likes > 0, ErrorCode::InvalidDailyLikeLimit);
+
+        ctx.accounts.config.max_daily_likes = max_daily_likes;
+
+        msg!("Daily like limit set to {} likes", max_daily_likes);
+        Ok(())
+    }
+
+    /// Hands the verification authority to a new key, e.g. a KYC provider's
+    pub fn set_verification_authority(ctx: Context<AdminConfig>, verification_authority: Pubkey) -> Result<()> {
+        require!(
+            verification_authority != Pubkey::default(),
+            ErrorCode::InvalidVerificationAuthority
+        );
+
+        ctx.accounts.config.verification_authority = verification_authority;
+
+        msg!("Verification authority set to {}", verification_authority);
+        Ok(())
+    }
+
+    /// Sets or clears a profile's verified badge, e.g. once the user passes KYC
+    pub fn set_verification(ctx: Context<SetVerification>, verified: bool) -> Result<()> {
+        let user_profile = &mut ctx.accounts.user_profile;
+        user_profile.is_verified = verified;
+
+        emit!(ProfileVerifiedEvent {
+            user: user_profile.owner,
+            verified,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Boosts a profile's discovery ranking for `duration` seconds, extending
+    /// any boost that is still active
+    pub fn grant_boost(ctx: Context<GrantBoost>, duration: i64) -> Result<()> {
+        require!(duration > 0, ErrorCode::InvalidBoostDuration);
+
+        let user_profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+        user_profile.boost_until = user_profile.boost_until.max(now).saturating_add(duration);
+
+        emit!(BoostGrantedEvent {
+            user: user_profile.owner,
+            boost_until: user_profile.boost_until,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Step one of an admin handoff: the current admin nominates a successor
+    pub fn propose_admin(ctx: Context<AdminConfig>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(new_admin != Pubkey::default(), ErrorCode::InvalidAdmin);
+        require_keys_neq!(new_admin, config.admin, ErrorCode::InvalidAdmin);
+
+        config.pending_admin = new_admin;
+
+        emit!(AdminProposedEvent {
+            admin: config.admin,
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin transfer proposed to: {}", new_admin);
+        Ok(())
+    }
+
+    /// Cancels a pending admin handoff
+    pub fn cancel_admin_transfer(ctx: Context<AdminConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.pending_admin != Pubkey::default(), ErrorCode::NoPendingAdmin);
+
+        let cancelled_admin = config.pending_admin;
+        config.pending_admin = Pubkey::default();
+
+        emit!(AdminTransferCancelledEvent {
+            admin: config.admin,
+            cancelled_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin transfer to {} cancelled", cancelled_admin);
+        Ok(())
+    }
+
+    /// Step two of an admin handoff: the nominated key claims the role
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_admin = config.admin;
+
+        config.admin = ctx.accounts.pending_admin.key();
+        config.pending_admin = Pubkey::default();
+
+        emit!(AdminChangedEvent {
+            previous_admin,
+            new_admin: config.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin changed from {} to {}", previous_admin, config.admin);
+        Ok(())
+    }
+
+    /// Recomputes platform stats from profile and session accounts passed via
+    /// `remaining_accounts`. Call with `reset = true` for the first batch, then
+    /// `reset = false` for each following batch until every account is covered.
+    /// An account repeated within a batch is counted once.
+    pub fn rebuild_stats(ctx: Context<RebuildStats>, reset: bool) -> Result<()> {
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        let clock = Clock::get()?;
+
+        if reset {
+            platform_stats.total_profiles = 0;
+            platform_stats.active_profiles = 0;
+            platform_stats.total_sessions = 0;
+            platform_stats.finalized_sessions = 0;
+            platform_stats.total_matches = 0;
+        }
+
+        let mut processed: u32 = 0;
+        let mut seen: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for account_info in ctx.remaining_accounts.iter() {
+            // Skip anything this program does not own, and repeats
+            if account_info.owner != &crate::ID || seen.contains(account_info.key) {
+                continue;
+            }
+            seen.push(*account_info.key);
+
+            let data = account_info.try_borrow_data()?;
+            if data.starts_with(UserProfile::DISCRIMINATOR) {
+                // Profiles not yet migrated still count
+                let (_, profile) = migration::decode_profile(&data)?;
+                platform_stats.total_profiles = platform_stats.total_profiles.saturating_add(1);
+                if profile.is_active {
+                    platform_stats.active_profiles = platform_stats.active_profiles.saturating_add(1);
+                }
+            } else if data.starts_with(MatchPairSession::DISCRIMINATOR) {
+                let session = MatchPairSession::try_deserialize(&mut &data[..])?;
+                platform_stats.total_sessions = platform_stats.total_sessions.saturating_add(1);
+                if session.is_finalized {
+                    platform_stats.finalized_sessions = platform_stats.finalized_sessions.saturating_add(1);
+                }
+                if session.match_found {
+                    platform_stats.total_matches = platform_stats.total_matches.saturating_add(1);
+                }
+            } else {
+                continue;
+            }
+            processed += 1;
+        }
+
+        platform_stats.last_rebuilt_at = clock.unix_timestamp;
+
+        emit!(StatsRebuiltEvent {
+            processed,
+            total_profiles: platform_stats.total_profiles,
+            total_sessions: platform_stats.total_sessions,
+            total_matches: platform_stats.total_matches,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Platform stats rebuilt from {} accounts", processed);
+        Ok(())
+    }
+
+    /// Deactivates every profile passed in `remaining_accounts` for a
+    /// moderation sweep. Profiles that are already inactive are skipped, so a
+    /// partially applied sweep can simply be resubmitted.
+    pub fn bulk_deactivate<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BulkDeactivate<'info>>,
+    ) -> Result<()> {
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        let clock = Clock::get()?;
+        let mut count: u32 = 0;
+
+        for account_info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*account_info.owner, crate::ID, ErrorCode::ProfileNotFound);
+            require!(account_info.is_writable, ErrorCode::ProfileNotFound);
+
+            let mut profile = {
+                let data = account_info.try_borrow_data()?;
+                require!(data.starts_with(UserProfile::DISCRIMINATOR), ErrorCode::ProfileNotFound);
+                UserProfile::try_deserialize(&mut &data[..])?
+            };
+            let expected = Pubkey::create_program_address(
+                &[b"user_profile", profile.owner.as_ref(), &[profile.bump]],
+                &crate::ID,
+            ).map_err(|_| error!(ErrorCode::ProfileNotFound))?;
+            require_keys_eq!(account_info.key(), expected, ErrorCode::ProfileNotFound);
+
+            if !profile.is_active {
+                continue;
+            }
+
+            profile.is_active = false;
+            profile.last_updated = clock.unix_timestamp;
+            profile.try_serialize(&mut &mut account_info.try_borrow_mut_data()?[..])?;
+            platform_stats.active_profiles = platform_stats.active_profiles.saturating_sub(1);
+            count += 1;
+        }
+
+        emit!(BulkDeactivatedEvent {
+            admin: ctx.accounts.admin.key(),
+            count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Deactivated {} profiles", count);
+        Ok(())
+    }
+
+    // ========================================================================
+    // NONCE MANAGEMENT FUNCTIONS
+    // ========================================================================
+
+    /// Creates the caller's nonce counter
+    pub fn init_nonce_counter(ctx: Context<InitNonceCounter>) -> Result<()> {
+        let nonce_counter = &mut ctx.accounts.nonce_counter;
+        nonce_counter.owner = ctx.accounts.user.key();
+        nonce_counter.next_nonce = 0;
+        nonce_counter.bump = ctx.bumps.nonce_counter;
+        Ok(())
+    }
+
+    /// Returns the next nonce a client may encrypt with
+    pub fn get_next_nonce(ctx: Context<GetNextNonce>) -> Result<u128> {
+        Ok(ctx.accounts.nonce_counter.next_nonce)
+    }
+
+    // ========================================================================
+    // LIKE DELEGATION FUNCTIONS
+    // ========================================================================
+
+    /// Authorizes a relayer to submit encrypted likes on the user's behalf
+    pub fn set_like_delegate(
+        ctx: Context<SetLikeDelegate>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        let user_key = ctx.accounts.user.key();
+        require!(delegate != Pubkey::default(), ErrorCode::InvalidDelegate);
+        require_keys_neq!(delegate, user_key, ErrorCode::InvalidDelegate);
+
+        let like_delegate = &mut ctx.accounts.like_delegate;
+        let clock = Clock::get()?;
+
+        like_delegate.owner = user_key;
+        like_delegate.delegate = delegate;
+        like_delegate.updated_at = clock.unix_timestamp;
+        like_delegate.bump = ctx.bumps.like_delegate;
+
+        emit!(LikeDelegateSetEvent {
+            user: user_key,
+            delegate,
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Like delegate set for user: {}", user_key);
+        Ok(())
+    }
+
+    /// Revokes the user's like relayer and reclaims the delegation rent
+    pub fn revoke_like_delegate(ctx: Context<RevokeLikeDelegate>) -> Result<()> {
+        emit!(LikeDelegateRevokedEvent {
+            user: ctx.accounts.user.key(),
+            delegate: ctx.accounts.like_delegate.delegate,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Like delegate revoked for user: {}", ctx.accounts.user.key());
+        Ok(())
+    }
+
+    // ========================================================================
+    // BLOCKING FUNCTIONS
+    // ========================================================================
+
+    /// Adds `target` to the caller's blocklist, creating it on first use.
+    /// Blocked pairs can no longer open sessions or like each other.
+    pub fn add_block(ctx: Context<AddBlock>, target: Pubkey) -> Result<()> {
+        let user_key = ctx.accounts.user.key();
+        require_keys_neq!(target, user_key, ErrorCode::CannotBlockSelf);
+
+        let blocklist = &mut ctx.accounts.blocklist;
+        blocklist.owner = user_key;
+        blocklist.bump = ctx.bumps.blocklist;
+        require!(!blocklist.contains(&target), ErrorCode::AlreadyBlocked);
+        require!(blocklist.blocked.len() < MAX_BLOCKED_USERS, ErrorCode::BlocklistFull);
+        blocklist.blocked.push(target);
+
+        emit!(UserBlockedEvent {
+            user: user_key,
+            target,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Removes `target` from the caller's blocklist
+    pub fn remove_block(ctx: Context<RemoveBlock>, target: Pubkey) -> Result<()> {
+        let blocklist = &mut ctx.accounts.blocklist;
+        let index = blocklist.blocked
+            .iter()
+            .position(|blocked| *blocked == target)
+            .ok_or(ErrorCode::NotBlocked)?;
+        blocklist.blocked.remove(index);
+
+        emit!(UserUnblockedEvent {
+            user: ctx.accounts.user.key(),
+            target,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // REPORTING FUNCTIONS
+    // ========================================================================
+
+    /// Reports `reported` to moderators with an opaque `reason_code`, which
+    /// clients may encrypt. Each reporter keeps one report per user; a report
+    /// from an inactive or unverified profile, or one renewed within
+    /// `REPORT_COOLDOWN`, is dropped with a `ReportThrottledEvent` instead of
+    /// failing. A profile is deactivated once `AUTO_DEACTIVATION_REPORT_THRESHOLD`
+    /// distinct verified reporters have reported it.
+    pub fn report_profile(ctx: Context<ReportProfile>, reported: Pubkey, reason_code: u8) -> Result<()> {
+        let reporter = ctx.accounts.reporter.key();
+        require_keys_neq!(reported, reporter, ErrorCode::CannotReportSelf);
+
+        let now = Clock::get()?.unix_timestamp;
+        let profile_report = &mut ctx.accounts.profile_report;
+        profile_report.reporter = reporter;
+        profile_report.reported = reported;
+        profile_report.bump = ctx.bumps.profile_report;
+
+        let throttle = if !ctx.accounts.reporter_profile.is_active {
+            Some(REPORT_THROTTLE_INACTIVE_REPORTER)
+        } else if !ctx.accounts.reporter_profile.is_verified {
+            Some(REPORT_THROTTLE_UNVERIFIED_REPORTER)
+        } else if profile_report.reported_at != 0 &&
+            now.saturating_sub(profile_report.reported_at) < REPORT_COOLDOWN
+        {
+            Some(REPORT_THROTTLE_COOLDOWN)
+        } else {
+            None
+        };
+        if let Some(reason) = throttle {
+            emit!(ReportThrottledEvent {
+                reporter,
+                reported,
+                reason,
+                timestamp: now,
+            });
+            return Ok(());
+        }
+
+        // A renewed report is still the same reporter, so only a first report
+        // counts toward auto-deactivation
+        let first_report = profile_report.reported_at == 0;
+        profile_report.reason_code = reason_code;
+        profile_report.reported_at = now;
+
+        emit!(ProfileReportedEvent {
+            reporter,
+            reported,
+            reason_code,
+            timestamp: now,
+        });
+
+        let report_tally = &mut ctx.accounts.report_tally;
+        report_tally.reported = reported;
+        report_tally.bump = ctx.bumps.report_tally;
+        let threshold_reached = first_report && report_tally.record();
+
+        let reported_profile = &mut ctx.accounts.reported_profile;
+        if threshold_reached && reported_profile.is_active {
+            reported_profile.is_active = false;
+            reported_profile.last_updated = now;
+            let platform_stats = &mut ctx.accounts.platform_stats;
+            platform_stats.active_profiles = platform_stats.active_profiles.saturating_sub(1);
+
+            emit!(ProfileAutoDeactivatedEvent {
+                user: reported,
+                verified_reports: report_tally.verified_reports,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // ENCRYPTED MATCHING FUNCTIONS
+    // ========================================================================
+
+
+    /// Initializes an encrypted matching session between two users under a
+    /// client-chosen `session_id`
+    pub fn init_match_session(
+        ctx: Context<InitMatchSession>,
+        computation_offset: u64,
+        session_id: u64,
+        user_a: Pubkey,
+        user_b: Pubkey,
+        nonce: u128,
+    ) -> Result<()> {
+        let bump = ctx.bumps.match_pair_session;
+        open_match_session(ctx.accounts, bump, computation_offset, session_id, user_a, user_b, nonce)
+    }
+
+    /// Same as `init_match_session`, but takes the session id from the global
+    /// `SessionCounter` so clients need not coordinate ids.
+    ///
+    /// Every call write-locks the counter, so auto-numbered sessions open one
+    /// at a time across the program; `init_match_session` leaves the counter
+    /// alone. Client-chosen ids share the same range and are not checked
+    /// against the counter, so ids are only unique among auto-numbered
+    /// sessions. Sessions are addressed by their pair, never by id, so a
+    /// repeated id only matters to off-chain indexing.
+    pub fn init_match_session_auto(
+        ctx: Context<InitMatchSessionAuto>,
+        computation_offset: u64,
+        user_a: Pubkey,
+        user_b: Pubkey,
+        nonce: u128,
+    ) -> Result<()> {
+        let session_counter = &mut ctx.accounts.session_counter;
+        session_counter.bump = ctx.bumps.session_counter;
+        let session_id = session_counter.next()?;
+
+        let bump = ctx.bumps.match_pair_session;
+        open_match_session(ctx.accounts, bump, computation_offset, session_id, user_a, user_b, nonce)
+    }
+
+    /// Callback for match session initialization MPC computation
+    #[arcium_callback(encrypted_ix = "init_match_session")]
+    pub fn init_match_session_callback(
+        ctx: Context<InitMatchSessionCallback>,
+        output: ComputationOutputs<InitMatchSessionOutput>,
+    ) -> Result<()> {
+        let (encrypted_session, encrypted_intents) = match output {
+            ComputationOutputs::Success(InitMatchSessionOutput { field_0 }) => (field_0.field_0, field_0.field_1),
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_INIT_MATCH_SESSION, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_init_match_session(
+            match_session,
+            SessionCiphertexts {
+                match_data: encrypted_session.ciphertexts,
+                nonce: encrypted_session.nonce,
+                intents: encrypted_intents.ciphertexts,
+                intents_nonce: encrypted_intents.nonce,
+            },
+        )
+    }
+
+
+    /// Submits an encrypted like action for a user
+    pub fn submit_like(
+        ctx: Context<SubmitLike>,
+        computation_offset: u64,
+        encrypted_user_id: [u8; 32],
+        encrypted_target_id: [u8; 32],
+        encrypted_like_action: [u8; 32],
+        encrypted_timestamp: [u8; 32],
+        encrypted_intent_strength: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        queue_submit_like(
+            ctx,
+            computation_offset,
+            EncryptedLikeAction {
+                user_id: encrypted_user_id,
+                target_id: encrypted_target_id,
+                like_action: encrypted_like_action,
+                timestamp: encrypted_timestamp,
+                intent_strength: encrypted_intent_strength,
+                pub_key,
+                nonce,
+            },
+            LIKE_WEIGHT_NORMAL,
+        )
+    }
+
+    /// Submits a super like: an encrypted like that counts `LIKE_WEIGHT_SUPER`
+    /// times toward the liker's intent and tells the target they were super
+    /// liked. Limited to `MAX_DAILY_SUPER_LIKES` per UTC day, apart from the
+    /// daily like limit.
+    pub fn submit_super_like(
+        ctx: Context<SubmitLike>,
+        computation_offset: u64,
+        encrypted_user_id: [u8; 32],
+        encrypted_target_id: [u8; 32],
+        encrypted_like_action: [u8; 32],
+        encrypted_timestamp: [u8; 32],
+        encrypted_intent_strength: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        queue_submit_like(
+            ctx,
+            computation_offset,
+            EncryptedLikeAction {
+                user_id: encrypted_user_id,
+                target_id: encrypted_target_id,
+                like_action: encrypted_like_action,
+                timestamp: encrypted_timestamp,
+                intent_strength: encrypted_intent_strength,
+                pub_key,
+                nonce,
+            },
+            LIKE_WEIGHT_SUPER,
+        )
+    }
+
+    #[arcium_callback(encrypted_ix = "submit_like")]
+    pub fn submit_like_callback(
+        ctx: Context<SubmitLikeCallback>,
+        output: ComputationOutputs<SubmitLikeOutput>,
+    ) -> Result<()> {
+        let (updated_session, updated_intents, status_flag) = match output {
+            ComputationOutputs::Success(SubmitLikeOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1, field_0.field_2)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_SUBMIT_LIKE, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_submit_like(
+            match_session,
+            &mut ctx.accounts.liker_profile,
+            &mut ctx.accounts.target_profile,
+            SessionCiphertexts {
+                match_data: updated_session.ciphertexts,
+                nonce: updated_session.nonce,
+                intents: updated_intents.ciphertexts,
+                intents_nonce: updated_intents.nonce,
+            },
+            status_flag,
+        )
+    }
+
+
+    /// Takes back the caller's like while the other side has not liked back.
+    /// The revealed flag only says whether a like was withdrawn.
+    pub fn withdraw_like(
+        ctx: Context<WithdrawLike>,
+        computation_offset: u64,
+        encrypted_user_id: [u8; 32],
+        encrypted_timestamp: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let match_session = &ctx.accounts.match_pair_session;
+
+        // Validate user authorization: only a participant may withdraw
+        require!(
+            user == match_session.user_a || user == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require_session_state(match_session, SessionOperation::WithdrawLike)?;
+
+        // Consume the caller's nonce so ciphertexts never reuse one
+        let nonce_counter = &mut ctx.accounts.nonce_counter;
+        require_keys_eq!(nonce_counter.owner, user, ErrorCode::UnauthorizedUser);
+        nonce_counter.consume(nonce)?;
+
+        // Same idle window as submit_like, so a session that takes no more
+        // likes takes no more withdrawals either
+        let now = Clock::get()?.unix_timestamp;
+        let idle_ttl = session_idle_ttl(
+            &ctx.accounts.user_a_profile,
+            &ctx.accounts.user_b_profile,
+            &ctx.accounts.config,
+            now,
+        );
+        require!(
+            !ctx.accounts.match_pair_session.is_expired(now, idle_ttl),
+            ErrorCode::SessionExpired
+        );
+
+        let match_session = &mut ctx.accounts.match_pair_session;
+        match_session.begin_computation(COMP_DEF_OFFSET_WITHDRAW_LIKE, computation_offset, now)?;
+        match_session.last_updated = now;
+        let match_session = &ctx.accounts.match_pair_session;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(encrypted_user_id),
+            Argument::EncryptedU64(encrypted_timestamp),
+            Argument::PlaintextU128(match_session.nonce),
+            Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
+            Argument::PlaintextU128(match_session.intents_nonce),
+            Argument::Account(match_session.key(), MatchPairSession::ENCRYPTED_INTENTS_OFFSET, 32 * 2),
+        ];
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: match_session.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let session_key = ctx.accounts.match_pair_session.key();
+            let match_session = &mut ctx.accounts.match_pair_session;
+            let current = mock_mpc::MatchSessionState::decode(&match_session.encrypted_match_data);
+            let intents = mock_mpc::LikeIntentsState::decode(&match_session.encrypted_intents);
+            let (updated, updated_intents, status_flag) = mock_mpc::withdraw_like(
+                mock_mpc::decode_value(&encrypted_user_id) as u64,
+                mock_mpc::decode_value(&encrypted_timestamp) as u64,
+                current,
+                intents,
+            );
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_WITHDRAW_LIKE, ctx.accounts.computation_account.key())?;
+            let next_nonce = match_session.nonce.wrapping_add(1);
+            let next_intents_nonce = match_session.intents_nonce.wrapping_add(1);
+            apply_withdraw_like(
+                match_session,
+                SessionCiphertexts {
+                    match_data: updated.encode(),
+                    nonce: next_nonce,
+                    intents: updated_intents.encode(),
+                    intents_nonce: next_intents_nonce,
+                },
+                status_flag,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "withdraw_like")]
+    pub fn withdraw_like_callback(
+        ctx: Context<WithdrawLikeCallback>,
+        output: ComputationOutputs<WithdrawLikeOutput>,
+    ) -> Result<()> {
+        let (updated_session, updated_intents, status_flag) = match output {
+            ComputationOutputs::Success(WithdrawLikeOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1, field_0.field_2)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_WITHDRAW_LIKE, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_withdraw_like(
+            match_session,
+            SessionCiphertexts {
+                match_data: updated_session.ciphertexts,
+                nonce: updated_session.nonce,
+                intents: updated_intents.ciphertexts,
+                intents_nonce: updated_intents.nonce,
+            },
+            status_flag,
+        )
+    }
+
+
+    /// Records a definitive pass, finalizing the session as a no-match
+    /// without waiting for the other participant
+    pub fn submit_pass(
+        ctx: Context<SubmitPass>,
+        computation_offset: u64,
+        encrypted_user_id: [u8; 32],
+        encrypted_target_id: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let match_session = &ctx.accounts.match_pair_session;
+
+        // Validate user authorization: only a participant may pass
+        require!(
+            user == match_session.user_a || user == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require_session_state(match_session, SessionOperation::SubmitPass)?;
+
+        // Consume the caller's nonce so ciphertexts never reuse one
+        let nonce_counter = &mut ctx.accounts.nonce_counter;
+        require_keys_eq!(nonce_counter.owner, user, ErrorCode::UnauthorizedUser);
+        nonce_counter.consume(nonce)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let match_session = &mut ctx.accounts.match_pair_session;
+        match_session.begin_computation(COMP_DEF_OFFSET_SUBMIT_PASS, computation_offset, now)?;
+        match_session.last_updated = now;
+        let match_session = &ctx.accounts.match_pair_session;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU64(encrypted_user_id),
+            Argument::EncryptedU64(encrypted_target_id),
+            Argument::PlaintextU128(match_session.nonce),
+            Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
+        ];
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: match_session.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.platform_stats.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_a_profile.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_b_profile.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let session_key = ctx.accounts.match_pair_session.key();
+            let match_session = &mut ctx.accounts.match_pair_session;
+            let current = mock_mpc::MatchSessionState::decode(&match_session.encrypted_match_data);
+            let status_flag = mock_mpc::submit_pass(
+                mock_mpc::decode_value(&encrypted_user_id) as u64,
+                mock_mpc::decode_value(&encrypted_target_id) as u64,
+                &current,
+            );
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_SUBMIT_PASS, ctx.accounts.computation_account.key())?;
+            apply_submit_pass(
+                match_session,
+                &mut ctx.accounts.platform_stats,
+                &mut ctx.accounts.user_a_profile,
+                &mut ctx.accounts.user_b_profile,
+                status_flag,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "submit_pass")]
+    pub fn submit_pass_callback(
+        ctx: Context<SubmitPassCallback>,
+        output: ComputationOutputs<SubmitPassOutput>,
+    ) -> Result<()> {
+        let status_flag = match output {
+            ComputationOutputs::Success(SubmitPassOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_SUBMIT_PASS, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_submit_pass(
+            match_session,
+            &mut ctx.accounts.platform_stats,
+            &mut ctx.accounts.user_a_profile,
+            &mut ctx.accounts.user_b_profile,
+            status_flag,
+        )
+    }
+
+
+    pub fn check_mutual_match(
+        ctx: Context<CheckMutualMatch>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        // Only a participant may finalize their own session
+        let payer = ctx.accounts.payer.key();
+        let session = &ctx.accounts.match_pair_session;
+        require!(payer == session.user_a || payer == session.user_b, ErrorCode::UnauthorizedUser);
+        require_session_state(&ctx.accounts.match_pair_session, SessionOperation::CheckMutualMatch)?;
+        // Nothing to verify until both participants have liked
+        require!(session.is_ready_for_verification, ErrorCode::SessionNotReady);
+
+        // Record the in-flight computation so its callback can be verified
+        ctx.accounts.match_pair_session.begin_computation(
+            COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH,
+            computation_offset,
+            clock.unix_timestamp,
+        )?;
+        let match_session = &ctx.accounts.match_pair_session;
+
+        let user_a_data = &ctx.accounts.user_a_matching_ciphertexts;
+        let user_b_data = &ctx.accounts.user_b_matching_ciphertexts;
+
+        let mut args = vec![
+            Argument::PlaintextU128(match_session.nonce),
+            Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
+            Argument::PlaintextU128(match_session.intents_nonce),
+            Argument::Account(match_session.key(), MatchPairSession::ENCRYPTED_INTENTS_OFFSET, 32 * 2),
+        ];
+        // Each user's dealbreakers and attributes, so the circuit can veto the match
+        for data in [user_a_data, user_b_data] {
+            args.push(Argument::ArcisPubkey(data.encryption_pubkey));
+            args.push(Argument::PlaintextU128(data.nonce));
+            args.push(Argument::Account(
+                data.key(),
+                MatchingCiphertexts::ENCRYPTED_DATA_OFFSET,
+                32 * MATCHING_DATA_CIPHERTEXTS as u32,
+            ));
+        }
+        args.push(Argument::PlaintextU64(clock.unix_timestamp as u64));
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: match_session.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.platform_stats.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_a_profile.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_b_profile.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let session_key = ctx.accounts.match_pair_session.key();
+            let match_session = &mut ctx.accounts.match_pair_session;
+            let current = mock_mpc::MatchSessionState::decode(&match_session.encrypted_match_data);
+            let intents = mock_mpc::LikeIntentsState::decode(&match_session.encrypted_intents);
+            let user_a_data = mock_mpc::MatchingData::decode(&ctx.accounts.user_a_matching_ciphertexts.encrypted_data);
+            let user_b_data = mock_mpc::MatchingData::decode(&ctx.accounts.user_b_matching_ciphertexts.encrypted_data);
+            let (is_mutual_match, session_status, _match_timestamp, confidence, compatibility_score) =
+                mock_mpc::check_mutual_match(&current, &intents, &user_a_data, &user_b_data, clock.unix_timestamp as u64);
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH, ctx.accounts.computation_account.key())?;
+            apply_check_mutual_match(
+                match_session,
+                &mut ctx.accounts.platform_stats,
+                &mut ctx.accounts.user_a_profile,
+                &mut ctx.accounts.user_b_profile,
+                MatchVerdict { is_mutual_match, session_status, confidence, compatibility_score },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "check_mutual_match")]
+    pub fn check_mutual_match_callback(
+        ctx: Context<CheckMutualMatchCallback>,
+        output: ComputationOutputs<CheckMutualMatchOutput>,
+    ) -> Result<()> {
+        let match_result = match output {
+            ComputationOutputs::Success(CheckMutualMatchOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        let _match_timestamp = match_result.field_2;
+        let verdict = MatchVerdict {
+            is_mutual_match: match_result.field_0,
+            session_status: match_result.field_1,
+            confidence: match_result.field_3,
+            compatibility_score: match_result.field_4,
+        };
+
+        apply_check_mutual_match(
+            match_session,
+            &mut ctx.accounts.platform_stats,
+            &mut ctx.accounts.user_a_profile,
+            &mut ctx.accounts.user_b_profile,
+            verdict,
+        )
+    }
+
+    /// Re-encrypts the session state under a fresh nonce without changing it;
+    /// participants call this between likes on long-lived sessions
+    pub fn rotate_session_nonce(
+        ctx: Context<RotateSessionNonce>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let payer = ctx.accounts.payer.key();
+        let now = Clock::get()?.unix_timestamp;
+        let match_session = &mut ctx.accounts.match_pair_session;
+
+        require!(
+            payer == match_session.user_a || payer == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require_session_state(match_session, SessionOperation::RotateNonce)?;
+
+        match_session.begin_computation(COMP_DEF_OFFSET_ROTATE_SESSION_NONCE, computation_offset, now)?;
+        let session_key = match_session.key();
+
+        let args = vec![
+            Argument::PlaintextU128(match_session.nonce),
+            Argument::Account(session_key, 8 + 8 + 32 + 32, 32 * 6),
+        ];
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: session_key,
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: ciphertexts are plaintext, so only the nonce changes
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let match_session = &mut ctx.accounts.match_pair_session;
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_ROTATE_SESSION_NONCE, ctx.accounts.computation_account.key())?;
+            let ciphertexts = match_session.encrypted_match_data;
+            let next_nonce = match_session.nonce.wrapping_add(1);
+            apply_rotate_session_nonce(match_session, ciphertexts, next_nonce)?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "rotate_session_nonce")]
+    pub fn rotate_session_nonce_callback(
+        ctx: Context<RotateSessionNonceCallback>,
+        output: ComputationOutputs<RotateSessionNonceOutput>,
+    ) -> Result<()> {
+        let rotated_session = match output {
+            ComputationOutputs::Success(RotateSessionNonceOutput { field_0 }) => field_0,
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_ROTATE_SESSION_NONCE, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_rotate_session_nonce(match_session, rotated_session.ciphertexts, rotated_session.nonce)
+    }
+
+    /// Records that the caller's client has shown this session's likes. Only
+    /// the fact is recorded, never which way the likes went; the first call
+    /// per participant sets the receipt and later calls are no-ops.
+    pub fn mark_like_seen(ctx: Context<MarkLikeSeen>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let now = Clock::get()?.unix_timestamp;
+        let match_session = &mut ctx.accounts.match_pair_session;
+
+        let seen_at = if user == match_session.user_a {
+            &mut match_session.user_a_seen_at
+        } else if user == match_session.user_b {
+            &mut match_session.user_b_seen_at
+        } else {
+            return Err(ErrorCode::UnauthorizedUser.into());
+        };
+        if *seen_at != 0 {
+            return Ok(());
+        }
+        *seen_at = now;
+
+        emit!(LikeSeenEvent {
+            session_id: match_session.session_id,
+            seen_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Records that a participant started a conversation on a confirmed match,
+    /// which keeps it out of the stale-match archive
+    pub fn mark_conversation_started(ctx: Context<SessionParticipant>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        require!(
+            user == match_session.user_a || user == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require_session_state(match_session, SessionOperation::StartConversation)?;
+
+        if match_session.conversation_started_at == 0 {
+            match_session.conversation_started_at = Clock::get()?.unix_timestamp;
+        }
+
+        Ok(())
+    }
+
+    /// Archives a match nobody started a conversation on within the configured
+    /// window. Permissionless; the session and its history are kept.
+    pub fn archive_stale_match(ctx: Context<ArchiveStaleMatch>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let archive_window = ctx.accounts.config.match_archive_window;
+        let match_session = &mut ctx.accounts.match_pair_session;
+        require_session_state(match_session, SessionOperation::Archive)?;
+        require!(
+            match_session.is_stale_match(now, archive_window),
+            ErrorCode::MatchNotArchivable
+        );
+
+        match_session.finalization_reason = FINALIZATION_REASON_ARCHIVED;
+        match_session.last_updated = now;
+
+        emit!(MatchArchivedEvent {
+            session_id: match_session.session_id,
+            archived_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Finalizes a session left idle past its participants' recommended
+    /// expiry window without a match, freeing the pair to rekindle it.
+    /// Permissionless.
+    pub fn expire_match_session(ctx: Context<ExpireMatchSession>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let idle_ttl = session_idle_ttl(
+            &ctx.accounts.user_a_profile,
+            &ctx.accounts.user_b_profile,
+            &ctx.accounts.config,
+            now,
+        );
+        let match_session = &mut ctx.accounts.match_pair_session;
+        require_session_state(match_session, SessionOperation::Expire)?;
+        require!(match_session.is_expired(now, idle_ttl), ErrorCode::SessionNotExpired);
+
+        // A stuck computation is abandoned so its late callback is rejected
+        require!(
+            match_session.pending_comp_def_offset == 0 ||
+            now.saturating_sub(match_session.pending_since) > PENDING_COMPUTATION_TIMEOUT,
+            ErrorCode::ComputationPending
+        );
+        match_session.pending_comp_def_offset = 0;
+        match_session.pending_computation_offset = 0;
+        match_session.pending_since = 0;
+
+        match_session.is_finalized = true;
+        match_session.match_found = false;
+        match_session.finalization_reason = FINALIZATION_REASON_EXPIRED;
+        match_session.last_updated = now;
+
+        let platform_stats = &mut ctx.accounts.platform_stats;
+        platform_stats.finalized_sessions = platform_stats.finalized_sessions.saturating_add(1);
+        for profile in [&mut ctx.accounts.user_a_profile, &mut ctx.accounts.user_b_profile] {
+            profile.open_sessions = profile.open_sessions.saturating_sub(1);
+        }
+
+        emit!(SessionExpiredEvent {
+            session_id: match_session.session_id,
+            expired_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Revives an archived match; either participant may do so within the
+    /// grace period after archiving
+    pub fn unarchive_match(ctx: Context<SessionParticipant>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let now = Clock::get()?.unix_timestamp;
+        let match_session = &mut ctx.accounts.match_pair_session;
+        require!(
+            user == match_session.user_a || user == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require_session_state(match_session, SessionOperation::Unarchive)?;
+        require!(
+            now.saturating_sub(match_session.last_updated) <= UNARCHIVE_GRACE_PERIOD,
+            ErrorCode::UnarchiveGraceExpired
+        );
+
+        // Reviving restarts the archive window without touching matched_at
+        match_session.finalization_reason = FINALIZATION_REASON_CHECKED;
+        match_session.last_updated = now;
+
+        emit!(MatchUnarchivedEvent {
+            session_id: match_session.session_id,
+            unarchived_by: user,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the session's in-flight computation so clients can wait instead
+    /// of re-queueing; `None` when no callback is outstanding
+    pub fn get_pending_computation(ctx: Context<GetPendingComputation>) -> Result<Option<PendingComputation>> {
+        Ok(ctx.accounts.match_pair_session.pending_computation())
+    }
+
+    /// Whether `user_a` and `user_b` matched in the given session. Sessions are
+    /// keyed by id, so the client passes the pair's session account; only a
+    /// participant learns when the match happened.
+    pub fn have_matched(ctx: Context<HaveMatched>, user_a: Pubkey, user_b: Pubkey) -> Result<HaveMatchedView> {
+        let match_session = &ctx.accounts.match_pair_session;
+        let is_pair = (match_session.user_a == user_a && match_session.user_b == user_b) ||
+            (match_session.user_a == user_b && match_session.user_b == user_a);
+        require!(is_pair, ErrorCode::InvalidSession);
+
+        let matched = match_session.is_finalized && match_session.match_found;
+        let caller = ctx.accounts.caller.key();
+        let is_participant = caller == match_session.user_a || caller == match_session.user_b;
+
+        Ok(HaveMatchedView {
+            matched,
+            matched_at: (matched && is_participant).then_some(match_session.matched_at),
+        })
+    }
+
+    /// Moves a session to the current ciphertext layout: grows the account to
+    /// the current size and re-runs the init circuit to repopulate
+    /// `encrypted_match_data`. Open sessions restart with both like flags
+    /// cleared; finalized sessions only need the resize. No-op when current.
+    pub fn migrate_session(
+        ctx: Context<MigrateSession>,
+        computation_offset: u64,
+        nonce: u128,
+    ) -> Result<()> {
+        let session_info = ctx.accounts.match_pair_session.to_account_info();
+        require_keys_eq!(*session_info.owner, crate::ID, ErrorCode::InvalidSession);
+        require!(
+            session_info.try_borrow_data()?.starts_with(MatchPairSession::DISCRIMINATOR),
+            ErrorCode::InvalidSession
+        );
+
+        // Legacy accounts are too short to deserialize, so grow them first
+        grow_account(
+            &session_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + MatchPairSession::INIT_SPACE,
+        )?;
+
+        let mut match_session = MatchPairSession::try_deserialize(&mut &session_info.try_borrow_data()?[..])?;
+        require_keys_eq!(session_info.key(), match_session.derive_address()?, ErrorCode::InvalidSession);
+
+        let payer = ctx.accounts.payer.key();
+        require!(
+            payer == match_session.user_a ||
+            payer == match_session.user_b ||
+            payer == ctx.accounts.config.admin,
+            ErrorCode::UnauthorizedUser
+        );
+
+        let from_version = match_session.ciphertext_layout_version;
+        if from_version >= CIPHERTEXT_LAYOUT_VERSION {
+            return Ok(());
+        }
+
+        let clock = Clock::get()?;
+        match_session.ciphertext_layout_version = CIPHERTEXT_LAYOUT_VERSION;
+        let repopulate = !match_session.is_finalized;
+        if repopulate {
+            ctx.accounts.nonce_counter.consume(nonce)?;
+            match_session.nonce = nonce;
+            match_session.last_updated = clock.unix_timestamp;
+            match_session.begin_computation(
+                COMP_DEF_OFFSET_INIT_MATCH_SESSION,
+                computation_offset,
+                clock.unix_timestamp,
+            )?;
+        }
+        match_session.try_serialize(&mut &mut session_info.try_borrow_mut_data()?[..])?;
+
+        emit!(SessionMigratedEvent {
+            session_id: match_session.session_id,
+            from_version,
+            to_version: CIPHERTEXT_LAYOUT_VERSION,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if !repopulate {
+            return Ok(());
+        }
+
+        let user_a_id = user_mpc_id(&match_session.user_a);
+        let user_b_id = user_mpc_id(&match_session.user_b);
+        let session_key = session_info.key();
+
+        let args = vec![
+            Argument::PlaintextU128(nonce),
+            Argument::PlaintextU64(user_a_id),
+            Argument::PlaintextU64(user_b_id),
+            Argument::PlaintextU64(clock.unix_timestamp as u64),
+        ];
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        // Completes through `init_match_session_callback`
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: session_key,
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let (mock_session, mock_intents) = mock_mpc::init_match_session(user_a_id, user_b_id, clock.unix_timestamp as u64);
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_INIT_MATCH_SESSION, ctx.accounts.computation_account.key())?;
+            apply_init_match_session(
+                &mut match_session,
+                SessionCiphertexts {
+                    match_data: mock_session.encode(),
+                    nonce: nonce.wrapping_add(1),
+                    intents: mock_intents.encode(),
+                    intents_nonce: nonce.wrapping_add(1),
+                },
+            )?;
+            match_session.try_serialize(&mut &mut session_info.try_borrow_mut_data()?[..])?;
+        }
+
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        match_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+        match_session.try_serialize(&mut &mut session_info.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+
+    /// Re-opens a finalized session that ended without a match, resetting both
+    /// like flags through the init circuit so each user has to like again.
+    /// Both participants must pass the same checks as when opening a session.
+    pub fn rekindle(
+        ctx: Context<Rekindle>,
+        computation_offset: u64,
+        nonce: u128,
+    ) -> Result<()> {
+        ctx.accounts.nonce_counter.consume(nonce)?;
+
+        let payer = ctx.accounts.payer.key();
+        let clock = Clock::get()?;
+        let match_session = &ctx.accounts.match_pair_session;
+        require_session_state(match_session, SessionOperation::Rekindle)?;
+        require!(
+            clock.unix_timestamp.saturating_sub(match_session.last_updated) >= REKINDLE_COOLDOWN,
+            ErrorCode::RekindleCooldown
+        );
+
+        // A rekindled session is a new one for the participant checks: blocks,
+        // pauses and the account age all apply again
+        register_session_participants(
+            payer,
+            SessionParticipant {
+                user: match_session.user_a,
+                profile: &ctx.accounts.user_a_profile,
+                blocklist: &ctx.accounts.user_a_blocklist,
+            },
+            SessionParticipant {
+                user: match_session.user_b,
+                profile: &ctx.accounts.user_b_profile,
+                blocklist: &ctx.accounts.user_b_blocklist,
+            },
+            ctx.accounts.config.min_account_age_secs,
+            clock.unix_timestamp,
+        )?;
+
+        let match_session = &mut ctx.accounts.match_pair_session;
+        match_session.is_finalized = false;
+        match_session.match_found = false;
+        match_session.matched_at = 0;
+        match_session.user_a_seen_at = 0;
+        match_session.user_b_seen_at = 0;
+        match_session.conversation_started_at = 0;
+        match_session.user_a_rated = false;
+        match_session.user_b_rated = false;
+        match_session.match_confidence = 0;
+        match_session.is_ready_for_verification = false;
+        match_session.finalization_reason = FINALIZATION_REASON_CHECKED;
+        match_session.nonce = nonce;
+        match_session.last_updated = clock.unix_timestamp;
+        match_session.begin_computation(
+            COMP_DEF_OFFSET_INIT_MATCH_SESSION,
+            computation_offset,
+            clock.unix_timestamp,
+        )?;
+
+        let user_a_id = user_mpc_id(&match_session.user_a);
+        let user_b_id = user_mpc_id(&match_session.user_b);
+        let session_id = match_session.session_id;
+        let session_key = match_session.key();
+
+        let args = vec![
+            Argument::PlaintextU128(nonce),
+            Argument::PlaintextU64(user_a_id),
+            Argument::PlaintextU64(user_b_id),
+            Argument::PlaintextU64(clock.unix_timestamp as u64),
+        ];
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        // Completes through `init_match_session_callback`
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: session_key,
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let (mock_session, mock_intents) = mock_mpc::init_match_session(user_a_id, user_b_id, clock.unix_timestamp as u64);
+            let match_session = &mut ctx.accounts.match_pair_session;
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_INIT_MATCH_SESSION, ctx.accounts.computation_account.key())?;
+            apply_init_match_session(
+                match_session,
+                SessionCiphertexts {
+                    match_data: mock_session.encode(),
+                    nonce: nonce.wrapping_add(1),
+                    intents: mock_intents.encode(),
+                    intents_nonce: nonce.wrapping_add(1),
+                },
+            )?;
+        }
+
+        emit!(RekindleEvent {
+            session_id,
+            initiated_by: payer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Closes every finalized session passed in `remaining_accounts` that the
+    /// caller participates in, refunding rent to the caller. Sessions that are
+    /// not finalized or still awaiting a callback are skipped.
+    pub fn close_all_finalized_sessions_for_user<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseFinalizedSessions<'info>>,
+    ) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let mut count: u32 = 0;
+
+        for account_info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*account_info.owner, crate::ID, ErrorCode::InvalidSession);
+            require!(account_info.is_writable, ErrorCode::InvalidSession);
+
+            let session = {
+                let data = account_info.try_borrow_data()?;
+                require!(data.starts_with(MatchPairSession::DISCRIMINATOR), ErrorCode::InvalidSession);
+                MatchPairSession::try_deserialize(&mut &data[..])?
+            };
+            require_keys_eq!(account_info.key(), session.derive_address()?, ErrorCode::InvalidSession);
+            require!(
+                user == session.user_a || user == session.user_b,
+                ErrorCode::UnauthorizedUser
+            );
+
+            if !session.is_finalized || session.pending_comp_def_offset != 0 {
+                continue;
+            }
+
+            anchor_lang::common::close(account_info.clone(), ctx.accounts.user.to_account_info())?;
+            count += 1;
+        }
+
+        emit!(SessionsClosedEvent {
+            user,
+            count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Closed {} finalized sessions", count);
+        Ok(())
+    }
+
+    /// Closes one finalized session, refunding its rent to the caller. Only a
+    /// participant may close it, so the reclaimed lamports always go to user_a
+    /// or user_b and never to a third party.
+    pub fn close_match_session(ctx: Context<CloseMatchSession>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let match_session = &ctx.accounts.match_pair_session;
+        require!(
+            user == match_session.user_a || user == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require!(match_session.is_finalized, ErrorCode::SessionNotFinalized);
+        // A callback still in flight must find its session
+        require!(match_session.pending_comp_def_offset == 0, ErrorCode::ComputationPending);
+
+        emit!(MatchSessionClosedEvent {
+            session_id: match_session.session_id,
+            closed_at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // COMPATIBILITY FUNCTIONS
+    // ========================================================================
+
+    /// Writes a chunk of the caller's encrypted preferences and profile (the
+    /// circuit's `MatchingData`), starting at `start_index`. The whole struct
+    /// is encrypted under one key and nonce, so every chunk must carry the
+    /// same `pub_key` and `nonce`.
+    pub fn stage_matching_ciphertexts(
+        ctx: Context<StageMatchingCiphertexts>,
+        pub_key: [u8; 32],
+        nonce: u128,
+        start_index: u8,
+        ciphertexts: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let start = start_index as usize;
+        require!(
+            start + ciphertexts.len() <= MATCHING_DATA_CIPHERTEXTS,
+            ErrorCode::MatchingDataOutOfRange
+        );
+
+        let matching_ciphertexts = &mut ctx.accounts.matching_ciphertexts;
+        matching_ciphertexts.owner = ctx.accounts.user.key();
+        matching_ciphertexts.encryption_pubkey = pub_key;
+        matching_ciphertexts.nonce = nonce;
+        matching_ciphertexts.encrypted_data[start..start + ciphertexts.len()].copy_from_slice(&ciphertexts);
+        matching_ciphertexts.updated_at = Clock::get()?.unix_timestamp;
+        matching_ciphertexts.bump = ctx.bumps.matching_ciphertexts;
+
+        Ok(())
+    }
+
+    /// Scores one pair from both users' staged matching data. Either
+    /// participant may ask; the score is revealed in the callback event
+    pub fn calculate_compatibility(
+        ctx: Context<CalculateCompatibility>,
+        computation_offset: u64,
+        user_a: Pubkey,
+        user_b: Pubkey,
+        scoring_flags: u8,
+    ) -> Result<()> {
+        let payer = ctx.accounts.payer.key();
+        require!(payer == user_a || payer == user_b, ErrorCode::UnauthorizedUser);
+        require_keys_neq!(user_a, user_b, ErrorCode::CannotMatchSelf);
+        require!(
+            ctx.accounts.user_a_profile.is_active && ctx.accounts.user_b_profile.is_active,
+            ErrorCode::ProfileInactive
+        );
+
+        // A user who was never rated still needs an account to hand the circuit
+        for (reputation, user, bump) in [
+            (&mut ctx.accounts.user_a_reputation, user_a, ctx.bumps.user_a_reputation),
+            (&mut ctx.accounts.user_b_reputation, user_b, ctx.bumps.user_b_reputation),
+        ] {
+            if reputation.owner == Pubkey::default() {
+                reputation.owner = user;
+                reputation.bump = bump;
+            }
+        }
+
+        let mut args = Vec::new();
+        for data in [&ctx.accounts.user_a_matching_ciphertexts, &ctx.accounts.user_b_matching_ciphertexts] {
+            args.push(Argument::ArcisPubkey(data.encryption_pubkey));
+            args.push(Argument::PlaintextU128(data.nonce));
+            args.push(Argument::Account(
+                data.key(),
+                MatchingCiphertexts::ENCRYPTED_DATA_OFFSET,
+                32 * MATCHING_DATA_CIPHERTEXTS as u32,
+            ));
+        }
+        for reputation in [&ctx.accounts.user_a_reputation, &ctx.accounts.user_b_reputation] {
+            args.push(Argument::PlaintextU128(reputation.nonce));
+            args.push(Argument::Account(reputation.key(), Reputation::ENCRYPTED_REPUTATION_OFFSET, 32 * 2));
+        }
+        args.push(Argument::PlaintextBool(ctx.accounts.user_a_reputation.initialized));
+        args.push(Argument::PlaintextBool(ctx.accounts.user_b_reputation.initialized));
+        args.push(Argument::PlaintextU8(scoring_flags));
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_a_profile.key(),
+                    is_writable: false,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_b_profile.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = (args, computation_offset);
+            let user_a_data = &ctx.accounts.user_a_matching_ciphertexts;
+            let user_b_data = &ctx.accounts.user_b_matching_ciphertexts;
+            let (score, age_gate_passed, breakdown) = mock_mpc::calculate_compatibility(
+                &mock_mpc::MatchingData::decode(&user_a_data.encrypted_data),
+                &mock_mpc::MatchingData::decode(&user_b_data.encrypted_data),
+                mock_mpc::decode_reputation(&ctx.accounts.user_a_reputation).as_ref(),
+                mock_mpc::decode_reputation(&ctx.accounts.user_b_reputation).as_ref(),
+                scoring_flags,
+            );
+            let breakdown = mock_mpc::encode_breakdown(&breakdown);
+            apply_calculate_compatibility(
+                user_a,
+                user_b,
+                score,
+                age_gate_passed,
+                (user_a_data.nonce.wrapping_add(1), breakdown),
+                (user_b_data.nonce.wrapping_add(1), breakdown),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_compatibility")]
+    pub fn calculate_compatibility_callback(
+        ctx: Context<CalculateCompatibilityCallback>,
+        output: ComputationOutputs<CalculateCompatibilityOutput>,
+    ) -> Result<()> {
+        let (score, age_gate_passed, user_a_breakdown, user_b_breakdown) = match output {
+            ComputationOutputs::Success(CalculateCompatibilityOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1, field_0.field_2, field_0.field_3)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        apply_calculate_compatibility(
+            ctx.accounts.user_a_profile.owner,
+            ctx.accounts.user_b_profile.owner,
+            score,
+            age_gate_passed,
+            (user_a_breakdown.nonce, user_a_breakdown.ciphertexts),
+            (user_b_breakdown.nonce, user_b_breakdown.ciphertexts),
+        )
+    }
+
+    /// Reads up to LIST_PAGE_SIZE cached compatibility batches, passed as
+    /// remaining accounts, so a feed can be hydrated in one call. Each entry
+    /// is flagged stale when it was never scored or has outlived the TTL.
+    pub fn get_compatibility_scores_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetCompatibilityScoresBatch<'info>>,
+    ) -> Result<Vec<CompatibilityScoresEntry>> {
+        require!(
+            ctx.remaining_accounts.len() <= LIST_PAGE_SIZE,
+            ErrorCode::InvalidBatchSize
+        );
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut entries = Vec::with_capacity(ctx.remaining_accounts.len());
+        for account_info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*account_info.owner, crate::ID, ErrorCode::InvalidCompatibilityBatch);
+            let batch = {
+                let data = account_info.try_borrow_data()?;
+                require!(
+                    data.starts_with(CompatibilityBatch::DISCRIMINATOR),
+                    ErrorCode::InvalidCompatibilityBatch
+                );
+                CompatibilityBatch::try_deserialize(&mut &data[..])?
+            };
+            let expected = Pubkey::create_program_address(
+                &[b"compat_batch", batch.owner.as_ref(), &[batch.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| ErrorCode::InvalidCompatibilityBatch)?;
+            require_keys_eq!(account_info.key(), expected, ErrorCode::InvalidCompatibilityBatch);
+
+            entries.push(CompatibilityScoresEntry {
+                owner: batch.owner,
+                scores: batch.scores,
+                candidate_count: batch.candidate_count,
+                scored_at: batch.scored_at,
+                stale: batch.scored_at == 0 ||
+                    now.saturating_sub(batch.scored_at) > COMPATIBILITY_CACHE_TTL,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Scores up to 8 candidates against the caller in one computation. Each
+    /// candidate's own `MatchingCiphertexts` is passed as a remaining account,
+    /// and its score lands in the same slot of the caller's batch.
+    pub fn calculate_compatibility_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CalculateCompatibilityBatch<'info>>,
+        computation_offset: u64,
+        scoring_flags: u8,
+    ) -> Result<()> {
+        let candidate_count = ctx.remaining_accounts.len();
+        require!(
+            candidate_count >= 1 && candidate_count <= COMPATIBILITY_BATCH_SIZE,
+            ErrorCode::InvalidBatchSize
+        );
+        let payer = ctx.accounts.payer.key();
+        let mut candidates = Vec::with_capacity(candidate_count);
+        for account_info in ctx.remaining_accounts.iter() {
+            let candidate = load_matching_ciphertexts(account_info)?;
+            require_keys_neq!(candidate.owner, payer, ErrorCode::CannotMatchSelf);
+            candidates.push((account_info.key(), candidate));
+        }
+
+        let batch = &mut ctx.accounts.compatibility_batch;
+        batch.owner = payer;
+        batch.candidate_count = candidate_count as u8;
+        batch.bump = ctx.bumps.compatibility_batch;
+
+        // The requester first, then a fixed number of candidate slots; slots
+        // past candidate_count repeat the requester's data and are never revealed
+        let requester = &ctx.accounts.requester_matching_ciphertexts;
+        let requester_input = (requester.key(), requester.encryption_pubkey, requester.nonce);
+        let mut inputs = vec![requester_input];
+        for slot in 0..COMPATIBILITY_BATCH_SIZE {
+            inputs.push(
+                candidates
+                    .get(slot)
+                    .map_or(requester_input, |(key, data)| (*key, data.encryption_pubkey, data.nonce)),
+            );
+        }
+        let mut args = Vec::new();
+        for (key, encryption_pubkey, nonce) in inputs {
+            args.push(Argument::ArcisPubkey(encryption_pubkey));
+            args.push(Argument::PlaintextU128(nonce));
+            args.push(Argument::Account(
+                key,
+                MatchingCiphertexts::ENCRYPTED_DATA_OFFSET,
+                32 * MATCHING_DATA_CIPHERTEXTS as u32,
+            ));
+        }
+        args.push(Argument::PlaintextU8(candidate_count as u8));
+        args.push(Argument::PlaintextU8(scoring_flags));
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.compatibility_batch.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = (args, computation_offset);
+            let requester = mock_mpc::MatchingData::decode(&ctx.accounts.requester_matching_ciphertexts.encrypted_data);
+            let candidates: Vec<_> = candidates
+                .iter()
+                .map(|(_, data)| mock_mpc::MatchingData::decode(&data.encrypted_data))
+                .collect();
+            let (scores, age_gate_passed) =
+                mock_mpc::calculate_compatibility_batch(&requester, &candidates, scoring_flags);
+            let computation_key = ctx.accounts.computation_account.key();
+            let batch = &mut ctx.accounts.compatibility_batch;
+            batch.last_processed_computation = computation_key;
+            apply_calculate_compatibility_batch(batch, scores, age_gate_passed)?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_compatibility_batch")]
+    pub fn calculate_compatibility_batch_callback(
+        ctx: Context<CalculateCompatibilityBatchCallback>,
+        output: ComputationOutputs<CalculateCompatibilityBatchOutput>,
+    ) -> Result<()> {
+        let (scores, age_gate_passed) = match output {
+            ComputationOutputs::Success(CalculateCompatibilityBatchOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let computation_key = ctx.accounts.computation_account.key();
+        let batch = &mut ctx.accounts.compatibility_batch;
+        if batch.last_processed_computation == computation_key {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+        batch.last_processed_computation = computation_key;
+
+        apply_calculate_compatibility_batch(batch, scores, age_gate_passed)
+    }
+
+    // ========================================================================
+    // POST-MATCH DISCLOSURE FUNCTIONS
+    // ========================================================================
+
+    /// Stores a participant's encrypted consent and disclosable fields
+    pub fn submit_disclosure_consent(
+        ctx: Context<SubmitDisclosureConsent>,
+        encrypted_consent: [u8; 32],
+        encrypted_exact_age: [u8; 32],
+        encrypted_income_bracket: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        let match_session = &ctx.accounts.match_pair_session;
+        let user_key = ctx.accounts.user.key();
+
+        require!(
+            user_key == match_session.user_a || user_key == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require!(
+            match_session.is_finalized && match_session.match_found,
+            ErrorCode::SessionNotMatched
+        );
+
+        let clock = Clock::get()?;
+        let disclosure = &mut ctx.accounts.disclosure_consent;
+        disclosure.session = match_session.key();
+        disclosure.owner = user_key;
+        disclosure.encryption_pubkey = pub_key;
+        disclosure.nonce = nonce;
+        disclosure.encrypted_input = [encrypted_consent, encrypted_exact_age, encrypted_income_bracket];
+        disclosure.submitted_at = clock.unix_timestamp;
+        disclosure.bump = ctx.bumps.disclosure_consent;
+
+        emit!(DisclosureConsentSubmittedEvent {
+            session_id: match_session.session_id,
+            user: user_key,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Reveals each participant's disclosed fields to the other, only if both consented
+    pub fn reveal_matched_data(
+        ctx: Context<RevealMatchedData>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let match_session = &ctx.accounts.match_pair_session;
+        let payer = ctx.accounts.payer.key();
+
+        require!(
+            payer == match_session.user_a || payer == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require!(
+            match_session.is_finalized && match_session.match_found,
+            ErrorCode::SessionNotMatched
+        );
+
+        // Record the in-flight computation so its callback can be verified
+        ctx.accounts.match_pair_session.begin_computation(
+            COMP_DEF_OFFSET_REVEAL_MATCHED_DATA,
+            computation_offset,
+            Clock::get()?.unix_timestamp,
+        )?;
+        let match_session = &ctx.accounts.match_pair_session;
+        let user_a_consent = &ctx.accounts.user_a_consent;
+        let user_b_consent = &ctx.accounts.user_b_consent;
+
+        let args = vec![
+            Argument::ArcisPubkey(user_a_consent.encryption_pubkey),
+            Argument::PlaintextU128(user_a_consent.nonce),
+            Argument::Account(user_a_consent.key(), DisclosureConsent::ENCRYPTED_INPUT_OFFSET, 32 * 3),
+            Argument::ArcisPubkey(user_b_consent.encryption_pubkey),
+            Argument::PlaintextU128(user_b_consent.nonce),
+            Argument::Account(user_b_consent.key(), DisclosureConsent::ENCRYPTED_INPUT_OFFSET, 32 * 3),
+            Argument::PlaintextU128(match_session.nonce),
+            Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6),
+        ];
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: match_session.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let user_a_consent = &ctx.accounts.user_a_consent;
+            let user_b_consent = &ctx.accounts.user_b_consent;
+            let session_key = ctx.accounts.match_pair_session.key();
+            let match_session = &mut ctx.accounts.match_pair_session;
+            let (for_user_a, for_user_b, both_consented) = mock_mpc::reveal_matched_data(
+                &mock_mpc::DisclosureInput::decode(&user_a_consent.encrypted_input),
+                &mock_mpc::DisclosureInput::decode(&user_b_consent.encrypted_input),
+                &mock_mpc::MatchSessionState::decode(&match_session.encrypted_match_data),
+            );
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_REVEAL_MATCHED_DATA, ctx.accounts.computation_account.key())?;
+            apply_reveal_matched_data(
+                match_session,
+                (user_a_consent.nonce.wrapping_add(1), for_user_a.encode()),
+                (user_b_consent.nonce.wrapping_add(1), for_user_b.encode()),
+                both_consented,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_matched_data")]
+    pub fn reveal_matched_data_callback(
+        ctx: Context<RevealMatchedDataCallback>,
+        output: ComputationOutputs<RevealMatchedDataOutput>,
+    ) -> Result<()> {
+        let (for_user_a, for_user_b, both_consented) = match output {
+            ComputationOutputs::Success(RevealMatchedDataOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1, field_0.field_2)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_REVEAL_MATCHED_DATA, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_reveal_matched_data(
+            match_session,
+            (for_user_a.nonce, for_user_a.ciphertexts),
+            (for_user_b.nonce, for_user_b.ciphertexts),
+            both_consented,
+        )
+    }
+
+
+    /// Re-encrypts each participant's icebreaker answer to the other after a match
+    pub fn reveal_icebreakers(
+        ctx: Context<RevealIcebreakers>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let match_session = &ctx.accounts.match_pair_session;
+        let payer = ctx.accounts.payer.key();
+
+        require!(
+            payer == match_session.user_a || payer == match_session.user_b,
+            ErrorCode::UnauthorizedUser
+        );
+        require!(
+            match_session.is_finalized && match_session.match_found,
+            ErrorCode::SessionNotMatched
+        );
+
+        let user_a_profile = &ctx.accounts.user_a_profile;
+        let user_b_profile = &ctx.accounts.user_b_profile;
+        let (user_a_nonce, user_a_answer) = user_a_profile.icebreaker_ciphertexts()?;
+        let (user_b_nonce, user_b_answer) = user_b_profile.icebreaker_ciphertexts()?;
+
+        let mut args = vec![
+            Argument::ArcisPubkey(user_a_profile.encryption_pubkey),
+            Argument::PlaintextU128(user_a_nonce),
+        ];
+        args.extend(user_a_answer.iter().map(|c| Argument::EncryptedU128(*c)));
+        args.push(Argument::ArcisPubkey(user_b_profile.encryption_pubkey));
+        args.push(Argument::PlaintextU128(user_b_nonce));
+        args.extend(user_b_answer.iter().map(|c| Argument::EncryptedU128(*c)));
+        args.push(Argument::PlaintextU128(match_session.nonce));
+        args.push(Argument::Account(match_session.key(), 8 + 8 + 32 + 32, 32 * 6));
+
+        // Record the in-flight computation so its callback can be verified
+        ctx.accounts.match_pair_session.begin_computation(
+            COMP_DEF_OFFSET_REVEAL_ICEBREAKERS,
+            computation_offset,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.match_pair_session.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let session_key = ctx.accounts.match_pair_session.key();
+            let match_session = &mut ctx.accounts.match_pair_session;
+            let (for_user_a, for_user_b, is_mutual_match) = mock_mpc::reveal_icebreakers(
+                mock_mpc::IcebreakerAnswer::decode(&user_a_answer),
+                mock_mpc::IcebreakerAnswer::decode(&user_b_answer),
+                &mock_mpc::MatchSessionState::decode(&match_session.encrypted_match_data),
+            );
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_REVEAL_ICEBREAKERS, ctx.accounts.computation_account.key())?;
+            apply_reveal_icebreakers(
+                match_session,
+                (user_a_nonce.wrapping_add(1), for_user_a.encode()),
+                (user_b_nonce.wrapping_add(1), for_user_b.encode()),
+                is_mutual_match,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_icebreakers")]
+    pub fn reveal_icebreakers_callback(
+        ctx: Context<RevealIcebreakersCallback>,
+        output: ComputationOutputs<RevealIcebreakersOutput>,
+    ) -> Result<()> {
+        let (for_user_a, for_user_b, is_mutual_match) = match output {
+            ComputationOutputs::Success(RevealIcebreakersOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1, field_0.field_2)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_REVEAL_ICEBREAKERS, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_reveal_icebreakers(
+            match_session,
+            (for_user_a.nonce, for_user_a.ciphertexts),
+            (for_user_b.nonce, for_user_b.ciphertexts),
+            is_mutual_match,
+        )
+    }
+
+    // ========================================================================
+    // MATCH FEEDBACK FUNCTIONS
+    // ========================================================================
+
+    /// Submits a participant's encrypted 1-5 star rating of their match. Only
+    /// the counterpart's encrypted reputation aggregate is updated; the rating
+    /// itself is never revealed.
+    pub fn submit_feedback(
+        ctx: Context<SubmitFeedback>,
+        computation_offset: u64,
+        rated_user: Pubkey,
+        encrypted_rating: [u8; 32],
+        pub_key: [u8; 32],
+        nonce: u128,
+    ) -> Result<()> {
+        let payer = ctx.accounts.payer.key();
+        let now = Clock::get()?.unix_timestamp;
+        let match_session = &ctx.accounts.match_pair_session;
+
+        let (counterpart, already_rated) = if payer == match_session.user_a {
+            (match_session.user_b, match_session.user_a_rated)
+        } else if payer == match_session.user_b {
+            (match_session.user_a, match_session.user_b_rated)
+        } else {
+            return Err(ErrorCode::UnauthorizedUser.into());
+        };
+        require_keys_eq!(rated_user, counterpart, ErrorCode::UnauthorizedUser);
+        require_session_state(match_session, SessionOperation::SubmitFeedback)?;
+        require!(!already_rated, ErrorCode::FeedbackAlreadySubmitted);
+
+        ctx.accounts.nonce_counter.consume(nonce)?;
+
+        // One rating at a time per reputation, so concurrent callbacks cannot drop an update
+        let reputation = &mut ctx.accounts.reputation;
+        require!(
+            reputation.pending_since == 0 ||
+            now.saturating_sub(reputation.pending_since) > PENDING_COMPUTATION_TIMEOUT,
+            ErrorCode::ComputationPending
+        );
+        reputation.owner = rated_user;
+        reputation.bump = ctx.bumps.reputation;
+        reputation.pending_since = now;
+        let is_first_rating = !reputation.initialized;
+
+        let args = vec![
+            Argument::ArcisPubkey(pub_key),
+            Argument::PlaintextU128(nonce),
+            Argument::EncryptedU8(encrypted_rating),
+            Argument::PlaintextU128(reputation.nonce),
+            Argument::Account(reputation.key(), Reputation::ENCRYPTED_REPUTATION_OFFSET, 32 * 2),
+            Argument::PlaintextBool(is_first_rating),
+        ];
+
+        // Record the in-flight computation so its callback can be verified
+        ctx.accounts.match_pair_session.begin_computation(
+            COMP_DEF_OFFSET_SUBMIT_FEEDBACK,
+            computation_offset,
+            now,
+        )?;
+
+        require_fresh_computation_offset(&ctx.accounts.computation_account.to_account_info())?;
+
+        let fee_pool_before = ctx.accounts.pool_account.to_account_info().lamports();
+        #[cfg(not(feature = "mock-mpc"))]
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            vec![
+                CallbackAccount {
+                    pubkey: ctx.accounts.match_pair_session.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.reputation.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_account.key(),
+                    is_writable: false,
+                },
+            ],
+            None,
+        )?;
+        let fee_pool_after = ctx.accounts.pool_account.to_account_info().lamports();
+        ctx.accounts.match_pair_session.record_compute_fee(fee_pool_after.saturating_sub(fee_pool_before));
+
+        // Mock backend: run the plaintext circuit and apply its result inline
+        #[cfg(feature = "mock-mpc")]
+        {
+            let _ = args;
+            let (updated, accepted) = mock_mpc::submit_feedback(
+                mock_mpc::decode_value(&encrypted_rating) as u8,
+                mock_mpc::decode_reputation(&ctx.accounts.reputation),
+            );
+            let next_nonce = ctx.accounts.reputation.nonce.wrapping_add(1);
+            let session_key = ctx.accounts.match_pair_session.key();
+            let match_session = &mut ctx.accounts.match_pair_session;
+            match_session.complete_computation(session_key, COMP_DEF_OFFSET_SUBMIT_FEEDBACK, ctx.accounts.computation_account.key())?;
+            apply_submit_feedback(
+                match_session,
+                &mut ctx.accounts.reputation,
+                (next_nonce, mock_mpc::encode_reputation(&updated)),
+                accepted,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "submit_feedback")]
+    pub fn submit_feedback_callback(
+        ctx: Context<SubmitFeedbackCallback>,
+        output: ComputationOutputs<SubmitFeedbackOutput>,
+    ) -> Result<()> {
+        let (encrypted_reputation, accepted) = match output {
+            ComputationOutputs::Success(SubmitFeedbackOutput { field_0 }) => {
+                (field_0.field_0, field_0.field_1)
+            },
+            _ => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        let session_key = ctx.accounts.match_pair_session.key();
+        let computation_key = ctx.accounts.computation_account.key();
+        let match_session = &mut ctx.accounts.match_pair_session;
+        if !match_session.complete_computation(session_key, COMP_DEF_OFFSET_SUBMIT_FEEDBACK, computation_key)? {
+            msg!("Callback already applied for this computation");
+            return Ok(());
+        }
+
+        apply_submit_feedback(
+            match_session,
+            &mut ctx.accounts.reputation,
+            (encrypted_reputation.nonce, encrypted_reputation.ciphertexts),
+            accepted,
+        )
+    }
+
+
+    // ========================================================================
+    // MPC COMPUTATION DEFINITION INITIALIZATION FUNCTIONS
+    // ========================================================================
+
+    /// Dummy function for compatibility
+    pub fn init_add_together_comp_def(_ctx: Context<InitCompDef>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Initialize computation definition for match session initialization
+    pub fn init_init_match_session_comp_def(ctx: Context<InitInitMatchSessionCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_INIT_MATCH_SESSION)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_INIT_MATCH_SESSION,
+            name: "init_match_session".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for like submission
+    pub fn init_submit_like_comp_def(ctx: Context<InitSubmitLikeCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_SUBMIT_LIKE)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_SUBMIT_LIKE,
+            name: "submit_like".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for mutual match checking
+    pub fn init_check_mutual_match_comp_def(ctx: Context<InitCheckMutualMatchCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH,
+            name: "check_mutual_match".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for post-match disclosure
+    pub fn init_reveal_matched_data_comp_def(ctx: Context<InitRevealMatchedDataCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_REVEAL_MATCHED_DATA)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_REVEAL_MATCHED_DATA,
+            name: "reveal_matched_data".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for icebreaker reveal
+    pub fn init_reveal_icebreakers_comp_def(ctx: Context<InitRevealIcebreakersCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_REVEAL_ICEBREAKERS)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_REVEAL_ICEBREAKERS,
+            name: "reveal_icebreakers".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for like withdrawal
+    pub fn init_withdraw_like_comp_def(ctx: Context<InitWithdrawLikeCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_WITHDRAW_LIKE)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_WITHDRAW_LIKE,
+            name: "withdraw_like".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for definitive passes
+    pub fn init_submit_pass_comp_def(ctx: Context<InitSubmitPassCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_SUBMIT_PASS)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_SUBMIT_PASS,
+            name: "submit_pass".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for session nonce rotation
+    pub fn init_rotate_session_nonce_comp_def(ctx: Context<InitRotateSessionNonceCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_ROTATE_SESSION_NONCE)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_ROTATE_SESSION_NONCE,
+            name: "rotate_session_nonce".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for pairwise compatibility scoring
+    pub fn init_calculate_compatibility_comp_def(ctx: Context<InitCalculateCompatibilityCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY,
+            name: "calculate_compatibility".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for batch compatibility scoring
+    pub fn init_calculate_compatibility_batch_comp_def(ctx: Context<InitCalculateCompatibilityBatchCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY_BATCH)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY_BATCH,
+            name: "calculate_compatibility_batch".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+
+    /// Initialize computation definition for match feedback
+    pub fn init_submit_feedback_comp_def(ctx: Context<InitSubmitFeedbackCompDef>) -> Result<()> {
+        require_comp_def_offset(&ctx.accounts.comp_def_account.to_account_info(), COMP_DEF_OFFSET_SUBMIT_FEEDBACK)?;
+        let newly_created = ctx.accounts.comp_def_account.data_is_empty();
+        if newly_created {
+            init_comp_def(ctx.accounts, true, 0, None, None)?;
+        }
+        emit!(CompDefInitializedEvent {
+            offset: COMP_DEF_OFFSET_SUBMIT_FEEDBACK,
+            name: "submit_feedback".to_string(),
+            newly_created,
+        });
+        Ok(())
+    }
+}
+
+// ============================================================================
+// ACCOUNT VALIDATION STRUCTS FOR MPC OPERATIONS
+// ============================================================================
+
+
+#[queue_computation_accounts("init_match_session", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, session_id: u64, user_a: Pubkey, user_b: Pubkey)]
+pub struct InitMatchSession<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MatchPairSession::INIT_SPACE,
+        seeds = [
+            b"pair_session",
+            session_pair(&user_a, &user_b).0.as_ref(),
+            session_pair(&user_a, &user_b).1.as_ref()
+        ],
+        bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", payer.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_a.as_ref()],
+        bump
+    )]
+    /// CHECK: Loaded by `load_user_profile`, which fails with ProfileNotFound
+    pub user_a_profile: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_b.as_ref()],
+        bump
+    )]
+    /// CHECK: Loaded by `load_user_profile`, which fails with ProfileNotFound
+    pub user_b_profile: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", user_a.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_a blocked nobody
+    pub user_a_blocklist: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", user_b.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_b blocked nobody
+    pub user_b_blocklist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[queue_computation_accounts("init_match_session", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, user_a: Pubkey, user_b: Pubkey)]
+pub struct InitMatchSessionAuto<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MatchPairSession::INIT_SPACE,
+        seeds = [
+            b"pair_session",
+            session_pair(&user_a, &user_b).0.as_ref(),
+            session_pair(&user_a, &user_b).1.as_ref()
+        ],
+        bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", payer.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_a.as_ref()],
+        bump
+    )]
+    /// CHECK: Loaded by `load_user_profile`, which fails with ProfileNotFound
+    pub user_a_profile: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", user_b.as_ref()],
+        bump
+    )]
+    /// CHECK: Loaded by `load_user_profile`, which fails with ProfileNotFound
+    pub user_b_profile: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", user_a.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_a blocked nobody
+    pub user_a_blocklist: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", user_b.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_b blocked nobody
+    pub user_b_blocklist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SessionCounter::INIT_SPACE,
+        seeds = [b"session_counter"],
+        bump
+    )]
+    pub session_counter: Account<'info, SessionCounter>,
+}
+
+#[callback_accounts("init_match_session", payer)]
+#[derive(Accounts)]
+pub struct InitMatchSessionCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+
+#[queue_computation_accounts("submit_like", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SubmitLike<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_LIKE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(mut)]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    /// Required only when a relayer submits the like on a participant's behalf
+    #[account(
+        seeds = [b"like_delegate", like_delegate.owner.as_ref()],
+        bump = like_delegate.bump
+    )]
+    pub like_delegate: Option<Account<'info, LikeDelegate>>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", nonce_counter.owner.as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    /// The liker's quota; `submit_like` checks the nonce counter belongs to the liker
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LikeQuota::INIT_SPACE,
+        seeds = [b"quota", nonce_counter.owner.as_ref()],
+        bump
+    )]
+    pub like_quota: Box<Account<'info, LikeQuota>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(seeds = [b"blocklist", match_pair_session.user_a.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_a blocked nobody
+    pub user_a_blocklist: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", match_pair_session.user_b.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_b blocked nobody
+    pub user_b_blocklist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[callback_accounts("submit_like", user)]
+#[derive(Accounts)]
+pub struct SubmitLikeCallback<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_LIKE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// Checked against the session's participants by `apply_submit_like`
+    #[account(mut)]
+    pub liker_profile: Box<Account<'info, UserProfile>>,
+    #[account(mut)]
+    pub target_profile: Box<Account<'info, UserProfile>>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+
+#[queue_computation_accounts("withdraw_like", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct WithdrawLike<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_WITHDRAW_LIKE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", user.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+}
+
+#[callback_accounts("withdraw_like", user)]
+#[derive(Accounts)]
+pub struct WithdrawLikeCallback<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_WITHDRAW_LIKE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+
+#[queue_computation_accounts("submit_pass", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SubmitPass<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_PASS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", user.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+}
+
+#[callback_accounts("submit_pass", user)]
+#[derive(Accounts)]
+pub struct SubmitPassCallback<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_PASS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+
+#[queue_computation_accounts("check_mutual_match", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CheckMutualMatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(mut)]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    // Written in place by the mock-mpc backend
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        seeds = [b"matching_ciphertexts", match_pair_session.user_a.as_ref()],
+        bump = user_a_matching_ciphertexts.bump
+    )]
+    pub user_a_matching_ciphertexts: Box<Account<'info, MatchingCiphertexts>>,
+
+    #[account(
+        seeds = [b"matching_ciphertexts", match_pair_session.user_b.as_ref()],
+        bump = user_b_matching_ciphertexts.bump
+    )]
+    pub user_b_matching_ciphertexts: Box<Account<'info, MatchingCiphertexts>>,
+}
+
+#[callback_accounts("check_mutual_match", payer)]
+#[derive(Accounts)]
+pub struct CheckMutualMatchCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    #[account(
+        mut,
+        seeds = [b"platform_stats"],
+        bump = platform_stats.bump
+    )]
+    pub platform_stats: Account<'info, PlatformStats>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+
+#[queue_computation_accounts("reveal_matched_data", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RevealMatchedData<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_MATCHED_DATA)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(mut)]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        seeds = [b"disclosure", match_pair_session.key().as_ref(), match_pair_session.user_a.as_ref()],
+        bump = user_a_consent.bump
+    )]
+    pub user_a_consent: Account<'info, DisclosureConsent>,
+
+    #[account(
+        seeds = [b"disclosure", match_pair_session.key().as_ref(), match_pair_session.user_b.as_ref()],
+        bump = user_b_consent.bump
+    )]
+    pub user_b_consent: Account<'info, DisclosureConsent>,
+}
+
+#[callback_accounts("reveal_matched_data", payer)]
+#[derive(Accounts)]
+pub struct RevealMatchedDataCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_MATCHED_DATA)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+
+#[queue_computation_accounts("init_match_session", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct Rekindle<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", payer.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump
+    )]
+    /// CHECK: Loaded by `load_user_profile`, which fails with ProfileNotFound
+    pub user_a_profile: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump
+    )]
+    /// CHECK: Loaded by `load_user_profile`, which fails with ProfileNotFound
+    pub user_b_profile: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", match_pair_session.user_a.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_a blocked nobody
+    pub user_a_blocklist: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"blocklist", match_pair_session.user_b.as_ref()], bump)]
+    /// CHECK: Read by `require_not_blocked`; absent when user_b blocked nobody
+    pub user_b_blocklist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[queue_computation_accounts("init_match_session", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct MigrateSession<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(mut)]
+    /// CHECK: May still have a legacy layout; owner, discriminator and PDA are checked in the handler
+    pub match_pair_session: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", payer.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+}
+
+#[queue_computation_accounts("rotate_session_nonce", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RotateSessionNonce<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ROTATE_SESSION_NONCE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+}
+
+#[callback_accounts("rotate_session_nonce", payer)]
+#[derive(Accounts)]
+pub struct RotateSessionNonceCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_ROTATE_SESSION_NONCE)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("reveal_icebreakers", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RevealIcebreakers<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_ICEBREAKERS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    
+    #[account(mut)]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        seeds = [b"user_profile", match_pair_session.user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        seeds = [b"user_profile", match_pair_session.user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+}
+
+#[callback_accounts("reveal_icebreakers", payer)]
+#[derive(Accounts)]
+pub struct RevealIcebreakersCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_ICEBREAKERS)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
+    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+#[queue_computation_accounts("calculate_compatibility", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, user_a: Pubkey, user_b: Pubkey)]
+pub struct CalculateCompatibility<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Account<'info, MXEAccount>,
+    
+    #[account(
+        mut,
+        address = derive_mempool_pda!()
+    )]
+    /// CHECK: Mempool account is validated by Arcium framework
+    pub mempool_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_execpool_pda!()
+    )]
+    /// CHECK: Executing pool account is validated by Arcium framework
+    pub executing_pool: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        address = derive_comp_pda!(computation_offset)
+    )]
+    /// CHECK: Computation account is validated by Arcium framework
+    pub computation_account: UncheckedAccount<'info>,
+    
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    
+    #[account(
+        mut,
+        address = derive_cluster_pda!(mxe_account)
+    )]
+    pub cluster_account: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+    )]
+    pub pool_account: Account<'info, FeePool>,
+    
+    #[account(
+        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+    )]
+    pub clock_account: Account<'info, ClockAccount>,
+    
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+
+    #[account(
+        seeds = [b"user_profile", user_a.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        seeds = [b"user_profile", user_b.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+
+    #[account(
+        seeds = [b"matching_ciphertexts", user_a.as_ref()],
+        bump = user_a_matching_ciphertexts.bump
+    )]
+    pub user_a_matching_ciphertexts: Box<Account<'info, MatchingCiphertexts>>,
+
+    #[account(
+        seeds = [b"matching_ciphertexts", user_b.as_ref()],
+        bump = user_b_matching_ciphertexts.bump
+    )]
+    pub user_b_matching_ciphertexts: Box<Account<'info, MatchingCiphertexts>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [b"reputation", user_a.as_ref()],
+        bump
+    )]
+    pub user_a_reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [b"reputation", user_b.as_ref()],
+        bump
+    )]
+    pub user_b_reputation: Box<Account<'info, Reputation>>,
+}
+
+#[callback_accounts("calculate_compatibility", payer)]
+#[derive(Accounts)]
+pub struct CalculateCompatibilityCallback<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY)
+    )]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: Pinned to the instructions sysvar address
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(
+        seeds = [b"user_profile", user_a_profile.owner.as_ref()],
+        bump = user_a_profile.bump
+    )]
+    pub user_a_profile: Box<Account<'info, UserProfile>>,
+    #[account(
+        seeds = [b"user_profile", user_b_profile.owner.as_ref()],
+        bump = user_b_profile.bump
+    )]
+    pub user_b_profile: Box<Account<'info, UserProfile>>,
+}
+
+#[queue_computation_accounts("calculate_compatibility_batch", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateCompatibilityBatch<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
@@ -669,7 +8723,7 @@ pub struct InitMatchSession<'info> {
     pub computation_account: UncheckedAccount<'info>,
     
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY_BATCH)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     
@@ -694,117 +8748,152 @@ pub struct InitMatchSession<'info> {
     pub arcium_program: Program<'info, Arcium>,
     
     #[account(
-        init,
+        seeds = [b"matching_ciphertexts", payer.key().as_ref()],
+        bump = requester_matching_ciphertexts.bump
+    )]
+    pub requester_matching_ciphertexts: Box<Account<'info, MatchingCiphertexts>>,
+
+    #[account(
+        init_if_needed,
         payer = payer,
-        space = 8 + MatchPairSession::INIT_SPACE,
-        seeds = [b"match_session", session_id.to_le_bytes().as_ref()],
+        space = 8 + CompatibilityBatch::INIT_SPACE,
+        seeds = [b"compat_batch", payer.key().as_ref()],
         bump
     )]
-    pub match_pair_session: Account<'info, MatchPairSession>,
+    pub compatibility_batch: Box<Account<'info, CompatibilityBatch>>,
 }
 
-#[callback_accounts("init_match_session", payer)]
+#[callback_accounts("calculate_compatibility_batch", payer)]
 #[derive(Accounts)]
-pub struct InitMatchSessionCallback<'info> {
+pub struct CalculateCompatibilityBatchCallback<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub arcium_program: Program<'info, Arcium>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_INIT_MATCH_SESSION)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_COMPATIBILITY_BATCH)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: Instructions sysvar is validated by Arcium framework
+    /// CHECK: Pinned to the instructions sysvar address
     pub instructions_sysvar: AccountInfo<'info>,
-    #[account(mut)]
-    pub match_pair_session: Account<'info, MatchPairSession>,
+    #[account(
+        mut,
+        seeds = [b"compat_batch", compatibility_batch.owner.as_ref()],
+        bump = compatibility_batch.bump
+    )]
+    pub compatibility_batch: Box<Account<'info, CompatibilityBatch>>,
+    /// CHECK: The computation this callback completes, compared with the
+    /// batch's last applied computation
+    pub computation_account: UncheckedAccount<'info>,
 }
 
 
-#[queue_computation_accounts("submit_like", user)]
+#[init_computation_definition_accounts("init_match_session", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct SubmitLike<'info> {
+pub struct InitInitMatchSessionCompDef<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
-    
-    #[account(
-        address = derive_mxe_pda!()
-    )]
-    pub mxe_account: Account<'info, MXEAccount>,
-    
+    pub payer: Signer<'info>,
     #[account(
         mut,
-        address = derive_mempool_pda!()
+        address = derive_mxe_pda!()
     )]
-    /// CHECK: Mempool account is validated by Arcium framework
-    pub mempool_account: UncheckedAccount<'info>,
-    
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("submit_like", payer)]
+#[derive(Accounts)]
+pub struct InitSubmitLikeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
         mut,
-        address = derive_execpool_pda!()
+        address = derive_mxe_pda!()
     )]
-    /// CHECK: Executing pool account is validated by Arcium framework
-    pub executing_pool: UncheckedAccount<'info>,
-    
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("withdraw_like", payer)]
+#[derive(Accounts)]
+pub struct InitWithdrawLikeCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
         mut,
-        address = derive_comp_pda!(computation_offset)
-    )]
-    /// CHECK: Computation account is validated by Arcium framework
-    pub computation_account: UncheckedAccount<'info>,
-    
-    #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_LIKE)
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("submit_pass", payer)]
+#[derive(Accounts)]
+pub struct InitSubmitPassCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
         mut,
-        address = derive_cluster_pda!(mxe_account)
+        address = derive_mxe_pda!()
     )]
-    pub cluster_account: Account<'info, Cluster>,
-    
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("check_mutual_match", payer)]
+#[derive(Accounts)]
+pub struct InitCheckMutualMatchCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
     #[account(
         mut,
-        address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
-    )]
-    pub pool_account: Account<'info, FeePool>,
-    
-    #[account(
-        address = ARCIUM_CLOCK_ACCOUNT_ADDRESS
+        address = derive_mxe_pda!()
     )]
-    pub clock_account: Account<'info, ClockAccount>,
-    
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
-    
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
     #[account(mut)]
-    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-#[callback_accounts("submit_like", user)]
+#[init_computation_definition_accounts("reveal_matched_data", payer)]
 #[derive(Accounts)]
-pub struct SubmitLikeCallback<'info> {
+pub struct InitRevealMatchedDataCompDef<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
-    pub arcium_program: Program<'info, Arcium>,
+    pub payer: Signer<'info>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_LIKE)
+        mut,
+        address = derive_mxe_pda!()
     )]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: Instructions sysvar is validated by Arcium framework
-    pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
     #[account(mut)]
-    pub match_pair_session: Account<'info, MatchPairSession>,
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-
-#[queue_computation_accounts("check_mutual_match", payer)]
+#[queue_computation_accounts("submit_feedback", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct CheckMutualMatch<'info> {
+#[instruction(computation_offset: u64, rated_user: Pubkey)]
+pub struct SubmitFeedback<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     
@@ -835,7 +8924,7 @@ pub struct CheckMutualMatch<'info> {
     pub computation_account: UncheckedAccount<'info>,
     
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_FEEDBACK)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     
@@ -859,31 +8948,80 @@ pub struct CheckMutualMatch<'info> {
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
     pub match_pair_session: Account<'info, MatchPairSession>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + Reputation::INIT_SPACE,
+        seeds = [b"reputation", rated_user.as_ref()],
+        bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"nonce_counter", payer.key().as_ref()],
+        bump = nonce_counter.bump
+    )]
+    pub nonce_counter: Account<'info, NonceCounter>,
 }
 
-#[callback_accounts("check_mutual_match", payer)]
+#[callback_accounts("submit_feedback", payer)]
 #[derive(Accounts)]
-pub struct CheckMutualMatchCallback<'info> {
+pub struct SubmitFeedbackCallback<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub arcium_program: Program<'info, Arcium>,
     #[account(
-        address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_MUTUAL_MATCH)
+        address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_FEEDBACK)
     )]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: Instructions sysvar is validated by Arcium framework
+    /// CHECK: Pinned to the instructions sysvar address
     pub instructions_sysvar: AccountInfo<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"pair_session", match_pair_session.pair_low().as_ref(), match_pair_session.pair_high().as_ref()],
+        bump = match_pair_session.bump
+    )]
     pub match_pair_session: Account<'info, MatchPairSession>,
+    #[account(
+        mut,
+        seeds = [b"reputation", reputation.owner.as_ref()],
+        bump = reputation.bump
+    )]
+    pub reputation: Box<Account<'info, Reputation>>,
+    /// CHECK: The computation this callback completes, checked against the
+    /// pending one by `complete_computation`
+    pub computation_account: UncheckedAccount<'info>,
 }
 
+#[init_computation_definition_accounts("submit_feedback", payer)]
+#[derive(Accounts)]
+pub struct InitSubmitFeedbackCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
 
-#[init_computation_definition_accounts("init_match_session", payer)]
+#[init_computation_definition_accounts("calculate_compatibility", payer)]
 #[derive(Accounts)]
-pub struct InitInitMatchSessionCompDef<'info> {
+pub struct InitCalculateCompatibilityCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -898,9 +9036,9 @@ pub struct InitInitMatchSessionCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[init_computation_definition_accounts("submit_like", payer)]
+#[init_computation_definition_accounts("calculate_compatibility_batch", payer)]
 #[derive(Accounts)]
-pub struct InitSubmitLikeCompDef<'info> {
+pub struct InitCalculateCompatibilityBatchCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -915,9 +9053,26 @@ pub struct InitSubmitLikeCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[init_computation_definition_accounts("check_mutual_match", payer)]
+#[init_computation_definition_accounts("reveal_icebreakers", payer)]
 #[derive(Accounts)]
-pub struct InitCheckMutualMatchCompDef<'info> {
+pub struct InitRevealIcebreakersCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        address = derive_mxe_pda!()
+    )]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: Computation definition account is validated by Arcium framework
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("rotate_session_nonce", payer)]
+#[derive(Accounts)]
+pub struct InitRotateSessionNonceCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -931,3 +9086,244 @@ pub struct InitCheckMutualMatchCompDef<'info> {
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avatar_urls_must_be_https_with_a_host() {
+        assert!(validate_avatar_url("https://example.com/avatar.jpg").is_ok());
+        assert!(validate_avatar_url("https://cdn.example.com").is_ok());
+
+        assert!(validate_avatar_url("http://example.com/avatar.jpg").is_err());
+        assert!(validate_avatar_url("javascript:alert(1)").is_err());
+        assert!(validate_avatar_url("HTTPS://example.com/avatar.jpg").is_err());
+        assert!(validate_avatar_url("https://").is_err());
+        assert!(validate_avatar_url("https:///avatar.jpg").is_err());
+        assert!(validate_avatar_url("https://?size=64").is_err());
+    }
+
+    #[test]
+    fn avatar_urls_are_capped_at_196_bytes() {
+        let prefix = "https://example.com/";
+        let longest = format!("{}{}", prefix, "a".repeat(MAX_AVATAR_URL_LEN - prefix.len()));
+        assert!(validate_avatar_url(&longest).is_ok());
+        assert!(validate_avatar_url(&format!("{}a", longest)).is_err());
+    }
+
+    #[test]
+    fn photo_urls_follow_the_avatar_rules() {
+        assert!(validate_photo_url("https://example.com/photo-1.jpg").is_ok());
+        assert!(validate_photo_url("").is_err());
+        assert!(validate_photo_url("http://example.com/photo-1.jpg").is_err());
+        assert!(validate_photo_url(&format!("https://example.com/{}", "a".repeat(MAX_PHOTO_URL_LEN))).is_err());
+    }
+
+    fn like_quota() -> LikeQuota {
+        LikeQuota { owner: Pubkey::new_unique(), likes_today: 0, super_likes_today: 0, window_start: 0, bump: 0 }
+    }
+
+    #[test]
+    fn like_quota_stops_at_the_limit_until_the_next_utc_day() {
+        let mut quota = like_quota();
+        let noon = 20_000 * SECONDS_PER_DAY + SECONDS_PER_DAY / 2;
+
+        for _ in 0..3 {
+            quota.consume(noon, 3).unwrap();
+        }
+        assert_eq!(quota.window_start, 20_000 * SECONDS_PER_DAY);
+        assert_eq!(quota.remaining(noon, 3), 0);
+        assert!(quota.consume(noon + 3_600, 3).is_err());
+
+        let next_day = 20_001 * SECONDS_PER_DAY;
+        assert_eq!(quota.remaining(next_day, 3), 3);
+        quota.consume(next_day, 3).unwrap();
+        assert_eq!(quota.likes_today, 1);
+        assert_eq!(quota.window_start, next_day);
+    }
+
+    #[test]
+    fn like_quota_ignores_a_clock_that_steps_back() {
+        let mut quota = like_quota();
+        let day = 20_000 * SECONDS_PER_DAY;
+
+        quota.consume(day + 10, 2).unwrap();
+        quota.consume(day - 10, 2).unwrap();
+        assert_eq!((quota.likes_today, quota.window_start), (2, day));
+        assert!(quota.consume(day - 10, 2).is_err());
+    }
+
+    #[test]
+    fn super_likes_have_their_own_daily_quota() {
+        let mut quota = like_quota();
+        let day = 20_000 * SECONDS_PER_DAY;
+
+        quota.consume(day, 1).unwrap();
+        quota.consume_super_like(day + 60).unwrap();
+        assert!(quota.consume_super_like(day + 120).is_err());
+        assert_eq!(quota.remaining_super_likes(day + 120), 0);
+        assert_eq!(quota.likes_today, 1);
+
+        let next_day = day + SECONDS_PER_DAY;
+        assert_eq!(quota.remaining_super_likes(next_day), MAX_DAILY_SUPER_LIKES);
+        quota.consume_super_like(next_day).unwrap();
+        assert_eq!((quota.likes_today, quota.super_likes_today), (0, 1));
+    }
+
+    #[test]
+    fn quantized_coordinates_are_one_km_cells_and_never_unset() {
+        assert_eq!(quantize_coordinates(-90.0, -180.0), (1, 1));
+        assert_eq!(quantize_coordinates(90.0, 180.0), (40076, 20039));
+        // Out-of-range input clamps to the grid edges
+        assert_eq!(quantize_coordinates(-91.0, 200.0), (40076, 1));
+
+        let (x, y) = quantize_coordinates(0.0, 0.0);
+        let (north_x, north_y) = quantize_coordinates(0.1, 0.0);
+        assert_eq!((north_x, north_y - y), (x, 11));
+    }
+
+    #[test]
+    fn byte_pages_walk_the_history_by_offset() {
+        let history: Vec<u8> = (0..10).collect();
+
+        let first = byte_page(&history, 0, 4, 2).unwrap();
+        assert_eq!((first.bytes, first.next_offset), (vec![0, 1, 2, 3], Some(4)));
+        let last = byte_page(&history, 8, 4, 2).unwrap();
+        assert_eq!((last.bytes, last.next_offset), (vec![8, 9], None));
+
+        assert!(byte_page(&history, 10, 4, 2).is_err());
+        assert!(byte_page(&[], 0, 4, 2).unwrap().bytes.is_empty());
+        assert!(byte_page(&[], 1, 4, 2).is_err());
+    }
+
+    #[test]
+    fn byte_pages_start_on_a_record_boundary() {
+        let history: Vec<u8> = (0..10).collect();
+        assert!(byte_page(&history, 3, 4, 2).is_err());
+        assert_eq!(byte_page(&history, 6, 4, 2).unwrap().bytes, vec![6, 7, 8, 9]);
+        assert_eq!(LIKES_PAGE_LEN % ENCRYPTED_LIKE_RECORD_LEN, 0);
+    }
+
+    #[test]
+    fn degenerate_encrypted_blobs_are_rejected() {
+        let private_data = vec![1u8; 64];
+        let preferences = vec![2u8; 64];
+        assert!(validate_encrypted_blobs(&private_data, &preferences).is_ok());
+
+        // All zeros in either field
+        assert!(validate_encrypted_blobs(&[0u8; 64], &preferences).is_err());
+        assert!(validate_encrypted_blobs(&private_data, &[0u8; 64]).is_err());
+        // The same bytes submitted for both fields
+        assert!(validate_encrypted_blobs(&private_data, &private_data).is_err());
+        // Empty blobs are allowed, alone or together
+        assert!(validate_encrypted_blobs(&[], &preferences).is_ok());
+        assert!(validate_encrypted_blobs(&[], &[]).is_ok());
+    }
+
+    #[test]
+    fn report_tally_triggers_at_the_threshold() {
+        let mut tally = ReportTally { reported: Pubkey::new_unique(), verified_reports: 0, bump: 0 };
+        for _ in 1..AUTO_DEACTIVATION_REPORT_THRESHOLD {
+            assert!(!tally.record());
+        }
+        assert!(tally.record());
+        // Further reporters keep it over the threshold
+        assert!(tally.record());
+        assert_eq!(tally.verified_reports, AUTO_DEACTIVATION_REPORT_THRESHOLD + 1);
+    }
+
+    #[test]
+    fn matching_preferences_stay_within_circuit_bounds() {
+        let mut preferences = MatchingPreferences {
+            preferred_age_min: 25,
+            preferred_age_max: 35,
+            preferred_distance_km: 50,
+            interests: (0..MAX_INTERESTS).map(|i| format!("interest{}", i)).collect(),
+            relationship_type: "long_term".to_string(),
+            pet_preference: 0,
+            pet_dealbreaker: false,
+            budget_tier_min: 0,
+            budget_tier_max: 3,
+            relationship_status_preference: 0,
+            religion_preference: 0,
+            diet_preference: 0,
+            diet_dealbreaker: false,
+            max_age_gap: 0,
+            children_preference: 0,
+            min_youngest_child_bucket: 0,
+            open_to_long_distance: false,
+            preferred_height_min: 160,
+            preferred_height_max: 0,
+            body_type_preference: 0,
+            preferred_genders: 0,
+        };
+        assert!(preferences.validate().is_ok());
+
+        preferences.interests.push("one too many".to_string());
+        assert!(preferences.validate().is_err());
+        preferences.interests.pop();
+
+        // 0 leaves the top of the height range open; otherwise it must not be below the minimum
+        preferences.preferred_height_max = 150;
+        assert!(preferences.validate().is_err());
+        preferences.preferred_height_max = 160;
+        assert!(preferences.validate().is_ok());
+    }
+
+    #[test]
+    fn recommended_session_ttl_follows_idle_buckets() {
+        let (min, max) = (DEFAULT_MIN_SESSION_TTL, DEFAULT_MAX_SESSION_TTL);
+        let now = 1_000 * SECONDS_PER_DAY;
+        let midpoint = min + (max - min) / 2;
+
+        assert_eq!(recommended_session_ttl(now, 0, now, min, max), max);
+        assert_eq!(recommended_session_ttl(now - SECONDS_PER_DAY, 0, now, min, max), max);
+        assert_eq!(recommended_session_ttl(now - SECONDS_PER_DAY - 1, 0, now, min, max), midpoint);
+        assert_eq!(recommended_session_ttl(now - 7 * SECONDS_PER_DAY, 0, now, min, max), midpoint);
+        assert_eq!(recommended_session_ttl(now - 7 * SECONDS_PER_DAY - 1, 0, now, min, max), min);
+        // Activity in the future counts as just now
+        assert_eq!(recommended_session_ttl(now + 60, 0, now, min, max), max);
+    }
+
+    #[test]
+    fn recommended_session_ttl_adds_half_a_day_per_match_within_bounds() {
+        let (min, max) = (DEFAULT_MIN_SESSION_TTL, DEFAULT_MAX_SESSION_TTL);
+        let now = 1_000 * SECONDS_PER_DAY;
+        let idle = now - 30 * SECONDS_PER_DAY;
+
+        assert_eq!(recommended_session_ttl(idle, 1, now, min, max), min + SECONDS_PER_DAY / 2);
+        assert_eq!(recommended_session_ttl(idle, 4, now, min, max), min + 2 * SECONDS_PER_DAY);
+        // Clamped to the upper bound, however many matches
+        assert_eq!(recommended_session_ttl(idle, 1_000, now, min, max), max);
+        assert_eq!(recommended_session_ttl(now, u32::MAX, now, min, max), max);
+        // A collapsed range pins the result
+        assert_eq!(recommended_session_ttl(idle, 3, now, min, min), min);
+    }
+
+    #[test]
+    fn presence_heartbeat_extends_the_session_idle_ttl() {
+        // Every field zeroed, as a freshly allocated account decodes
+        let blank_profile = || UserProfile::deserialize(&mut &[0u8; 2048][..]).unwrap();
+        let mut config = ProgramConfig::deserialize(&mut &[0u8; 512][..]).unwrap();
+        config.min_session_ttl = DEFAULT_MIN_SESSION_TTL;
+        config.max_session_ttl = DEFAULT_MAX_SESSION_TTL;
+        let now = 1_000 * SECONDS_PER_DAY;
+
+        let mut idle = blank_profile();
+        idle.last_updated = now - 30 * SECONDS_PER_DAY;
+        assert_eq!(session_idle_ttl(&idle, &idle, &config, now), DEFAULT_MIN_SESSION_TTL);
+
+        // A recent heartbeat counts as activity without any profile edit
+        let mut present = blank_profile();
+        present.last_updated = idle.last_updated;
+        present.last_seen = now - 60;
+        assert_eq!(present.last_active_at(), now - 60);
+        assert_eq!(session_idle_ttl(&idle, &present, &config, now), DEFAULT_MAX_SESSION_TTL);
+
+        // An old heartbeat never hides a newer profile write
+        present.last_seen = idle.last_updated;
+        present.last_updated = now;
+        assert_eq!(present.last_active_at(), now);
+    }
+}
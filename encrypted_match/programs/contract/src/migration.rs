@@ -0,0 +1,357 @@
+// ============================================================================
+// PROFILE LAYOUT MIGRATION
+// ============================================================================
+//
+// `UserProfile` fields are only ever appended. From version 7 on, every layout
+// stores `layout_version` at the same offset, so those accounts say which
+// layout they hold whatever their length; compact accounts (see
+// `UserProfile::COMPACT_SPACE`) rely on this. Each earlier addition grew
+// `INIT_SPACE`, so an older account's data length identifies its layout, and
+// is checked first: where a later layout keeps layout_version, an older one
+// may hold anything. Self-describing accounts are never sized at a legacy
+// length (see `self_describing_len`).
+// Accounts are decoded field by field up to the end of their layout and the
+// appended fields take their defaults (no icebreaker, not paused, no boost,
+// public, anyone may like, nothing archived, no open sessions, unspecified
+// gender and orientation, no gallery beyond the avatar, no bio, last seen at
+// the last update, unverified). Bytes past a legacy layout are never read:
+// Anchor does not clear them when a Vec shrinks, so they may hold stale data.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    ErrorCode, UserProfile, GENDER_UNSPECIFIED, ICEBREAKER_ANSWER_LEN, LIKE_POLICY_ANYONE,
+    MAX_BIO_LEN, MAX_PHOTOS, MAX_PHOTO_URL_LEN, ORIENTATION_UNSPECIFIED, PRIVACY_LEVEL_PUBLIC,
+    PROFILE_LAYOUT_VERSION,
+};
+
+/// Bytes each layout appended to the one before it, starting with version 2:
+/// icebreaker_answer, matching_paused, boost_until, privacy_level, the like
+/// policy fields, layout_version, archived_match_records, open_sessions,
+/// gender and orientation, photo_urls, bio, last_seen, is_verified
+const APPENDED_SPACE: [usize; PROFILE_LAYOUT_VERSION as usize - 1] = [
+    4 + ICEBREAKER_ANSWER_LEN,
+    1,
+    8,
+    1,
+    3,
+    1,
+    4,
+    4,
+    2,
+    4 + MAX_PHOTOS * (4 + MAX_PHOTO_URL_LEN),
+    4 + MAX_BIO_LEN,
+    8,
+    1,
+];
+
+/// First layout that stores its own version
+const SELF_DESCRIBING_LAYOUT: u8 = 7;
+
+/// Account length (discriminator included) of a given layout version
+pub fn profile_account_len(version: u8) -> usize {
+    let appended: usize = APPENDED_SPACE.iter().sum();
+    let v1_space = UserProfile::INIT_SPACE - appended;
+    8 + v1_space + APPENDED_SPACE[..version as usize - 1].iter().sum::<usize>()
+}
+
+/// The legacy layout an account of `len` bytes holds, if any
+fn legacy_version(len: usize) -> Option<u8> {
+    (1..SELF_DESCRIBING_LAYOUT).find(|&version| profile_account_len(version) == len)
+}
+
+/// Account length to allocate for a self-describing profile needing `len`
+/// bytes: padded past any legacy layout's length, so that length alone
+/// identifies a legacy account
+pub fn self_describing_len(len: usize) -> usize {
+    let mut len = len;
+    while legacy_version(len).is_some() {
+        len += 1;
+    }
+    len
+}
+
+/// Fields of the original (version 1) layout, in order
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProfileV1 {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub created_at: i64,
+    pub last_updated: i64,
+    pub profile_version: u8,
+    pub username: String,
+    pub avatar_url: String,
+    pub age: u8,
+    pub location_city: String,
+    pub is_active: bool,
+    pub encryption_pubkey: [u8; 32],
+    pub encrypted_private_data: Vec<u8>,
+    pub encrypted_preferences: Vec<u8>,
+    pub encrypted_likes_given: Vec<u8>,
+    pub encrypted_likes_received: Vec<u8>,
+    pub match_history: Vec<u8>,
+    pub total_likes_given: u32,
+    pub total_likes_received: u32,
+    pub total_matches: u32,
+}
+
+/// Decodes a profile account of any layout as the current `UserProfile`,
+/// returning the layout version it was stored in
+pub fn decode_profile(data: &[u8]) -> Result<(u8, UserProfile)> {
+    require!(data.starts_with(UserProfile::DISCRIMINATOR), ErrorCode::ProfileNotFound);
+
+    if let Some(version) = legacy_version(data.len()) {
+        return Ok((version, decode_layout(data, version)?));
+    }
+
+    let version = decode_layout(data, SELF_DESCRIBING_LAYOUT)
+        .map_err(|_| error!(ErrorCode::ProfileNotFound))?
+        .layout_version;
+    require!(
+        (SELF_DESCRIBING_LAYOUT..=PROFILE_LAYOUT_VERSION).contains(&version),
+        ErrorCode::ProfileNotFound
+    );
+    Ok((version, decode_layout(data, version)?))
+}
+
+/// Decodes `data` as the given layout version, defaulting later fields
+fn decode_layout(data: &[u8], version: u8) -> Result<UserProfile> {
+    let mut cursor = &data[8..];
+    let v1 = ProfileV1::deserialize(&mut cursor)?;
+    let icebreaker_answer = if version >= 2 { Vec::<u8>::deserialize(&mut cursor)? } else { Vec::new() };
+    let matching_paused = if version >= 3 { bool::deserialize(&mut cursor)? } else { false };
+    let boost_until = if version >= 4 { i64::deserialize(&mut cursor)? } else { 0 };
+    let privacy_level = if version >= 5 { u8::deserialize(&mut cursor)? } else { PRIVACY_LEVEL_PUBLIC };
+    let (like_policy, like_age_min, like_age_max) = if version >= 6 {
+        <(u8, u8, u8)>::deserialize(&mut cursor)?
+    } else {
+        (LIKE_POLICY_ANYONE, 0, 0)
+    };
+    let layout_version = if version >= SELF_DESCRIBING_LAYOUT { u8::deserialize(&mut cursor)? } else { version };
+    let archived_match_records = if version >= 8 { u32::deserialize(&mut cursor)? } else { 0 };
+    // Sessions opened before the counter existed are not tracked
+    let open_sessions = if version >= 9 { u32::deserialize(&mut cursor)? } else { 0 };
+    let (gender, orientation) = if version >= 10 {
+        <(u8, u8)>::deserialize(&mut cursor)?
+    } else {
+        (GENDER_UNSPECIFIED, ORIENTATION_UNSPECIFIED)
+    };
+    let photo_urls = if version >= 11 { Vec::<String>::deserialize(&mut cursor)? } else { Vec::new() };
+    let bio = if version >= 12 { String::deserialize(&mut cursor)? } else { String::new() };
+    let last_seen = if version >= 13 { i64::deserialize(&mut cursor)? } else { v1.last_updated };
+    let is_verified = if version >= 14 { bool::deserialize(&mut cursor)? } else { false };
+
+    Ok(UserProfile {
+        owner: v1.owner,
+        bump: v1.bump,
+        created_at: v1.created_at,
+        last_updated: v1.last_updated,
+        profile_version: v1.profile_version,
+        username: v1.username,
+        avatar_url: v1.avatar_url,
+        age: v1.age,
+        location_city: v1.location_city,
+        is_active: v1.is_active,
+        encryption_pubkey: v1.encryption_pubkey,
+        encrypted_private_data: v1.encrypted_private_data,
+        encrypted_preferences: v1.encrypted_preferences,
+        encrypted_likes_given: v1.encrypted_likes_given,
+        encrypted_likes_received: v1.encrypted_likes_received,
+        match_history: v1.match_history,
+        total_likes_given: v1.total_likes_given,
+        total_likes_received: v1.total_likes_received,
+        total_matches: v1.total_matches,
+        icebreaker_answer,
+        matching_paused,
+        boost_until,
+        privacy_level,
+        like_policy,
+        like_age_min,
+        like_age_max,
+        layout_version,
+        archived_match_records,
+        open_sessions,
+        gender,
+        orientation,
+        photo_urls,
+        bio,
+        last_seen,
+        is_verified,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v1_profile() -> ProfileV1 {
+        ProfileV1 {
+            owner: Pubkey::new_unique(),
+            bump: 254,
+            created_at: 1_700_000_000,
+            last_updated: 1_700_000_100,
+            profile_version: 1,
+            username: "alice".to_string(),
+            avatar_url: "https://example.com/a.png".to_string(),
+            age: 29,
+            location_city: "Lisbon".to_string(),
+            is_active: true,
+            encryption_pubkey: [7; 32],
+            encrypted_private_data: vec![1; 64],
+            encrypted_preferences: vec![2; 64],
+            encrypted_likes_given: Vec::new(),
+            encrypted_likes_received: Vec::new(),
+            match_history: Vec::new(),
+            total_likes_given: 4,
+            total_likes_received: 5,
+            total_matches: 2,
+        }
+    }
+
+    // Writes a legacy account: discriminator, the layout's fields, then stale
+    // non-zero bytes up to the layout's account length
+    fn legacy_account(version: u8, tail: &[u8]) -> Vec<u8> {
+        let mut data = UserProfile::DISCRIMINATOR.to_vec();
+        v1_profile().serialize(&mut data).unwrap();
+        data.extend_from_slice(tail);
+        data.resize(profile_account_len(version), 0xAB);
+        data
+    }
+
+    fn assert_v1_data_preserved(profile: &UserProfile) {
+        let v1 = v1_profile();
+        assert_eq!(profile.bump, v1.bump);
+        assert_eq!(profile.created_at, v1.created_at);
+        assert_eq!(profile.username, v1.username);
+        assert_eq!(profile.age, v1.age);
+        assert_eq!(profile.location_city, v1.location_city);
+        assert_eq!(profile.encrypted_private_data, v1.encrypted_private_data);
+        assert_eq!(profile.encrypted_preferences, v1.encrypted_preferences);
+        assert_eq!(profile.total_likes_received, v1.total_likes_received);
+        assert_eq!(profile.total_matches, v1.total_matches);
+    }
+
+    #[test]
+    fn v1_profile_defaults_every_appended_field() {
+        let (version, profile) = decode_profile(&legacy_account(1, &[])).unwrap();
+
+        assert_eq!(version, 1);
+        assert_v1_data_preserved(&profile);
+        assert!(profile.icebreaker_answer.is_empty());
+        assert!(!profile.matching_paused);
+        assert_eq!(profile.boost_until, 0);
+        assert_eq!(profile.privacy_level, PRIVACY_LEVEL_PUBLIC);
+        assert_eq!(profile.like_policy, LIKE_POLICY_ANYONE);
+        assert_eq!((profile.like_age_min, profile.like_age_max), (0, 0));
+        assert_eq!(profile.archived_match_records, 0);
+        assert_eq!((profile.gender, profile.orientation), (GENDER_UNSPECIFIED, ORIENTATION_UNSPECIFIED));
+        assert!(profile.bio.is_empty());
+        assert_eq!(profile.last_seen, v1_profile().last_updated);
+        assert!(!profile.is_verified);
+    }
+
+    #[test]
+    fn v4_profile_keeps_its_later_fields_and_defaults_the_rest() {
+        let mut tail = Vec::new();
+        vec![9u8; 80].serialize(&mut tail).unwrap(); // icebreaker_answer
+        true.serialize(&mut tail).unwrap(); // matching_paused
+        1_800_000_000i64.serialize(&mut tail).unwrap(); // boost_until
+
+        let (version, profile) = decode_profile(&legacy_account(4, &tail)).unwrap();
+
+        assert_eq!(version, 4);
+        assert_v1_data_preserved(&profile);
+        assert_eq!(profile.icebreaker_answer, vec![9u8; 80]);
+        assert!(profile.matching_paused);
+        assert_eq!(profile.boost_until, 1_800_000_000);
+        assert_eq!(profile.privacy_level, PRIVACY_LEVEL_PUBLIC);
+        assert_eq!(profile.like_policy, LIKE_POLICY_ANYONE);
+    }
+
+    #[test]
+    fn v7_profile_skips_its_layout_version_and_archives_nothing() {
+        let mut tail = Vec::new();
+        Vec::<u8>::new().serialize(&mut tail).unwrap(); // icebreaker_answer
+        false.serialize(&mut tail).unwrap(); // matching_paused
+        0i64.serialize(&mut tail).unwrap(); // boost_until
+        PRIVACY_LEVEL_PUBLIC.serialize(&mut tail).unwrap(); // privacy_level
+        (LIKE_POLICY_ANYONE, 25u8, 35u8).serialize(&mut tail).unwrap(); // like policy
+        7u8.serialize(&mut tail).unwrap(); // layout_version
+
+        let (version, profile) = decode_profile(&legacy_account(7, &tail)).unwrap();
+
+        assert_eq!(version, 7);
+        assert_v1_data_preserved(&profile);
+        assert_eq!((profile.like_age_min, profile.like_age_max), (25, 35));
+        assert_eq!(profile.archived_match_records, 0);
+    }
+
+    #[test]
+    fn migrated_profile_round_trips_at_the_current_layout() {
+        let (_, mut profile) = decode_profile(&legacy_account(1, &[])).unwrap();
+        profile.layout_version = PROFILE_LAYOUT_VERSION;
+
+        let mut data = vec![0u8; profile_account_len(PROFILE_LAYOUT_VERSION)];
+        profile.try_serialize(&mut &mut data[..]).unwrap();
+        let (version, migrated) = decode_profile(&data).unwrap();
+
+        assert_eq!(version, PROFILE_LAYOUT_VERSION);
+        assert_v1_data_preserved(&migrated);
+    }
+
+    #[test]
+    fn compact_profile_decodes_at_the_current_layout_and_after_growing() {
+        let (_, mut profile) = decode_profile(&legacy_account(1, &[])).unwrap();
+        profile.layout_version = PROFILE_LAYOUT_VERSION;
+
+        let mut data = vec![0u8; 8 + profile.required_space()];
+        profile.try_serialize(&mut &mut data[..]).unwrap();
+        assert!(data.len() < profile_account_len(PROFILE_LAYOUT_VERSION));
+        let (version, compact) = decode_profile(&data).unwrap();
+        assert_eq!(version, PROFILE_LAYOUT_VERSION);
+        assert_v1_data_preserved(&compact);
+
+        // `grow_profile` only appends zeroed space after the serialized fields
+        data.resize(data.len() + 300, 0);
+        let (version, grown) = decode_profile(&data).unwrap();
+        assert_eq!(version, PROFILE_LAYOUT_VERSION);
+        assert_v1_data_preserved(&grown);
+    }
+
+    #[test]
+    fn legacy_slack_that_looks_like_a_layout_version_is_ignored() {
+        // Zeroed slack decodes as empty appended fields, up to a stray byte
+        // where a self-describing layout keeps layout_version
+        let mut v1_fields = UserProfile::DISCRIMINATOR.to_vec();
+        v1_profile().serialize(&mut v1_fields).unwrap();
+        let layout_version_offset = v1_fields.len() + 4 + 1 + 8 + 1 + 3;
+        for stray in SELF_DESCRIBING_LAYOUT..=PROFILE_LAYOUT_VERSION {
+            let mut data = v1_fields.clone();
+            data.resize(profile_account_len(1), 0);
+            data[layout_version_offset] = stray;
+
+            let (version, profile) = decode_profile(&data).unwrap();
+            assert_eq!(version, 1);
+            assert_eq!(profile.layout_version, 1);
+            assert_v1_data_preserved(&profile);
+        }
+    }
+
+    #[test]
+    fn self_describing_lengths_never_look_legacy() {
+        for version in 1..SELF_DESCRIBING_LAYOUT {
+            let len = self_describing_len(profile_account_len(version));
+            assert!(len > profile_account_len(version));
+            assert!(legacy_version(len).is_none());
+        }
+        let current = profile_account_len(PROFILE_LAYOUT_VERSION);
+        assert_eq!(self_describing_len(current), current);
+    }
+
+    #[test]
+    fn unknown_lengths_are_rejected() {
+        let mut data = legacy_account(1, &[]);
+        data.push(0);
+        assert!(decode_profile(&data).is_err());
+    }
+}
@@ -0,0 +1,646 @@
+// ============================================================================
+// MOCK MPC BACKEND (feature = "mock-mpc")
+// ============================================================================
+//
+// Deterministic plaintext stand-ins for the circuits in `encrypted-ixs`, used
+// so the init -> like -> check flow can run in `solana-program-test` without
+// an Arcium cluster. Instead of queueing a computation, each instruction runs
+// the matching function below and applies its result immediately through the
+// same handlers the real callbacks use.
+//
+// In mock mode a "ciphertext" is simply the plaintext value encoded
+// little-endian into the first 16 bytes of its 32-byte slot. Clients (tests)
+// must encode every encrypted field with `encode_value` accordingly, and read
+// re-encrypted outputs (events, account state) back with `decode_value`.
+// Outputs carry the input nonce plus one, as a real re-encryption would
+// carry a fresh nonce.
+//
+// Only the queueing is mocked: the instruction contexts are unchanged, so a
+// test still passes the MXE, mempool, executing pool, computation, comp def,
+// cluster, fee pool and clock accounts at their usual addresses. None of them
+// is read in mock mode and they need not be initialized; the program-test
+// fixture only has to supply accounts that satisfy each context's address
+// and owner constraints. The mirrors themselves are unit-tested below; run
+// them with `cargo test --features mock-mpc`. The TypeScript suite's mock
+// path tests are skipped unless run as
+// `MOCK_MPC=1 anchor test -- --features mock-mpc`.
+//
+// NEVER enable this feature for a deployed program: it provides no privacy.
+
+use crate::scoring;
+
+/// Encodes a plaintext value into a mock ciphertext slot
+pub fn encode_value(value: u128) -> [u8; 32] {
+    let mut slot = [0u8; 32];
+    slot[..16].copy_from_slice(&value.to_le_bytes());
+    slot
+}
+
+/// Decodes a mock ciphertext slot back into its plaintext value
+pub fn decode_value(slot: &[u8; 32]) -> u128 {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&slot[..16]);
+    u128::from_le_bytes(bytes)
+}
+
+/// Mirror of the `MatchSession` circuit struct
+#[derive(Clone, Copy)]
+pub struct MatchSessionState {
+    pub user_a_id: u64,
+    pub user_b_id: u64,
+    pub user_a_liked: bool,
+    pub user_b_liked: bool,
+    pub session_created_at: u64,
+    pub last_updated: u64,
+}
+
+impl MatchSessionState {
+    pub fn decode(ciphertexts: &[[u8; 32]; 6]) -> Self {
+        Self {
+            user_a_id: decode_value(&ciphertexts[0]) as u64,
+            user_b_id: decode_value(&ciphertexts[1]) as u64,
+            user_a_liked: decode_value(&ciphertexts[2]) != 0,
+            user_b_liked: decode_value(&ciphertexts[3]) != 0,
+            session_created_at: decode_value(&ciphertexts[4]) as u64,
+            last_updated: decode_value(&ciphertexts[5]) as u64,
+        }
+    }
+
+    pub fn encode(&self) -> [[u8; 32]; 6] {
+        [
+            encode_value(self.user_a_id as u128),
+            encode_value(self.user_b_id as u128),
+            encode_value(self.user_a_liked as u128),
+            encode_value(self.user_b_liked as u128),
+            encode_value(self.session_created_at as u128),
+            encode_value(self.last_updated as u128),
+        ]
+    }
+}
+
+/// Mirror of the `LikeIntents` circuit struct
+#[derive(Clone, Copy, Default)]
+pub struct LikeIntentsState {
+    pub user_a_intent: u8,
+    pub user_b_intent: u8,
+}
+
+impl LikeIntentsState {
+    pub fn decode(ciphertexts: &[[u8; 32]; 2]) -> Self {
+        Self {
+            user_a_intent: decode_value(&ciphertexts[0]) as u8,
+            user_b_intent: decode_value(&ciphertexts[1]) as u8,
+        }
+    }
+
+    pub fn encode(&self) -> [[u8; 32]; 2] {
+        [
+            encode_value(self.user_a_intent as u128),
+            encode_value(self.user_b_intent as u128),
+        ]
+    }
+}
+
+/// Mirror of the `UserLikeAction` circuit struct
+pub struct LikeAction {
+    pub user_id: u64,
+    pub target_id: u64,
+    pub like_action: bool,
+    pub timestamp: u64,
+    pub intent_strength: u8,
+}
+
+/// Mirror of the `init_match_session` circuit
+pub fn init_match_session(user_a_id: u64, user_b_id: u64, current_timestamp: u64) -> (MatchSessionState, LikeIntentsState) {
+    let session = MatchSessionState {
+        user_a_id,
+        user_b_id,
+        user_a_liked: false,
+        user_b_liked: false,
+        session_created_at: current_timestamp,
+        last_updated: current_timestamp,
+    };
+    (session, LikeIntentsState::default())
+}
+
+/// Mirror of the `submit_like` circuit: returns (session, intents, status_flag)
+pub fn submit_like(
+    like_action: &LikeAction,
+    like_weight: u8,
+    mut session: MatchSessionState,
+    mut intents: LikeIntentsState,
+) -> (MatchSessionState, LikeIntentsState, u8) {
+    let distinct_ids = like_action.user_id != like_action.target_id;
+    let weight = like_weight.max(1) as u16;
+    let intent = if like_action.like_action {
+        (like_action.intent_strength.min(100) as u16 * weight).min(100) as u8
+    } else {
+        0
+    };
+    let liked_status = if like_action.like_action && like_weight > 1 { 4 } else { 1 };
+
+    let mut status_flag = 0u8;
+    if !distinct_ids {
+        status_flag = 0;
+    } else if like_action.user_id == session.user_a_id &&
+        like_action.target_id == session.user_b_id &&
+        !session.user_a_liked {
+        session.user_a_liked = like_action.like_action;
+        session.last_updated = like_action.timestamp;
+        intents.user_a_intent = intent;
+        status_flag = if session.user_a_liked && session.user_b_liked { 2 } else { liked_status };
+    } else if like_action.user_id == session.user_b_id &&
+        like_action.target_id == session.user_a_id &&
+        !session.user_b_liked {
+        session.user_b_liked = like_action.like_action;
+        session.last_updated = like_action.timestamp;
+        intents.user_b_intent = intent;
+        status_flag = if session.user_a_liked && session.user_b_liked { 2 } else { liked_status };
+    }
+
+    (session, intents, status_flag)
+}
+
+/// Mirror of the `withdraw_like` circuit: returns (session, intents, status_flag)
+pub fn withdraw_like(
+    user_id: u64,
+    timestamp: u64,
+    mut session: MatchSessionState,
+    mut intents: LikeIntentsState,
+) -> (MatchSessionState, LikeIntentsState, u8) {
+    let mutual = session.user_a_liked && session.user_b_liked;
+
+    let mut status_flag = 0u8;
+    if mutual {
+        status_flag = 0;
+    } else if user_id == session.user_a_id && session.user_a_liked {
+        session.user_a_liked = false;
+        session.last_updated = timestamp;
+        intents.user_a_intent = 0;
+        status_flag = 1;
+    } else if user_id == session.user_b_id && session.user_b_liked {
+        session.user_b_liked = false;
+        session.last_updated = timestamp;
+        intents.user_b_intent = 0;
+        status_flag = 1;
+    }
+
+    (session, intents, status_flag)
+}
+
+/// Mirror of the `submit_pass` circuit: returns the status flag
+pub fn submit_pass(user_id: u64, target_id: u64, session: &MatchSessionState) -> u8 {
+    let is_participant = (user_id == session.user_a_id && target_id == session.user_b_id) ||
+        (user_id == session.user_b_id && target_id == session.user_a_id);
+    let mutual = session.user_a_liked && session.user_b_liked;
+    if is_participant && !mutual { 5 } else { 0 }
+}
+
+/// The dealbreaker-relevant fields of the `MatchingData` circuit struct, plus
+/// the whole struct in the scoring mirror's types
+pub struct MatchingData {
+    pub pet_dealbreaker: bool,
+    pub relationship_status_preference: u8,
+    pub religion_preference: u8,
+    pub diet_preference: u8,
+    pub diet_dealbreaker: bool,
+    pub preferred_genders: u8,
+    pub age: u8,
+    pub pet_status: u8,
+    pub relationship_status: u8,
+    pub religion: u8,
+    pub religion_importance: u8,
+    pub diet: u8,
+    pub gender: u8,
+    pub prefs: scoring::Preferences,
+    pub profile: scoring::Profile,
+}
+
+/// Ciphertexts in one encrypted `UserPreferences`
+const PREFERENCES_CIPHERTEXTS: usize = 22;
+
+/// Decodes a `UserPreferences` by circuit field order
+fn decode_preferences(ciphertexts: &[[u8; 32]]) -> scoring::Preferences {
+    let field = |i: usize| decode_value(&ciphertexts[i]) as u8;
+    let wide_field = |i: usize| decode_value(&ciphertexts[i]) as u16;
+    scoring::Preferences {
+        preferred_age_min: field(0),
+        preferred_age_max: field(1),
+        interests_count: field(2),
+        location_preference: field(3),
+        relationship_type: field(4),
+        pet_preference: field(5),
+        pet_dealbreaker: field(6) != 0,
+        budget_tier_min: field(7),
+        budget_tier_max: field(8),
+        relationship_status_preference: field(9),
+        religion_preference: field(10),
+        diet_preference: field(11),
+        diet_dealbreaker: field(12) != 0,
+        max_age_gap: field(13),
+        children_preference: field(14),
+        min_youngest_child_bucket: field(15),
+        open_to_long_distance: field(16) != 0,
+        preferred_height_min: field(17),
+        preferred_height_max: field(18),
+        body_type_preference: field(19),
+        preferred_genders: field(20),
+        preferred_distance_km: wide_field(21),
+    }
+}
+
+/// Decodes a `UserProfile` by circuit field order
+fn decode_profile(ciphertexts: &[[u8; 32]]) -> scoring::Profile {
+    let field = |i: usize| decode_value(&ciphertexts[i]) as u8;
+    let wide_field = |i: usize| decode_value(&ciphertexts[i]) as u16;
+    scoring::Profile {
+        age: field(0),
+        interests_count: field(1),
+        location_score: field(2),
+        relationship_type: field(3),
+        pet_status: field(4),
+        zodiac: field(5),
+        budget_tier: field(6),
+        personality_type: field(7),
+        relationship_status: field(8),
+        religion: field(9),
+        religion_importance: field(10),
+        diet: field(11),
+        num_children: field(12),
+        youngest_child_age_bucket: field(13),
+        height_cm: field(14),
+        body_type: field(15),
+        gender: field(16),
+        grid_x: wide_field(17),
+        grid_y: wide_field(18),
+    }
+}
+
+impl MatchingData {
+    /// Decodes by circuit field order: the preferences, then the profile
+    pub fn decode(ciphertexts: &[[u8; 32]; crate::MATCHING_DATA_CIPHERTEXTS]) -> Self {
+        let prefs = decode_preferences(&ciphertexts[..PREFERENCES_CIPHERTEXTS]);
+        let profile = decode_profile(&ciphertexts[PREFERENCES_CIPHERTEXTS..]);
+        Self {
+            pet_dealbreaker: prefs.pet_dealbreaker,
+            relationship_status_preference: prefs.relationship_status_preference,
+            religion_preference: prefs.religion_preference,
+            diet_preference: prefs.diet_preference,
+            diet_dealbreaker: prefs.diet_dealbreaker,
+            preferred_genders: prefs.preferred_genders,
+            age: profile.age,
+            pet_status: profile.pet_status,
+            relationship_status: profile.relationship_status,
+            religion: profile.religion,
+            religion_importance: profile.religion_importance,
+            diet: profile.diet,
+            gender: profile.gender,
+            prefs,
+            profile,
+        }
+    }
+}
+
+fn mask_accepts(accepted_mask: u8, code: u8) -> bool {
+    accepted_mask == 0 || (code < 8 && accepted_mask & (1u8 << code) != 0)
+}
+
+/// Mirror of the circuit's `hard_filter_conflict`
+fn hard_filter_conflict(chooser: &MatchingData, candidate: &MatchingData) -> bool {
+    let pet_conflict = chooser.pet_dealbreaker && chooser.pet_status == 2 && candidate.pet_status == 1;
+    let religion_dealbreaker = chooser.religion_preference != 0 &&
+        candidate.religion != chooser.religion_preference &&
+        chooser.religion_importance >= 3;
+    let diet_dealbreaker = chooser.diet_dealbreaker && !mask_accepts(chooser.diet_preference, candidate.diet);
+
+    pet_conflict ||
+        religion_dealbreaker ||
+        diet_dealbreaker ||
+        candidate.age < 18 ||
+        !mask_accepts(chooser.relationship_status_preference, candidate.relationship_status) ||
+        !mask_accepts(chooser.preferred_genders, candidate.gender)
+}
+
+/// Mirror of the `check_mutual_match` circuit: returns (is_mutual_match,
+/// session_status, match_timestamp, confidence, compatibility_score)
+pub fn check_mutual_match(
+    session: &MatchSessionState,
+    intents: &LikeIntentsState,
+    user_a: &MatchingData,
+    user_b: &MatchingData,
+    current_timestamp: u64,
+) -> (bool, u8, u64, u8, u8) {
+    let both_liked = session.user_a_liked && session.user_b_liked;
+    let vetoed = hard_filter_conflict(user_a, user_b) || hard_filter_conflict(user_b, user_a);
+    let is_mutual = both_liked && !vetoed;
+
+    let status = if is_mutual {
+        1u8
+    } else if both_liked {
+        3u8
+    } else if session.user_a_liked || session.user_b_liked {
+        0u8
+    } else {
+        2u8
+    };
+
+    let match_timestamp = if is_mutual { current_timestamp } else { 0u64 };
+    let mean_intent = (intents.user_a_intent as u16 + intents.user_b_intent as u16) / 2;
+    let confidence = if is_mutual { mean_intent as u8 } else { 0 };
+    let (pair_score, _) = scoring::score_compatibility(&user_a.prefs, &user_a.profile, &user_b.prefs, &user_b.profile, 0);
+    let compatibility_score = if is_mutual { pair_score } else { 0 };
+
+    (is_mutual, status, match_timestamp, confidence, compatibility_score)
+}
+
+/// Encodes a circuit struct's fields, in order, as mock ciphertexts
+pub fn encode_fields<const N: usize>(values: [u128; N]) -> [[u8; 32]; N] {
+    values.map(encode_value)
+}
+
+/// Decodes a reputation aggregate; `None` until its first rating lands
+pub fn decode_reputation(reputation: &crate::Reputation) -> Option<scoring::Reputation> {
+    reputation.initialized.then(|| scoring::Reputation {
+        rating_sum: decode_value(&reputation.encrypted_reputation[0]) as u32,
+        rating_count: decode_value(&reputation.encrypted_reputation[1]) as u32,
+    })
+}
+
+pub fn encode_reputation(reputation: &scoring::Reputation) -> [[u8; 32]; 2] {
+    encode_fields([reputation.rating_sum as u128, reputation.rating_count as u128])
+}
+
+pub fn encode_breakdown(breakdown: &scoring::ScoreBreakdown) -> [[u8; 32]; 5] {
+    encode_fields([
+        breakdown.age as u128,
+        breakdown.interests as u128,
+        breakdown.location as u128,
+        breakdown.relationship as u128,
+        breakdown.physical as u128,
+    ])
+}
+
+/// Mirror of the `calculate_compatibility` circuit: returns (score,
+/// age_gate_passed, breakdown), the breakdown going to both users
+pub fn calculate_compatibility(
+    user_a: &MatchingData,
+    user_b: &MatchingData,
+    user_a_reputation: Option<&scoring::Reputation>,
+    user_b_reputation: Option<&scoring::Reputation>,
+    scoring_flags: u8,
+) -> (u8, bool, scoring::ScoreBreakdown) {
+    let (score, age_gate_passed) =
+        scoring::score_compatibility(&user_a.prefs, &user_a.profile, &user_b.prefs, &user_b.profile, scoring_flags);
+    let breakdown =
+        scoring::score_breakdown(&user_a.prefs, &user_a.profile, &user_b.prefs, &user_b.profile, scoring_flags);
+    let score = scoring::apply_reputation(score, user_a_reputation, user_b_reputation);
+    (score, age_gate_passed, breakdown)
+}
+
+/// Mirror of the `calculate_compatibility_batch` circuit over the requester's
+/// and each candidate's staged data: returns (scores, age_gate_passed)
+pub fn calculate_compatibility_batch(
+    requester: &MatchingData,
+    candidates: &[MatchingData],
+    scoring_flags: u8,
+) -> ([u8; crate::COMPATIBILITY_BATCH_SIZE], bool) {
+    let age_gate_passed = requester.age >= 18;
+
+    let mut scores = [0u8; crate::COMPATIBILITY_BATCH_SIZE];
+    if age_gate_passed {
+        for (score, candidate) in scores.iter_mut().zip(candidates) {
+            *score = scoring::score_candidate(
+                &requester.prefs,
+                &requester.profile,
+                &candidate.prefs,
+                &candidate.profile,
+                scoring_flags,
+            );
+        }
+    }
+    (scores, age_gate_passed)
+}
+
+/// Mirror of the `DisclosureInput` circuit struct
+pub struct DisclosureInput {
+    pub consent: bool,
+    pub exact_age: u8,
+    pub income_bracket: u8,
+}
+
+impl DisclosureInput {
+    pub fn decode(ciphertexts: &[[u8; 32]; 3]) -> Self {
+        Self {
+            consent: decode_value(&ciphertexts[0]) != 0,
+            exact_age: decode_value(&ciphertexts[1]) as u8,
+            income_bracket: decode_value(&ciphertexts[2]) as u8,
+        }
+    }
+}
+
+/// Mirror of the `DisclosedProfile` circuit struct
+#[derive(Default)]
+pub struct DisclosedProfile {
+    pub exact_age: u8,
+    pub income_bracket: u8,
+}
+
+impl DisclosedProfile {
+    pub fn encode(&self) -> [[u8; 32]; 2] {
+        encode_fields([self.exact_age as u128, self.income_bracket as u128])
+    }
+}
+
+/// Mirror of the `reveal_matched_data` circuit: returns (for_user_a,
+/// for_user_b, both_consented)
+pub fn reveal_matched_data(
+    user_a_input: &DisclosureInput,
+    user_b_input: &DisclosureInput,
+    session: &MatchSessionState,
+) -> (DisclosedProfile, DisclosedProfile, bool) {
+    let is_mutual = session.user_a_liked && session.user_b_liked;
+    let both_consented = is_mutual && user_a_input.consent && user_b_input.consent;
+    if !both_consented {
+        return (DisclosedProfile::default(), DisclosedProfile::default(), false);
+    }
+
+    let for_user_a = DisclosedProfile {
+        exact_age: user_b_input.exact_age,
+        income_bracket: user_b_input.income_bracket,
+    };
+    let for_user_b = DisclosedProfile {
+        exact_age: user_a_input.exact_age,
+        income_bracket: user_a_input.income_bracket,
+    };
+    (for_user_a, for_user_b, true)
+}
+
+/// Mirror of the `IcebreakerAnswer` circuit struct
+#[derive(Clone, Copy, Default)]
+pub struct IcebreakerAnswer {
+    pub chunks: [u128; 4],
+}
+
+impl IcebreakerAnswer {
+    pub fn decode(ciphertexts: &[[u8; 32]; 4]) -> Self {
+        Self { chunks: ciphertexts.map(|c| decode_value(&c)) }
+    }
+
+    pub fn encode(&self) -> [[u8; 32]; 4] {
+        encode_fields(self.chunks)
+    }
+}
+
+/// Mirror of the `reveal_icebreakers` circuit: returns (for_user_a,
+/// for_user_b, is_mutual_match)
+pub fn reveal_icebreakers(
+    user_a_answer: IcebreakerAnswer,
+    user_b_answer: IcebreakerAnswer,
+    session: &MatchSessionState,
+) -> (IcebreakerAnswer, IcebreakerAnswer, bool) {
+    let is_mutual = session.user_a_liked && session.user_b_liked;
+    if is_mutual {
+        (user_b_answer, user_a_answer, true)
+    } else {
+        (IcebreakerAnswer::default(), IcebreakerAnswer::default(), false)
+    }
+}
+
+/// Mirror of the `submit_feedback` circuit: returns (reputation, accepted).
+/// `stored` is `None` for a first rating, which starts from zero.
+pub fn submit_feedback(rating: u8, stored: Option<scoring::Reputation>) -> (scoring::Reputation, bool) {
+    let mut reputation = stored.unwrap_or_default();
+    let accepted = (1..=5).contains(&rating);
+    if accepted {
+        reputation.rating_sum += rating as u32;
+        reputation.rating_count += 1;
+    }
+    (reputation, accepted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_A: u64 = 1;
+    const USER_B: u64 = 2;
+
+    // Staged MatchingData accepting ages 18-99, everything else left open
+    fn matching_ciphertexts(age: u8) -> [[u8; 32]; crate::MATCHING_DATA_CIPHERTEXTS] {
+        let mut ciphertexts = [encode_value(0); crate::MATCHING_DATA_CIPHERTEXTS];
+        ciphertexts[0] = encode_value(18);
+        ciphertexts[1] = encode_value(99);
+        ciphertexts[PREFERENCES_CIPHERTEXTS] = encode_value(age as u128);
+        ciphertexts
+    }
+
+    fn like(user_id: u64, target_id: u64) -> LikeAction {
+        LikeAction {
+            user_id,
+            target_id,
+            like_action: true,
+            timestamp: 1_700_000_100,
+            intent_strength: 80,
+        }
+    }
+
+    // Runs a like through the session the way the program does: decode the
+    // stored ciphertexts, apply the circuit, store the re-encoded state
+    fn submit(action: &LikeAction, session: [[u8; 32]; 6], intents: [[u8; 32]; 2]) -> ([[u8; 32]; 6], [[u8; 32]; 2], u8) {
+        let (session, intents, status_flag) =
+            submit_like(action, 1, MatchSessionState::decode(&session), LikeIntentsState::decode(&intents));
+        (session.encode(), intents.encode(), status_flag)
+    }
+
+    fn mutual_session() -> MatchSessionState {
+        let (session, intents) = init_match_session(USER_A, USER_B, 1_700_000_000);
+        let (session, intents, _) = submit(&like(USER_A, USER_B), session.encode(), intents.encode());
+        let (session, _, _) = submit(&like(USER_B, USER_A), session, intents);
+        MatchSessionState::decode(&session)
+    }
+
+    #[test]
+    fn likes_from_both_sides_check_as_a_mutual_match() {
+        let (session, intents) = init_match_session(USER_A, USER_B, 1_700_000_000);
+        let (session, intents, first) = submit(&like(USER_A, USER_B), session.encode(), intents.encode());
+        assert_eq!(first, 1);
+        let (session, intents, second) = submit(&like(USER_B, USER_A), session, intents);
+        assert_eq!(second, 2);
+
+        let user_a = MatchingData::decode(&matching_ciphertexts(30));
+        let user_b = MatchingData::decode(&matching_ciphertexts(28));
+        let (is_mutual, status, matched_at, confidence, score) = check_mutual_match(
+            &MatchSessionState::decode(&session),
+            &LikeIntentsState::decode(&intents),
+            &user_a,
+            &user_b,
+            1_700_000_200,
+        );
+        assert!(is_mutual);
+        assert_eq!((status, matched_at, confidence), (1, 1_700_000_200, 80));
+        assert!(score > 0);
+
+        let underage = MatchingData::decode(&matching_ciphertexts(17));
+        let (is_mutual, status, ..) = check_mutual_match(
+            &MatchSessionState::decode(&session),
+            &LikeIntentsState::decode(&intents),
+            &user_a,
+            &underage,
+            1_700_000_200,
+        );
+        assert!(!is_mutual);
+        assert_eq!(status, 3);
+    }
+
+    #[test]
+    fn compatibility_mirrors_score_the_staged_data() {
+        let user_a = MatchingData::decode(&matching_ciphertexts(30));
+        let user_b = MatchingData::decode(&matching_ciphertexts(28));
+        let (score, age_gate_passed, breakdown) = calculate_compatibility(&user_a, &user_b, None, None, 0);
+        assert!(age_gate_passed);
+        assert_eq!(breakdown.age, 30);
+        assert_eq!(decode_value(&encode_breakdown(&breakdown)[0]), 30);
+        assert_eq!(score, scoring::score_compatibility(&user_a.prefs, &user_a.profile, &user_b.prefs, &user_b.profile, 0).0);
+
+        let candidates = [user_b, MatchingData::decode(&matching_ciphertexts(28))];
+        let (scores, age_gate_passed) = calculate_compatibility_batch(&user_a, &candidates, 0);
+        assert!(age_gate_passed);
+        assert!(scores[0] > 0 && scores[0] == scores[1]);
+        assert_eq!(scores[2], 0, "slots past the candidates stay zero");
+    }
+
+    #[test]
+    fn disclosures_and_icebreakers_cross_only_after_a_match() {
+        let consent = |exact_age: u128| DisclosureInput::decode(&encode_fields([1, exact_age, 3]));
+        let (for_a, for_b, both_consented) = reveal_matched_data(&consent(30), &consent(28), &mutual_session());
+        assert!(both_consented);
+        assert_eq!((for_a.exact_age, for_b.exact_age), (28, 30));
+
+        let (session, _) = init_match_session(USER_A, USER_B, 1_700_000_000);
+        let (for_a, _, both_consented) = reveal_matched_data(&consent(30), &consent(28), &session);
+        assert!(!both_consented);
+        assert_eq!(for_a.exact_age, 0);
+
+        let answer = |chunk: u128| IcebreakerAnswer::decode(&encode_fields([chunk; 4]));
+        let (for_a, for_b, is_mutual) = reveal_icebreakers(answer(7), answer(9), &mutual_session());
+        assert!(is_mutual);
+        assert_eq!((for_a.chunks[0], for_b.chunks[0]), (9, 7));
+        let (for_a, _, is_mutual) = reveal_icebreakers(answer(7), answer(9), &session);
+        assert!(!is_mutual);
+        assert_eq!(for_a.chunks, [0; 4]);
+    }
+
+    #[test]
+    fn feedback_aggregates_in_range_ratings_only() {
+        let (first, accepted) = submit_feedback(4, None);
+        assert!(accepted);
+        assert_eq!((first.rating_sum, first.rating_count), (4, 1));
+
+        let (second, accepted) = submit_feedback(6, Some(first.clone()));
+        assert!(!accepted);
+        assert_eq!((second.rating_sum, second.rating_count), (4, 1));
+
+        let (third, _) = submit_feedback(5, Some(second));
+        assert_eq!(encode_reputation(&third), encode_fields([9, 2]));
+    }
+}
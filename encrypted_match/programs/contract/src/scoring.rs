@@ -0,0 +1,829 @@
+// ============================================================================
+// PLAINTEXT COMPATIBILITY SCORING
+// ============================================================================
+//
+// Plaintext mirror of the `calculate_compatibility` circuit in `encrypted-ixs`.
+// The circuit cannot be run off-cluster, so the weights, caps and dealbreakers
+// are kept here as well and unit-tested against plaintext inputs. Any change
+// to the circuit's scoring must be made in both places.
+//
+// Lookups index the tables directly: constant-time scans only matter inside
+// MPC. Out-of-range codes fall back exactly as the circuit's scans do.
+
+/// Mirror of the `UserPreferences` circuit struct
+#[derive(Clone, Debug, Default)]
+pub struct Preferences {
+    pub preferred_age_min: u8,
+    pub preferred_age_max: u8,
+    pub interests_count: u8,
+    pub location_preference: u8,
+    pub relationship_type: u8,
+    pub pet_preference: u8,
+    pub pet_dealbreaker: bool,
+    pub budget_tier_min: u8,
+    pub budget_tier_max: u8,
+    pub relationship_status_preference: u8,
+    pub religion_preference: u8,
+    pub diet_preference: u8,
+    pub diet_dealbreaker: bool,
+    pub max_age_gap: u8,
+    pub children_preference: u8,
+    pub min_youngest_child_bucket: u8,
+    pub open_to_long_distance: bool,
+    pub preferred_height_min: u8,
+    pub preferred_height_max: u8,
+    pub body_type_preference: u8,
+    pub preferred_genders: u8,
+    pub preferred_distance_km: u16,
+}
+
+/// Mirror of the `UserProfile` circuit struct
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub age: u8,
+    pub interests_count: u8,
+    pub location_score: u8,
+    pub relationship_type: u8,
+    pub pet_status: u8,
+    pub zodiac: u8,
+    pub budget_tier: u8,
+    pub personality_type: u8,
+    pub relationship_status: u8,
+    pub religion: u8,
+    pub religion_importance: u8,
+    pub diet: u8,
+    pub num_children: u8,
+    pub youngest_child_age_bucket: u8,
+    pub height_cm: u8,
+    pub body_type: u8,
+    pub gender: u8,
+    pub grid_x: u16,
+    pub grid_y: u16,
+}
+
+/// Mirror of the `ScoreBreakdown` circuit struct, all zero for a vetoed pair
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScoreBreakdown {
+    pub age: u8,
+    pub interests: u8,
+    pub location: u8,
+    pub relationship: u8,
+    pub physical: u8,
+}
+
+/// Mirror of the `Reputation` circuit struct (aggregated star ratings)
+#[derive(Clone, Debug, Default)]
+pub struct Reputation {
+    pub rating_sum: u32,
+    pub rating_count: u32,
+}
+
+const MIN_MATCHING_AGE: u8 = 18;
+const MAX_INTERESTS: u8 = 10;
+
+const PET_STATUS_OWNER: u8 = 1;
+const PET_STATUS_ALLERGIC: u8 = 2;
+const PET_PREF_LOVES_PETS: u8 = 1;
+const PET_PREF_NO_PETS: u8 = 2;
+
+const RELIGION_NO_PREFERENCE: u8 = 0;
+const RELIGION_IMPORTANCE_DEALBREAKER: u8 = 3;
+
+const CHILDREN_PREF_NONE: u8 = 1;
+const CHILDREN_PREF_OPEN: u8 = 2;
+
+const SCORING_FLAG_ZODIAC: u8 = 1 << 0;
+const SCORING_FLAG_PERSONALITY: u8 = 1 << 1;
+
+const ZODIAC_ELEMENTS: [u8; 12] = [0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3];
+
+const ZODIAC_ELEMENT_COMPATIBILITY: [[u8; 4]; 4] = [
+    [3, 0, 2, 0],
+    [0, 3, 0, 2],
+    [2, 0, 3, 0],
+    [0, 2, 0, 3],
+];
+
+const PERSONALITY_COMPATIBILITY: [[u8; 16]; 16] = [
+    [3, 4, 4, 5, 2, 3, 3, 4, 1, 0, 2, 1, 2, 1, 3, 2], // INTJ
+    [4, 3, 5, 4, 3, 2, 4, 3, 2, 1, 3, 2, 1, 0, 2, 1], // INTP
+    [4, 5, 3, 4, 3, 4, 2, 3, 2, 1, 1, 0, 3, 2, 2, 1], // ENTJ
+    [5, 4, 4, 3, 4, 3, 3, 2, 3, 2, 2, 1, 2, 1, 1, 0], // ENTP
+    [2, 3, 3, 4, 3, 4, 4, 5, 0, 1, 1, 2, 1, 2, 2, 3], // INFJ
+    [3, 2, 4, 3, 4, 3, 5, 4, 1, 2, 2, 3, 0, 1, 1, 2], // INFP
+    [3, 4, 2, 3, 4, 5, 3, 4, 1, 2, 0, 1, 2, 3, 1, 2], // ENFJ
+    [4, 3, 3, 2, 5, 4, 4, 3, 2, 3, 1, 2, 1, 2, 0, 1], // ENFP
+    [1, 2, 2, 3, 0, 1, 1, 2, 3, 2, 4, 3, 4, 3, 5, 4], // ISTJ
+    [0, 1, 1, 2, 1, 2, 2, 3, 2, 3, 3, 4, 3, 4, 4, 5], // ISFJ
+    [2, 3, 1, 2, 1, 2, 0, 1, 4, 3, 3, 2, 5, 4, 4, 3], // ESTJ
+    [1, 2, 0, 1, 2, 3, 1, 2, 3, 4, 2, 3, 4, 5, 3, 4], // ESFJ
+    [2, 1, 3, 2, 1, 0, 2, 1, 4, 3, 5, 4, 3, 2, 4, 3], // ISTP
+    [1, 0, 2, 1, 2, 1, 3, 2, 3, 4, 4, 5, 2, 3, 3, 4], // ISFP
+    [3, 2, 2, 1, 2, 1, 1, 0, 5, 4, 4, 3, 4, 3, 3, 2], // ESTP
+    [2, 1, 1, 0, 3, 2, 2, 1, 4, 5, 3, 4, 3, 4, 2, 3], // ESFP
+];
+
+fn within_age_gap(max_age_gap: u8, chooser_age: u8, candidate_age: u8) -> bool {
+    max_age_gap == 0 || chooser_age.abs_diff(candidate_age) <= max_age_gap
+}
+
+fn mask_accepts(accepted_mask: u8, code: u8) -> bool {
+    accepted_mask == 0 || (code < 8 && accepted_mask & (1u8 << code) != 0)
+}
+
+fn diet_fit(chooser_prefs: &Preferences, candidate: &Profile) -> (u8, bool) {
+    let accepted = mask_accepts(chooser_prefs.diet_preference, candidate.diet);
+    let points = if chooser_prefs.diet_preference != 0 && accepted { 3 } else { 0 };
+    (points, chooser_prefs.diet_dealbreaker && !accepted)
+}
+
+fn religion_fit(chooser_prefs: &Preferences, chooser: &Profile, candidate: &Profile) -> (u8, bool) {
+    let has_preference = chooser_prefs.religion_preference != RELIGION_NO_PREFERENCE;
+    let matches = candidate.religion == chooser_prefs.religion_preference;
+    let importance = chooser.religion_importance.min(RELIGION_IMPORTANCE_DEALBREAKER);
+
+    let points = if has_preference && matches { importance } else { 0 };
+    let dealbreaker = has_preference && !matches && importance == RELIGION_IMPORTANCE_DEALBREAKER;
+    (points, dealbreaker)
+}
+
+fn children_fit(chooser_prefs: &Preferences, candidate: &Profile) -> u8 {
+    let has_children = candidate.num_children > 0;
+    let prefers_none = chooser_prefs.children_preference == CHILDREN_PREF_NONE && !has_children;
+    let open = chooser_prefs.children_preference == CHILDREN_PREF_OPEN &&
+        (!has_children || candidate.youngest_child_age_bucket >= chooser_prefs.min_youngest_child_bucket);
+    if prefers_none || open { 3 } else { 0 }
+}
+
+fn physical_fit(chooser_prefs: &Preferences, candidate: &Profile) -> u8 {
+    let has_height_preference = chooser_prefs.preferred_height_min != 0 || chooser_prefs.preferred_height_max != 0;
+    let in_height_range = candidate.height_cm >= chooser_prefs.preferred_height_min &&
+        (chooser_prefs.preferred_height_max == 0 || candidate.height_cm <= chooser_prefs.preferred_height_max);
+    let height_points = if has_height_preference && in_height_range { 2 } else { 0 };
+
+    let body_type_points = if chooser_prefs.body_type_preference != 0 &&
+        mask_accepts(chooser_prefs.body_type_preference, candidate.body_type) {
+        1
+    } else {
+        0
+    };
+    height_points + body_type_points
+}
+
+fn zodiac_bonus(zodiac_a: u8, zodiac_b: u8) -> u8 {
+    match (ZODIAC_ELEMENTS.get(zodiac_a as usize), ZODIAC_ELEMENTS.get(zodiac_b as usize)) {
+        (Some(&a), Some(&b)) => ZODIAC_ELEMENT_COMPATIBILITY[a as usize][b as usize],
+        _ => 0,
+    }
+}
+
+fn personality_bonus(type_a: u8, type_b: u8) -> u8 {
+    PERSONALITY_COMPATIBILITY
+        .get(type_a as usize)
+        .and_then(|row| row.get(type_b as usize))
+        .copied()
+        .unwrap_or(0)
+}
+
+fn has_coordinates(profile: &Profile) -> bool {
+    profile.grid_x != 0 || profile.grid_y != 0
+}
+
+fn within_radius(preferred_distance_km: u16, squared_distance: u64) -> bool {
+    let radius = preferred_distance_km as u64;
+    preferred_distance_km == 0 || squared_distance <= radius * radius
+}
+
+/// Pair location score (0-25): banded by the squared grid distance against
+/// each side's squared radius, or the averaged location_score when either
+/// side has no coordinates
+fn pair_location_score(a_prefs: &Preferences, a_profile: &Profile, b_prefs: &Preferences, b_profile: &Profile) -> u16 {
+    if !has_coordinates(a_profile) || !has_coordinates(b_profile) {
+        return (a_profile.location_score as u16 + b_profile.location_score as u16) / 2;
+    }
+    let dx = a_profile.grid_x.abs_diff(b_profile.grid_x) as u64;
+    let dy = a_profile.grid_y.abs_diff(b_profile.grid_y) as u64;
+    let distance = dx * dx + dy * dy;
+    match (within_radius(a_prefs.preferred_distance_km, distance), within_radius(b_prefs.preferred_distance_km, distance)) {
+        (true, true) => 25,
+        (true, false) | (false, true) => 12,
+        (false, false) => 0,
+    }
+}
+
+/// Whether either side's minimum location score rules the pair out, unless
+/// one of them is open to long distance
+fn distance_conflict(a_prefs: &Preferences, b_prefs: &Preferences, location_score: u16) -> bool {
+    let too_far = |prefs: &Preferences| {
+        prefs.location_preference > 0 && location_score < prefs.location_preference as u16
+    };
+    (too_far(a_prefs) || too_far(b_prefs)) &&
+        !(a_prefs.open_to_long_distance || b_prefs.open_to_long_distance)
+}
+
+/// Whether `candidate` violates any of the chooser's own dealbreakers
+fn hard_filter_conflict(chooser_prefs: &Preferences, chooser: &Profile, candidate: &Profile) -> bool {
+    let pet_conflict = chooser_prefs.pet_dealbreaker &&
+        chooser.pet_status == PET_STATUS_ALLERGIC &&
+        candidate.pet_status == PET_STATUS_OWNER;
+    let (_, religion_dealbreaker) = religion_fit(chooser_prefs, chooser, candidate);
+    let (_, diet_dealbreaker) = diet_fit(chooser_prefs, candidate);
+
+    pet_conflict ||
+        religion_dealbreaker ||
+        diet_dealbreaker ||
+        candidate.age < MIN_MATCHING_AGE ||
+        !mask_accepts(chooser_prefs.relationship_status_preference, candidate.relationship_status) ||
+        !mask_accepts(chooser_prefs.preferred_genders, candidate.gender)
+}
+
+/// Whether either side's dealbreakers, or the distance dealbreaker at the
+/// pair location score, rule the pair out; the pair and batch scores share it
+fn pair_dealbreaker(
+    a_prefs: &Preferences,
+    a_profile: &Profile,
+    b_prefs: &Preferences,
+    b_profile: &Profile,
+    location_score: u16,
+) -> bool {
+    hard_filter_conflict(a_prefs, a_profile, b_profile) ||
+        hard_filter_conflict(b_prefs, b_profile, a_profile) ||
+        distance_conflict(a_prefs, b_prefs, location_score)
+}
+
+/// Two-sided compatibility score (0-100) and whether both users pass the age
+/// gate, exactly as `calculate_compatibility` reveals them
+pub fn score_compatibility(
+    a_prefs: &Preferences,
+    a_profile: &Profile,
+    b_prefs: &Preferences,
+    b_profile: &Profile,
+    scoring_flags: u8,
+) -> (u8, bool) {
+    let (score, age_gate_passed, _) = evaluate(a_prefs, a_profile, b_prefs, b_profile, scoring_flags);
+    (score, age_gate_passed)
+}
+
+/// Per-factor sub-scores both users receive from `calculate_compatibility`
+pub fn score_breakdown(
+    a_prefs: &Preferences,
+    a_profile: &Profile,
+    b_prefs: &Preferences,
+    b_profile: &Profile,
+    scoring_flags: u8,
+) -> ScoreBreakdown {
+    evaluate(a_prefs, a_profile, b_prefs, b_profile, scoring_flags).2
+}
+
+fn evaluate(
+    a_prefs: &Preferences,
+    a_profile: &Profile,
+    b_prefs: &Preferences,
+    b_profile: &Profile,
+    scoring_flags: u8,
+) -> (u8, bool, ScoreBreakdown) {
+    let age_gate_passed = a_profile.age >= MIN_MATCHING_AGE && b_profile.age >= MIN_MATCHING_AGE;
+
+    let mut score = 0u8;
+
+    // Age compatibility (0-30 points): both ranges and both max gaps must hold
+    let age_points = if b_profile.age >= a_prefs.preferred_age_min &&
+        b_profile.age <= a_prefs.preferred_age_max &&
+        a_profile.age >= b_prefs.preferred_age_min &&
+        a_profile.age <= b_prefs.preferred_age_max &&
+        within_age_gap(a_prefs.max_age_gap, a_profile.age, b_profile.age) &&
+        within_age_gap(b_prefs.max_age_gap, b_profile.age, a_profile.age) {
+        30
+    } else {
+        0
+    };
+    score += age_points;
+
+    // Interests compatibility (0-25 points)
+    let interests_points = if a_profile.interests_count > 0 && b_profile.interests_count > 0 {
+        let min_interests = a_profile.interests_count.min(b_profile.interests_count).min(MAX_INTERESTS);
+        let scaled = (min_interests as u16 * 25) / MAX_INTERESTS as u16;
+        scaled.min(25) as u8
+    } else {
+        0
+    };
+    score += interests_points;
+
+    // Location compatibility (0-25 points)
+    let location_score = pair_location_score(a_prefs, a_profile, b_prefs, b_profile);
+    let location_points = location_score.min(25) as u8;
+    score += location_points;
+
+    // Relationship type compatibility (0-20 points)
+    let relationship_points = if a_profile.relationship_type == b_profile.relationship_type { 20 } else { 0 };
+    score += relationship_points;
+
+    // Pet compatibility (0-5 bonus points)
+    let a_pets_ok = (a_prefs.pet_preference == PET_PREF_LOVES_PETS && b_profile.pet_status == PET_STATUS_OWNER) ||
+        (a_prefs.pet_preference == PET_PREF_NO_PETS && b_profile.pet_status != PET_STATUS_OWNER);
+    let b_pets_ok = (b_prefs.pet_preference == PET_PREF_LOVES_PETS && a_profile.pet_status == PET_STATUS_OWNER) ||
+        (b_prefs.pet_preference == PET_PREF_NO_PETS && a_profile.pet_status != PET_STATUS_OWNER);
+    if a_pets_ok && b_pets_ok {
+        score += 5;
+    } else if a_pets_ok || b_pets_ok {
+        score += 2;
+    }
+
+    // Budget compatibility (0-5 bonus points)
+    let a_budget_ok = b_profile.budget_tier >= a_prefs.budget_tier_min &&
+        b_profile.budget_tier <= a_prefs.budget_tier_max;
+    let b_budget_ok = a_profile.budget_tier >= b_prefs.budget_tier_min &&
+        a_profile.budget_tier <= b_prefs.budget_tier_max;
+    if a_budget_ok && b_budget_ok {
+        score += 5;
+    } else if a_budget_ok || b_budget_ok {
+        score += 2;
+    }
+
+    // Zodiac compatibility (0-3 bonus points), only when enabled
+    if scoring_flags & SCORING_FLAG_ZODIAC != 0 {
+        score += zodiac_bonus(a_profile.zodiac, b_profile.zodiac);
+    }
+
+    // Personality compatibility (0-5 bonus points), only when enabled
+    if scoring_flags & SCORING_FLAG_PERSONALITY != 0 {
+        score += personality_bonus(a_profile.personality_type, b_profile.personality_type);
+    }
+
+    // Religion/values compatibility (0-6 bonus points)
+    let (a_religion_points, _) = religion_fit(a_prefs, a_profile, b_profile);
+    let (b_religion_points, _) = religion_fit(b_prefs, b_profile, a_profile);
+    score += a_religion_points + b_religion_points;
+
+    // Diet compatibility (0-6 bonus points)
+    let (a_diet_points, _) = diet_fit(a_prefs, b_profile);
+    let (b_diet_points, _) = diet_fit(b_prefs, a_profile);
+    score += a_diet_points + b_diet_points;
+
+    // Children/family compatibility (0-6 bonus points)
+    score += children_fit(a_prefs, b_profile) + children_fit(b_prefs, a_profile);
+
+    // Height/body-type compatibility (0-6 bonus points)
+    let physical_points = physical_fit(a_prefs, b_profile) + physical_fit(b_prefs, a_profile);
+    score += physical_points;
+
+    if pair_dealbreaker(a_prefs, a_profile, b_prefs, b_profile, location_score) || !age_gate_passed {
+        score = 0;
+    }
+
+    let breakdown = if score > 0 {
+        ScoreBreakdown {
+            age: age_points,
+            interests: interests_points,
+            location: location_points,
+            relationship: relationship_points,
+            physical: physical_points,
+        }
+    } else {
+        ScoreBreakdown::default()
+    };
+    (score.min(100), age_gate_passed, breakdown)
+}
+
+const REPUTATION_MIN_RATINGS: u32 = 3;
+const REPUTATION_ADJUSTMENT: u8 = 2;
+
+/// (bonus, penalty) one user's reputation contributes; `None` means the user
+/// has never been rated
+fn reputation_adjustment(reputation: Option<&Reputation>) -> (u8, u8) {
+    match reputation {
+        Some(r) if r.rating_count >= REPUTATION_MIN_RATINGS => {
+            let bonus = if r.rating_sum >= 4 * r.rating_count { REPUTATION_ADJUSTMENT } else { 0 };
+            let penalty = if r.rating_sum <= 2 * r.rating_count { REPUTATION_ADJUSTMENT } else { 0 };
+            (bonus, penalty)
+        }
+        _ => (0, 0),
+    }
+}
+
+/// Applies both users' reputation to a `score_compatibility` result, giving
+/// the score `calculate_compatibility` reveals. Zero scores stay zero.
+pub fn apply_reputation(score: u8, a: Option<&Reputation>, b: Option<&Reputation>) -> u8 {
+    let (a_bonus, a_penalty) = reputation_adjustment(a);
+    let (b_bonus, b_penalty) = reputation_adjustment(b);
+    if score == 0 {
+        return 0;
+    }
+    (score + a_bonus + b_bonus).min(100).saturating_sub(a_penalty + b_penalty)
+}
+
+/// One-sided score (0-100) of `candidate` against the requester's preferences,
+/// as `calculate_compatibility_batch` reveals it per slot. Only the points are
+/// one-sided: the pair's dealbreakers still apply from both sides.
+pub fn score_candidate(
+    prefs: &Preferences,
+    profile: &Profile,
+    candidate_prefs: &Preferences,
+    candidate: &Profile,
+    scoring_flags: u8,
+) -> u8 {
+    let mut score = 0u8;
+
+    // Age compatibility (0-30 points)
+    if candidate.age >= prefs.preferred_age_min &&
+        candidate.age <= prefs.preferred_age_max &&
+        within_age_gap(prefs.max_age_gap, profile.age, candidate.age) {
+        score += 30;
+    }
+
+    // Interests compatibility (0-25 points)
+    if profile.interests_count > 0 && candidate.interests_count > 0 {
+        let min_interests = profile.interests_count.min(candidate.interests_count).min(MAX_INTERESTS);
+        score += ((min_interests as u16 * 25) / MAX_INTERESTS as u16).min(25) as u8;
+    }
+
+    // Location compatibility (0-25 points): full band within the requester's radius
+    let location_score = if has_coordinates(profile) && has_coordinates(candidate) {
+        let dx = profile.grid_x.abs_diff(candidate.grid_x) as u64;
+        let dy = profile.grid_y.abs_diff(candidate.grid_y) as u64;
+        if within_radius(prefs.preferred_distance_km, dx * dx + dy * dy) { 25 } else { 0 }
+    } else {
+        (profile.location_score as u16 + candidate.location_score as u16) / 2
+    };
+    score += location_score.min(25) as u8;
+
+    // Relationship type compatibility (0-20 points)
+    if profile.relationship_type == candidate.relationship_type {
+        score += 20;
+    }
+
+    // Pet compatibility (0-5 bonus points)
+    let pets_ok = (prefs.pet_preference == PET_PREF_LOVES_PETS && candidate.pet_status == PET_STATUS_OWNER) ||
+        (prefs.pet_preference == PET_PREF_NO_PETS && candidate.pet_status != PET_STATUS_OWNER);
+    if pets_ok {
+        score += 5;
+    }
+
+    // Budget compatibility (0-5 bonus points)
+    if candidate.budget_tier >= prefs.budget_tier_min && candidate.budget_tier <= prefs.budget_tier_max {
+        score += 5;
+    }
+
+    if scoring_flags & SCORING_FLAG_ZODIAC != 0 {
+        score += zodiac_bonus(profile.zodiac, candidate.zodiac);
+    }
+    if scoring_flags & SCORING_FLAG_PERSONALITY != 0 {
+        score += personality_bonus(profile.personality_type, candidate.personality_type);
+    }
+
+    // Religion, diet, children and physical fit (0-3 bonus points each)
+    let (religion_points, _) = religion_fit(prefs, profile, candidate);
+    let (diet_points, _) = diet_fit(prefs, candidate);
+    score += religion_points + diet_points + children_fit(prefs, candidate) + physical_fit(prefs, candidate);
+
+    let pair_location = pair_location_score(prefs, profile, candidate_prefs, candidate);
+    if pair_dealbreaker(prefs, profile, candidate_prefs, candidate, pair_location) {
+        return 0;
+    }
+
+    score.min(100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefs() -> Preferences {
+        Preferences {
+            preferred_age_min: 18,
+            preferred_age_max: 99,
+            ..Default::default()
+        }
+    }
+
+    fn profile(age: u8) -> Profile {
+        Profile { age, ..Default::default() }
+    }
+
+    // Defaults earn age (30) + relationship type (20) + budget (5)
+    const BASELINE: u8 = 55;
+
+    #[test]
+    fn baseline_pair_scores_age_relationship_and_budget() {
+        assert_eq!(score_compatibility(&prefs(), &profile(30), &prefs(), &profile(30), 0), (BASELINE, true));
+    }
+
+    #[test]
+    fn age_range_is_inclusive_at_both_edges() {
+        let a_prefs = Preferences { preferred_age_min: 25, preferred_age_max: 30, ..prefs() };
+        let score = |b_age| score_compatibility(&a_prefs, &profile(28), &prefs(), &profile(b_age), 0).0;
+
+        assert_eq!(score(25), BASELINE);
+        assert_eq!(score(30), BASELINE);
+        assert_eq!(score(24), BASELINE - 30);
+        assert_eq!(score(31), BASELINE - 30);
+    }
+
+    #[test]
+    fn max_age_gap_is_inclusive() {
+        let a_prefs = Preferences { max_age_gap: 5, ..prefs() };
+        let score = |b_age| score_compatibility(&a_prefs, &profile(30), &prefs(), &profile(b_age), 0).0;
+
+        assert_eq!(score(35), BASELINE);
+        assert_eq!(score(36), BASELINE - 30);
+    }
+
+    #[test]
+    fn within_age_gap_edges() {
+        // A gap of 0 means no limit, not "same age only"
+        assert!(within_age_gap(0, 18, 99));
+        assert!(within_age_gap(0, 30, 30));
+
+        // The exact gap passes in either direction; one year more fails
+        assert!(within_age_gap(5, 30, 35));
+        assert!(within_age_gap(5, 35, 30));
+        assert!(!within_age_gap(5, 30, 36));
+        assert!(!within_age_gap(5, 36, 30));
+
+        // A gap of 1 still admits the same age
+        assert!(within_age_gap(1, 30, 30));
+        assert!(within_age_gap(1, 30, 31));
+        assert!(!within_age_gap(1, 30, 32));
+    }
+
+    #[test]
+    fn max_age_gap_applies_from_either_side_and_in_batches() {
+        let b_prefs = Preferences { max_age_gap: 5, ..prefs() };
+        let score = |b_age| score_compatibility(&prefs(), &profile(30), &b_prefs, &profile(b_age), 0).0;
+        assert_eq!(score(25), BASELINE);
+        assert_eq!(score(24), BASELINE - 30);
+
+        let gap_prefs = Preferences { max_age_gap: 5, ..prefs() };
+        assert_eq!(score_candidate(&gap_prefs, &profile(30), &prefs(), &profile(35), 0), BASELINE);
+        assert_eq!(score_candidate(&gap_prefs, &profile(30), &prefs(), &profile(36), 0), BASELINE - 30);
+        assert_eq!(score_candidate(&Preferences { max_age_gap: 0, ..prefs() }, &profile(18), &prefs(), &profile(99), 0), BASELINE);
+    }
+
+    #[test]
+    fn age_gate_zeroes_the_score() {
+        assert_eq!(score_compatibility(&prefs(), &profile(17), &prefs(), &profile(30), 0), (0, false));
+        assert_eq!(score_compatibility(&prefs(), &profile(18), &prefs(), &profile(18), 0), (BASELINE, true));
+    }
+
+    #[test]
+    fn oversized_interest_counts_saturate_without_overflow() {
+        let a = Profile { interests_count: 255, ..profile(30) };
+        let b = Profile { interests_count: 200, ..profile(30) };
+        assert_eq!(score_compatibility(&prefs(), &a, &prefs(), &b, 0).0, BASELINE + 25);
+    }
+
+    #[test]
+    fn interest_counts_past_u8_scaling_range_do_not_wrap() {
+        // 120 * 25 = 3000 would wrap a u8 intermediate
+        let a = Profile { interests_count: 120, ..profile(30) };
+        let b = Profile { interests_count: 120, ..profile(30) };
+        assert_eq!(score_compatibility(&prefs(), &a, &prefs(), &b, 0).0, BASELINE + 25);
+    }
+
+    #[test]
+    fn location_score_is_averaged_and_capped() {
+        let a = Profile { location_score: 25, ..profile(30) };
+        let b = Profile { location_score: 24, ..profile(30) };
+        assert_eq!(score_compatibility(&prefs(), &a, &prefs(), &b, 0).0, BASELINE + 24);
+
+        let far = Profile { location_score: 200, ..profile(30) };
+        assert_eq!(score_compatibility(&prefs(), &far, &prefs(), &far, 0).0, BASELINE + 25);
+    }
+
+    #[test]
+    fn coordinates_band_location_by_both_radii() {
+        let within_10 = Preferences { preferred_distance_km: 10, ..prefs() };
+        let within_5 = Preferences { preferred_distance_km: 5, ..prefs() };
+        let here = Profile { grid_x: 1000, grid_y: 1000, location_score: 3, ..profile(30) };
+        // 6-8-10 triangle: squared distance 100 sits exactly on a 10 km radius
+        let there = Profile { grid_x: 1006, grid_y: 1008, location_score: 3, ..profile(30) };
+
+        assert_eq!(score_compatibility(&within_10, &here, &within_10, &there, 0).0, BASELINE + 25);
+        assert_eq!(score_compatibility(&within_10, &here, &within_5, &there, 0).0, BASELINE + 12);
+        assert_eq!(score_compatibility(&within_5, &here, &within_5, &there, 0).0, BASELINE);
+        // A zero radius accepts any distance
+        assert_eq!(score_compatibility(&prefs(), &here, &within_5, &there, 0).0, BASELINE + 12);
+    }
+
+    #[test]
+    fn missing_coordinates_fall_back_to_location_score() {
+        let within_5 = Preferences { preferred_distance_km: 5, ..prefs() };
+        let here = Profile { grid_x: 1000, grid_y: 1000, location_score: 20, ..profile(30) };
+        let unset = Profile { location_score: 10, ..profile(30) };
+        assert_eq!(score_compatibility(&within_5, &here, &within_5, &unset, 0).0, BASELINE + 15);
+    }
+
+    #[test]
+    fn widest_grid_distance_does_not_overflow() {
+        let corner = Profile { grid_x: 1, grid_y: 1, ..profile(30) };
+        let opposite = Profile { grid_x: u16::MAX, grid_y: u16::MAX, ..profile(30) };
+        let radius = Preferences { preferred_distance_km: u16::MAX, ..prefs() };
+        assert_eq!(score_compatibility(&radius, &corner, &radius, &opposite, 0).0, BASELINE);
+    }
+
+    #[test]
+    fn distance_dealbreaker_is_waived_by_either_long_distance_flag() {
+        let nearby_only = Preferences { location_preference: 20, ..prefs() };
+        let long_distance = Preferences { open_to_long_distance: true, ..prefs() };
+        let near = Profile { location_score: 20, ..profile(30) };
+        let far = Profile { location_score: 10, ..profile(30) };
+
+        // Pair location score 20 meets the minimum; 15 does not
+        assert_eq!(score_compatibility(&nearby_only, &near, &prefs(), &near, 0).0, BASELINE + 20);
+        assert_eq!(score_compatibility(&nearby_only, &near, &prefs(), &far, 0).0, 0);
+        assert_eq!(score_compatibility(&prefs(), &near, &nearby_only, &far, 0).0, 0);
+
+        // Either flag keeps the soft location points but skips the veto
+        let waived = BASELINE + 15;
+        let open_and_nearby = Preferences { open_to_long_distance: true, ..nearby_only.clone() };
+        assert_eq!(score_compatibility(&open_and_nearby, &near, &prefs(), &far, 0).0, waived);
+        assert_eq!(score_compatibility(&nearby_only, &near, &long_distance, &far, 0).0, waived);
+        assert_eq!(score_compatibility(&open_and_nearby, &near, &long_distance, &far, 0).0, waived);
+    }
+
+    #[test]
+    fn score_is_capped_at_100() {
+        let loves_pets = Preferences { pet_preference: PET_PREF_LOVES_PETS, ..prefs() };
+        let owner = Profile {
+            interests_count: 10,
+            location_score: 25,
+            pet_status: PET_STATUS_OWNER,
+            ..profile(30)
+        };
+        let flags = SCORING_FLAG_ZODIAC | SCORING_FLAG_PERSONALITY;
+
+        // 55 + 25 + 25 + 5 + 3 + 3 = 116 before the cap
+        assert_eq!(score_compatibility(&loves_pets, &owner, &loves_pets, &owner, flags).0, 100);
+    }
+
+    #[test]
+    fn pet_allergy_dealbreaker_zeroes_the_score() {
+        let allergic_prefs = Preferences { pet_dealbreaker: true, ..prefs() };
+        let allergic = Profile { pet_status: PET_STATUS_ALLERGIC, ..profile(30) };
+        let owner = Profile { pet_status: PET_STATUS_OWNER, ..profile(30) };
+
+        assert_eq!(score_compatibility(&allergic_prefs, &allergic, &prefs(), &owner, 0).0, 0);
+    }
+
+    #[test]
+    fn pet_points_cover_owner_allergic_and_neutral_combinations() {
+        let loves_pets = Preferences { pet_preference: PET_PREF_LOVES_PETS, ..prefs() };
+        let no_pets = Preferences { pet_preference: PET_PREF_NO_PETS, ..prefs() };
+        let owner = Profile { pet_status: PET_STATUS_OWNER, ..profile(30) };
+        let allergic = Profile { pet_status: PET_STATUS_ALLERGIC, ..profile(30) };
+        let neutral = profile(30);
+        let score = |a_prefs: &Preferences, a: &Profile, b_prefs: &Preferences, b: &Profile| {
+            score_compatibility(a_prefs, a, b_prefs, b, 0).0
+        };
+
+        // Both sides satisfied earns 5, one side 2, neither nothing
+        assert_eq!(score(&loves_pets, &owner, &loves_pets, &owner), BASELINE + 5);
+        assert_eq!(score(&loves_pets, &neutral, &loves_pets, &owner), BASELINE + 2);
+        assert_eq!(score(&no_pets, &allergic, &no_pets, &neutral), BASELINE + 5);
+        assert_eq!(score(&no_pets, &allergic, &prefs(), &owner), BASELINE);
+        assert_eq!(score(&prefs(), &neutral, &prefs(), &owner), BASELINE);
+    }
+
+    #[test]
+    fn pet_dealbreaker_only_fires_for_an_allergic_user_facing_an_owner() {
+        let dealbreaker = Preferences { pet_dealbreaker: true, ..prefs() };
+        let owner = Profile { pet_status: PET_STATUS_OWNER, ..profile(30) };
+        let allergic = Profile { pet_status: PET_STATUS_ALLERGIC, ..profile(30) };
+        let neutral = profile(30);
+
+        // Either side's opt-in counts
+        assert_eq!(score_compatibility(&prefs(), &owner, &dealbreaker, &allergic, 0).0, 0);
+
+        // Without the opt-in, or against a non-owner, the pair stays viable
+        assert_eq!(score_compatibility(&prefs(), &allergic, &prefs(), &owner, 0).0, BASELINE);
+        assert_eq!(score_compatibility(&dealbreaker, &allergic, &prefs(), &neutral, 0).0, BASELINE);
+        assert_eq!(score_compatibility(&dealbreaker, &allergic, &prefs(), &allergic, 0).0, BASELINE);
+        assert_eq!(score_compatibility(&dealbreaker, &owner, &prefs(), &owner, 0).0, BASELINE);
+    }
+
+    #[test]
+    fn relationship_status_mask_zeroes_the_score() {
+        let singles_only = Preferences { relationship_status_preference: 1 << 0, ..prefs() };
+        let divorced = Profile { relationship_status: 1, ..profile(30) };
+
+        assert_eq!(score_compatibility(&singles_only, &profile(30), &prefs(), &divorced, 0).0, 0);
+        assert_eq!(score_compatibility(&singles_only, &profile(30), &prefs(), &profile(30), 0).0, BASELINE);
+    }
+
+    #[test]
+    fn preferred_genders_gate_the_pair_from_either_side() {
+        let women_only = Preferences { preferred_genders: 1 << 1, ..prefs() };
+        let woman = Profile { gender: 1, ..profile(30) };
+        let man = Profile { gender: 2, ..profile(30) };
+
+        assert_eq!(score_compatibility(&women_only, &man, &prefs(), &woman, 0).0, BASELINE);
+        assert_eq!(score_compatibility(&women_only, &woman, &prefs(), &man, 0).0, 0);
+        assert_eq!(score_compatibility(&prefs(), &man, &women_only, &woman, 0).0, 0);
+    }
+
+    #[test]
+    fn height_and_body_type_are_soft_points_reported_in_the_breakdown() {
+        let tall_athletic = Preferences {
+            preferred_height_min: 170,
+            body_type_preference: 1 << 2,
+            ..prefs()
+        };
+        let matching = Profile { height_cm: 180, body_type: 2, ..profile(30) };
+        let short = Profile { height_cm: 160, body_type: 2, ..profile(30) };
+
+        assert_eq!(score_compatibility(&tall_athletic, &profile(30), &prefs(), &matching, 0).0, BASELINE + 3);
+        assert_eq!(score_compatibility(&tall_athletic, &profile(30), &prefs(), &short, 0).0, BASELINE + 1);
+        assert_eq!(score_breakdown(&tall_athletic, &profile(30), &prefs(), &matching, 0).physical, 3);
+
+        // Unset preferences earn nothing and never filter anyone out
+        assert_eq!(score_breakdown(&prefs(), &profile(30), &prefs(), &short, 0).physical, 0);
+        assert_eq!(score_compatibility(&prefs(), &profile(30), &prefs(), &short, 0).0, BASELINE);
+    }
+
+    #[test]
+    fn breakdown_is_empty_for_a_vetoed_pair() {
+        let expected = ScoreBreakdown { age: 30, interests: 0, location: 0, relationship: 20, physical: 0 };
+        assert_eq!(score_breakdown(&prefs(), &profile(30), &prefs(), &profile(30), 0), expected);
+
+        let singles_only = Preferences { relationship_status_preference: 1 << 0, ..prefs() };
+        let divorced = Profile { relationship_status: 1, ..profile(30) };
+        assert_eq!(score_breakdown(&singles_only, &profile(30), &prefs(), &divorced, 0), ScoreBreakdown::default());
+    }
+
+    #[test]
+    fn reputation_needs_enough_ratings_and_moves_viable_scores_only() {
+        let glowing = Reputation { rating_sum: 15, rating_count: 3 };
+        let poor = Reputation { rating_sum: 6, rating_count: 3 };
+        let too_few = Reputation { rating_sum: 10, rating_count: 2 };
+
+        assert_eq!(apply_reputation(BASELINE, None, None), BASELINE);
+        assert_eq!(apply_reputation(BASELINE, Some(&too_few), None), BASELINE);
+        assert_eq!(apply_reputation(BASELINE, Some(&glowing), Some(&glowing)), BASELINE + 4);
+        assert_eq!(apply_reputation(BASELINE, Some(&poor), None), BASELINE - 2);
+        assert_eq!(apply_reputation(BASELINE, Some(&glowing), Some(&poor)), BASELINE);
+
+        // Dealbreakers stay final and the cap applies before the penalty
+        assert_eq!(apply_reputation(0, Some(&glowing), None), 0);
+        assert_eq!(apply_reputation(99, Some(&glowing), Some(&poor)), 98);
+        assert_eq!(apply_reputation(1, Some(&poor), Some(&poor)), 0);
+    }
+
+    #[test]
+    fn candidate_score_is_one_sided() {
+        // Only the requester's preferences are known; an underage candidate
+        // still scores 0
+        assert_eq!(score_candidate(&prefs(), &profile(30), &prefs(), &profile(30), 0), BASELINE);
+        assert_eq!(score_candidate(&prefs(), &profile(30), &prefs(), &profile(17), 0), 0);
+
+        let mut picky = prefs();
+        picky.preferred_age_max = 25;
+        assert_eq!(score_candidate(&picky, &profile(30), &prefs(), &profile(30), 0), BASELINE - 30);
+        assert_eq!(score_candidate(&prefs(), &profile(30), &picky, &profile(30), 0), BASELINE);
+    }
+
+    #[test]
+    fn candidate_score_applies_the_pair_dealbreakers() {
+        let nearby_only = Preferences { location_preference: 20, ..prefs() };
+        let near = Profile { location_score: 20, ..profile(30) };
+        let far = Profile { location_score: 10, ..profile(30) };
+
+        // The distance veto fires from either side, at the pair location score
+        assert_eq!(score_candidate(&nearby_only, &near, &prefs(), &near, 0), BASELINE + 20);
+        assert_eq!(score_candidate(&nearby_only, &near, &prefs(), &far, 0), 0);
+        assert_eq!(score_candidate(&prefs(), &near, &nearby_only, &far, 0), 0);
+        let long_distance = Preferences { open_to_long_distance: true, ..prefs() };
+        assert_eq!(score_candidate(&nearby_only, &near, &long_distance, &far, 0), BASELINE + 15);
+
+        // So does the candidate's own gender gate
+        let women_only = Preferences { preferred_genders: 1 << 1, ..prefs() };
+        let man = Profile { gender: 2, ..profile(30) };
+        assert_eq!(score_candidate(&prefs(), &man, &women_only, &profile(30), 0), 0);
+    }
+
+    #[test]
+    fn out_of_range_codes_fall_back_like_the_circuit() {
+        let flags = SCORING_FLAG_ZODIAC | SCORING_FLAG_PERSONALITY;
+        let unknown = Profile { zodiac: 200, personality_type: 200, ..profile(30) };
+
+        // Unknown signs and personality types score 0
+        assert_eq!(score_compatibility(&prefs(), &unknown, &prefs(), &unknown, flags).0, BASELINE);
+    }
+
+    #[test]
+    fn zodiac_bonus_needs_two_known_signs() {
+        // Aries and Leo are both fire; 12 is the first code past Pisces
+        assert_eq!(zodiac_bonus(0, 4), 3);
+        assert_eq!(zodiac_bonus(11, 11), 3);
+        assert_eq!(zodiac_bonus(0, 12), 0);
+        assert_eq!(zodiac_bonus(12, 0), 0);
+        assert_eq!(zodiac_bonus(12, 12), 0);
+        assert_eq!(zodiac_bonus(255, 4), 0);
+
+        let aries = Profile { zodiac: 0, ..profile(30) };
+        let unknown = Profile { zodiac: 12, ..profile(30) };
+        let score = |a: &Profile, b: &Profile| score_compatibility(&prefs(), a, &prefs(), b, SCORING_FLAG_ZODIAC).0;
+        assert_eq!(score(&aries, &aries), BASELINE + 3);
+        assert_eq!(score(&aries, &unknown), BASELINE);
+        assert_eq!(score_candidate(&prefs(), &aries, &prefs(), &unknown, SCORING_FLAG_ZODIAC), BASELINE);
+    }
+}
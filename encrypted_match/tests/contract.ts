@@ -7,7 +7,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
 import { Contract } from "../target/types/contract";
-import { randomBytes } from "crypto";
+import { createHash, randomBytes } from "crypto";
 import * as fs from "fs";
 import * as os from "os";
 import * as bs58 from "bs58";
@@ -194,6 +194,39 @@ async function ensureSufficientBalance(
   }
 }
 
+// Mirrors the program's case-insensitive `username_hash`
+function usernameHash(username: string): Buffer {
+  return createHash("sha256").update(username.toLowerCase()).digest();
+}
+
+function usernameReservationAddress(program: Program<Contract>, username: string): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("username_reservation"), usernameHash(username)],
+    program.programId
+  )[0];
+}
+
+function usernameRegistryAddress(program: Program<Contract>, username: string): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("username"), usernameHash(username)],
+    program.programId
+  )[0];
+}
+
+// First phase of signup; create_profile consumes the reservation
+async function reserveUsername(
+  program: Program<Contract>,
+  user: anchor.web3.Keypair,
+  username: string
+): Promise<PublicKey> {
+  await program.methods
+    .reserveUsername(Array.from(usernameHash(username)))
+    .accounts({ user: user.publicKey })
+    .signers([user])
+    .rpc({ commitment: "confirmed" });
+  return usernameReservationAddress(program, username);
+}
+
 async function createUserProfile(
   program: Program<Contract>,
   user: anchor.web3.Keypair,
@@ -208,6 +241,8 @@ async function createUserProfile(
     .createProfile(profileData)
     .accountsPartial({
       userProfile: userProfilePDA,
+      usernameReservation: await reserveUsername(program, user, profileData.username),
+      usernameRegistry: usernameRegistryAddress(program, profileData.username),
       user: user.publicKey,
       systemProgram: anchor.web3.SystemProgram.programId,
     })
@@ -217,74 +252,187 @@ async function createUserProfile(
   return userProfilePDA;
 }
 
-async function initializeMPCComputationDefinitions(
+async function initNonceCounter(
   program: Program<Contract>,
-  provider: anchor.AnchorProvider,
-  owner: anchor.web3.Keypair
-): Promise<{
-  initMatchSessionCompDefPDA: PublicKey;
-  submitLikeCompDefPDA: PublicKey;
-  checkMutualMatchCompDefPDA: PublicKey;
-}> {
-  const mxeAccountPDA = getMXEAccAddress(program.programId);
-  const baseSeedCompDefAcc = getArciumAccountBaseSeed("ComputationDefinitionAccount");
-  
-  const initMatchSessionOffset = getCompDefAccOffset("init_match_session");
-  const initMatchSessionCompDefPDA = PublicKey.findProgramAddressSync(
-    [baseSeedCompDefAcc, program.programId.toBuffer(), initMatchSessionOffset],
-    getArciumProgAddress()
+  user: anchor.web3.Keypair
+): Promise<void> {
+  await program.methods
+    .initNonceCounter()
+    .accountsPartial({ user: user.publicKey })
+    .signers([user])
+    .rpc({ commitment: "confirmed" });
+}
+
+function nonceCounterAddress(program: Program<Contract>, user: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("nonce_counter"), user.toBuffer()],
+    program.programId
   )[0];
+}
 
-  const submitLikeOffset = getCompDefAccOffset("submit_like");
-  const submitLikeCompDefPDA = PublicKey.findProgramAddressSync(
-    [baseSeedCompDefAcc, program.programId.toBuffer(), submitLikeOffset],
-    getArciumProgAddress()
+function likeQuotaAddress(program: Program<Contract>, user: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("quota"), user.toBuffer()],
+    program.programId
   )[0];
+}
 
-  const checkMutualMatchOffset = getCompDefAccOffset("check_mutual_match");
-  const checkMutualMatchCompDefPDA = PublicKey.findProgramAddressSync(
-    [baseSeedCompDefAcc, program.programId.toBuffer(), checkMutualMatchOffset],
-    getArciumProgAddress()
+// Session and like nonces must increase per user, so encrypt with the counter's next value
+async function nextNonce(program: Program<Contract>, user: PublicKey): Promise<Buffer> {
+  const nonceCounter = await program.account.nonceCounter.fetch(nonceCounterAddress(program, user));
+  return Buffer.from(nonceCounter.nextNonce.toArray("le", 16));
+}
+
+// Mirrors the program's `session_pair`: one session PDA per unordered pair
+function pairSessionAddress(program: Program<Contract>, userA: PublicKey, userB: PublicKey): PublicKey {
+  const [low, high] = Buffer.compare(userA.toBuffer(), userB.toBuffer()) <= 0 ? [userA, userB] : [userB, userA];
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("pair_session"), low.toBuffer(), high.toBuffer()],
+    program.programId
   )[0];
+}
 
-  const compDefs = [
-    { name: "init_match_session", method: "initInitMatchSessionCompDef", pda: initMatchSessionCompDefPDA, offset: initMatchSessionOffset },
-    { name: "submit_like", method: "initSubmitLikeCompDef", pda: submitLikeCompDefPDA, offset: submitLikeOffset },
-    { name: "check_mutual_match", method: "initCheckMutualMatchCompDef", pda: checkMutualMatchCompDefPDA, offset: checkMutualMatchOffset }
-  ];
+// Queues init_match_session for a fresh session id and returns its PDA without
+// waiting for the computation; the payer must have a nonce counter
+async function openMatchSession(
+  program: Program<Contract>,
+  payer: anchor.web3.Keypair,
+  userA: PublicKey,
+  userB: PublicKey
+): Promise<PublicKey> {
+  const sessionId = Date.now();
+  const matchSessionPDA = pairSessionAddress(program, userA, userB);
+  const computationOffset = new anchor.BN(randomBytes(8), "hex");
+  await program.methods
+    .initMatchSession(
+      computationOffset,
+      new anchor.BN(sessionId),
+      userA,
+      userB,
+      new anchor.BN(Date.now())
+    )
+    .accountsPartial({
+      matchPairSession: matchSessionPDA,
+      payer: payer.publicKey,
+      mxeAccount: getMXEAccAddress(program.programId),
+      mempoolAccount: getMempoolAccAddress(program.programId),
+      executingPool: getExecutingPoolAccAddress(program.programId),
+      computationAccount: getComputationAccAddress(program.programId, computationOffset),
+      compDefAccount: getCompDefAccAddress(
+        program.programId,
+        Buffer.from(getCompDefAccOffset("init_match_session")).readUInt32LE()
+      ),
+      clusterAccount: getArciumEnv().arciumClusterPubkey,
+    })
+    .signers([payer])
+    .rpc({ commitment: "confirmed" });
+  return matchSessionPDA;
+}
 
-  for (const compDef of compDefs) {
-    try {
-      await (program.methods as any)[compDef.method]()
-        .accounts({
-          compDefAccount: compDef.pda,
-          payer: owner.publicKey,
-          mxeAccount: mxeAccountPDA,
-        })
-        .signers([owner])
-        .rpc();
-      
-      const finalizeTx = await buildFinalizeCompDefTx(
-        provider,
-        Buffer.from(compDef.offset).readUInt32LE(),
-        program.programId
-      );
-      
-      const latestBlockhash = await provider.connection.getLatestBlockhash();
-      finalizeTx.recentBlockhash = latestBlockhash.blockhash;
-      finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
-      finalizeTx.sign(owner);
-      await provider.sendAndConfirm(finalizeTx);
-    } catch (error) {
-      // Comp def may already exist
-    }
+async function stageMatchingCiphertexts(
+  program: Program<Contract>,
+  user: anchor.web3.Keypair,
+  mxePublicKey: Uint8Array,
+  age: number
+): Promise<void> {
+  const privateKey = x25519.utils.randomSecretKey();
+  const cipher = new RescueCipher(x25519.getSharedSecret(privateKey, mxePublicKey));
+
+  // MatchingData: 22 preference fields with no dealbreakers, then 19 profile fields
+  const fields = new Array<bigint>(41).fill(BigInt(0));
+  fields[0] = BigInt(18); // preferred_age_min
+  fields[1] = BigInt(99); // preferred_age_max
+  fields[22] = BigInt(age);
+  const nonce = randomBytes(16);
+  const ciphertexts = cipher.encrypt(fields, nonce);
+
+  // Chunks of 18 keep each transaction under the size limit
+  for (const start of [0, 18, 36]) {
+    await program.methods
+      .stageMatchingCiphertexts(
+        Array.from(x25519.getPublicKey(privateKey)),
+        new anchor.BN(deserializeLE(nonce).toString()),
+        start,
+        ciphertexts.slice(start, start + 18).map((c) => Array.from(c))
+      )
+      .accountsPartial({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
   }
+}
 
-  return {
-    initMatchSessionCompDefPDA,
-    submitLikeCompDefPDA,
-    checkMutualMatchCompDefPDA
-  };
+// A mock-mpc "ciphertext": the plaintext value little-endian in its slot
+function mockCiphertext(value: number | bigint): number[] {
+  const slot = Buffer.alloc(32);
+  slot.writeBigUInt64LE(BigInt(value));
+  return Array.from(slot);
+}
+
+// The u64 id the circuits use for a user, as the program's `user_mpc_id`
+function userMpcId(user: PublicKey): bigint {
+  return user.toBuffer().readBigUInt64LE(0);
+}
+
+// stageMatchingCiphertexts for a program built with the mock-mpc feature
+async function stageMockMatchingCiphertexts(
+  program: Program<Contract>,
+  user: anchor.web3.Keypair,
+  age: number
+): Promise<void> {
+  const fields = new Array<number>(41).fill(0);
+  fields[0] = 18; // preferred_age_min
+  fields[1] = 99; // preferred_age_max
+  fields[22] = age;
+  const ciphertexts = fields.map(mockCiphertext);
+
+  for (const start of [0, 18, 36]) {
+    await program.methods
+      .stageMatchingCiphertexts(
+        Array.from(new Uint8Array(32)),
+        new anchor.BN(0),
+        start,
+        ciphertexts.slice(start, start + 18)
+      )
+      .accountsPartial({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+  }
+}
+
+// Initializes and finalizes one circuit's computation definition, returning its
+// address; an already-initialized definition is left as it is
+async function ensureCompDef(
+  program: Program<Contract>,
+  provider: anchor.AnchorProvider,
+  owner: anchor.web3.Keypair,
+  circuit: string,
+  method: string
+): Promise<PublicKey> {
+  const offset = getCompDefAccOffset(circuit);
+  const compDefAccount = getCompDefAccAddress(program.programId, Buffer.from(offset).readUInt32LE());
+  try {
+    await (program.methods as any)[method]()
+      .accounts({
+        compDefAccount,
+        payer: owner.publicKey,
+        mxeAccount: getMXEAccAddress(program.programId),
+      })
+      .signers([owner])
+      .rpc();
+    const finalizeTx = await buildFinalizeCompDefTx(provider, Buffer.from(offset).readUInt32LE(), program.programId);
+    const latestBlockhash = await provider.connection.getLatestBlockhash();
+    finalizeTx.recentBlockhash = latestBlockhash.blockhash;
+    finalizeTx.lastValidBlockHeight = latestBlockhash.lastValidBlockHeight;
+    finalizeTx.sign(owner);
+    await provider.sendAndConfirm(finalizeTx);
+  } catch (error) {
+    // Only an existing comp def is expected; any other failure must surface
+    const logs: string[] = error.logs ?? [];
+    if (![error.message, ...logs].some((line) => /already in use/.test(line))) {
+      throw error;
+    }
+  }
+  return compDefAccount;
 }
 
 describe("Contract", () => {
@@ -306,6 +454,24 @@ describe("Contract", () => {
     return event;
   };
 
+  before(async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+
+    // Config and platform stats are created once per validator
+    const existing = await connection.getAccountInfo(configPDA);
+    if (!existing) {
+      await program.methods
+        .initializeConfig()
+        .accounts({ admin: owner.publicKey })
+        .signers([owner])
+        .rpc({ commitment: "confirmed" });
+    }
+  });
+
   it("Should create a user profile with encrypted sensitive data", async () => {
     const user = anchor.web3.Keypair.generate();
     await ensureSufficientBalance(connection, user);
@@ -330,6 +496,10 @@ describe("Contract", () => {
       encryptedPreferences: Buffer.from(encryptedPreferences),
       encryptionPubkey: Array.from(userPublicKey),
       profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
     };
 
     const [userProfilePDA] = PublicKey.findProgramAddressSync(
@@ -343,6 +513,8 @@ describe("Contract", () => {
       .accountsPartial({
         user: user.publicKey,
         userProfile: userProfilePDA,
+        usernameReservation: await reserveUsername(program, user, profileData.username),
+        usernameRegistry: usernameRegistryAddress(program, profileData.username),
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([user])
@@ -366,7 +538,7 @@ describe("Contract", () => {
     expect(profileAccount.totalMatches).to.equal(0);
     expect(profileAccount.encryptedLikesGiven).to.have.length(0);
     expect(profileAccount.encryptedLikesReceived).to.have.length(0);
-    expect(profileAccount.encryptedMatches).to.have.length(0);
+    expect(profileAccount.matchHistory).to.have.length(0);
   });
 
   it("Should fail with invalid username", async () => {
@@ -385,6 +557,10 @@ describe("Contract", () => {
       encryptedPreferences: randomBytes(50),
       encryptionPubkey: Array.from(userPublicKey),
       profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
     };
 
     const [userProfilePDA] = PublicKey.findProgramAddressSync(
@@ -398,6 +574,8 @@ describe("Contract", () => {
         .accountsPartial({
           user: user.publicKey,
           userProfile: userProfilePDA,
+          usernameReservation: await reserveUsername(program, user, invalidProfileData.username),
+          usernameRegistry: usernameRegistryAddress(program, invalidProfileData.username),
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([user])
@@ -425,6 +603,10 @@ describe("Contract", () => {
       encryptedPreferences: randomBytes(50),
       encryptionPubkey: Array.from(userPublicKey),
       profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
     };
 
     const [userProfilePDA] = PublicKey.findProgramAddressSync(
@@ -438,6 +620,8 @@ describe("Contract", () => {
         .accountsPartial({
           user: user.publicKey,
           userProfile: userProfilePDA,
+          usernameReservation: await reserveUsername(program, user, invalidProfileData.username),
+          usernameRegistry: usernameRegistryAddress(program, invalidProfileData.username),
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([user])
@@ -449,104 +633,523 @@ describe("Contract", () => {
     }
   });
 
-  it("Should initialize MPC environment", async () => {
-    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
-    const mxePublicKey = await getMXEPublicKeyWithRetry(
-      provider as anchor.AnchorProvider,
-      program.programId
-    );
-
-    expect(mxePublicKey).to.not.be.null;
-    expect(owner.publicKey).to.not.be.null;
-  });
+  it("Should store gender and orientation and reject unknown codes", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const profileData = {
+      username: "Identity",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hue",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("Identity", 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 9,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    };
 
-  it("Should demonstrate encrypted matching flow", async () => {
-    const alice = anchor.web3.Keypair.generate();
-    const bob = anchor.web3.Keypair.generate();
-    
-    const aliceAirdrop = await connection.requestAirdrop(alice.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
-    await connection.confirmTransaction(aliceAirdrop);
+    try {
+      await createUserProfile(program, user, profileData);
+      expect.fail("Created a profile with an unknown gender code");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidGender");
+    }
 
-    const bobAirdrop = await connection.requestAirdrop(bob.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
-    await connection.confirmTransaction(bobAirdrop);
-    
-    const aliceProfilePrivateKey = x25519.utils.randomSecretKey();
-    const aliceProfilePublicKey = x25519.getPublicKey(aliceProfilePrivateKey);
-    const bobProfilePrivateKey = x25519.utils.randomSecretKey();
-    const bobProfilePublicKey = x25519.getPublicKey(bobProfilePrivateKey);
-    
-    const aliceRealPrivateData = generatePrivateProfileData("Alice", 25);
-    const aliceRealPreferences = generateMatchingPreferences(25);
-    const aliceEncryptedPrivateData = encryptSensitiveData(aliceRealPrivateData, aliceProfilePrivateKey, 800);
-    const aliceEncryptedPreferences = encryptSensitiveData(aliceRealPreferences, aliceProfilePrivateKey, 300);
-    
-    const bobRealPrivateData = generatePrivateProfileData("Bob", 28);
-    const bobRealPreferences = generateMatchingPreferences(28);
-    const bobEncryptedPrivateData = encryptSensitiveData(bobRealPrivateData, bobProfilePrivateKey, 800);
-    const bobEncryptedPreferences = encryptSensitiveData(bobRealPreferences, bobProfilePrivateKey, 300);
-    
-    // Profiles data encrypted and ready
-    const [aliceProfilePDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_profile"), alice.publicKey.toBuffer()],
-      program.programId
-    );
-    
+    // The failed attempt left the username reserved for this user
     await program.methods
-      .createProfile({
-        username: "Alice",
-        avatarUrl: "https://images.unsplash.com/photo-1494790108755-2616b612b786",
-        age: 25,
-        locationCity: "San Francisco",
-        encryptedPrivateData: Buffer.from(aliceEncryptedPrivateData),
-        encryptedPreferences: Buffer.from(aliceEncryptedPreferences),
-        encryptionPubkey: Array.from(aliceProfilePublicKey),
-        profileVersion: 1,
-      })
+      .createProfile({ ...profileData, gender: 1, orientation: 3 }) // GENDER_WOMAN, ORIENTATION_BISEXUAL
       .accountsPartial({
-        userProfile: aliceProfilePDA,
-        user: alice.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
+        user: user.publicKey,
+        usernameReservation: usernameReservationAddress(program, "Identity"),
+        usernameRegistry: usernameRegistryAddress(program, "Identity"),
       })
-      .signers([alice])
-      .rpc();
-    
-    const [bobProfilePDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("user_profile"), bob.publicKey.toBuffer()],
-      program.programId
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const profile = await program.account.userProfile.fetch(
+      PublicKey.findProgramAddressSync([Buffer.from("user_profile"), user.publicKey.toBuffer()], program.programId)[0]
     );
-    
+    expect(profile.gender).to.equal(1);
+    expect(profile.orientation).to.equal(3);
+  });
+
+  it("Should update public profile fields in place", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const profilePDA = await createUserProfile(program, user, {
+      username: "Mover",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("Mover", 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    const before = await program.account.userProfile.fetch(profilePDA);
+
+    const updatedEvent = awaitEvent("profileUpdatedEvent");
     await program.methods
-      .createProfile({
-        username: "Bob",
-        avatarUrl: "https://images.unsplash.com/photo-1507003211169-0a1dd7228f2d",
-        age: 28,
-        locationCity: "New York",
-        encryptedPrivateData: Buffer.from(bobEncryptedPrivateData),
-        encryptedPreferences: Buffer.from(bobEncryptedPreferences),
-        encryptionPubkey: Array.from(bobProfilePublicKey),
+      .updateProfile({ username: null, avatarUrl: null, age: 31, locationCity: "Da Nang", bio: "Moved south for the sea" })
+      .accountsPartial({ user: user.publicKey, oldUsernameRegistry: usernameRegistryAddress(program, "Mover"), newUsernameRegistry: null })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    expect((await updatedEvent).locationCity).to.equal("Da Nang");
+
+    const after = await program.account.userProfile.fetch(profilePDA);
+    expect(after.username).to.equal("Mover");
+    expect(after.age).to.equal(31);
+    expect(after.locationCity).to.equal("Da Nang");
+    expect(after.bio).to.equal("Moved south for the sea");
+    expect(after.encryptedPrivateData).to.deep.equal(before.encryptedPrivateData);
+
+    // The same validation as create_profile applies to the new values
+    try {
+      await program.methods
+        .updateProfile({ username: null, avatarUrl: null, age: 17, locationCity: null, bio: null })
+        .accountsPartial({ user: user.publicKey, oldUsernameRegistry: usernameRegistryAddress(program, "Mover"), newUsernameRegistry: null })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Accepted an underage update");
+    } catch (error) {
+      expect(error.message).to.match(/InvalidAge/);
+    }
+    try {
+      await program.methods
+        .updateProfile({ username: null, avatarUrl: null, age: null, locationCity: null, bio: "x".repeat(257) })
+        .accountsPartial({ user: user.publicKey, oldUsernameRegistry: usernameRegistryAddress(program, "Mover"), newUsernameRegistry: null })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Accepted an overlong bio");
+    } catch (error) {
+      expect(error.message).to.match(/BioTooLong/);
+    }
+  });
+
+  it("Should keep usernames unique regardless of case", async () => {
+    const [holder, rival] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    const profileFor = (username: string) => {
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      return {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hanoi",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
         profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      };
+    };
+    for (const user of [holder, rival]) {
+      await ensureSufficientBalance(connection, user);
+    }
+    await createUserProfile(program, holder, profileFor("Unique1"));
+
+    // "unique1" and "Unique1" share a registry entry
+    try {
+      await createUserProfile(program, rival, profileFor("unique1"));
+      expect.fail("Created a second profile with a taken username");
+    } catch (error) {
+      expect(error.message).to.match(/UsernameTaken/);
+    }
+
+    // A rename moves the entry, freeing the old name
+    await program.methods
+      .updateProfile({ username: "Unique2", avatarUrl: null, age: null, locationCity: null, bio: null })
+      .accountsPartial({
+        user: holder.publicKey,
+        oldUsernameRegistry: usernameRegistryAddress(program, "Unique1"),
+        newUsernameRegistry: usernameRegistryAddress(program, "Unique2"),
       })
+      .signers([holder])
+      .rpc({ commitment: "confirmed" });
+    expect(await connection.getAccountInfo(usernameRegistryAddress(program, "Unique1"))).to.be.null;
+
+    // The rival's reservation survived the failed attempt
+    await program.methods
+      .createProfile(profileFor("unique1"))
       .accountsPartial({
-        userProfile: bobProfilePDA,
-        user: bob.publicKey,
-        systemProgram: anchor.web3.SystemProgram.programId,
+        user: rival.publicKey,
+        usernameReservation: usernameReservationAddress(program, "unique1"),
+        usernameRegistry: usernameRegistryAddress(program, "unique1"),
       })
-      .signers([bob])
-      .rpc();
-      
-    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
-    
-    const ownerBalance = await connection.getBalance(owner.publicKey);
-    if (ownerBalance < 5 * anchor.web3.LAMPORTS_PER_SOL) {
-      const ownerAirdrop = await connection.requestAirdrop(owner.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
-      await connection.confirmTransaction(ownerAirdrop);
+      .signers([rival])
+      .rpc({ commitment: "confirmed" });
+    const registry = await program.account.usernameRegistry.fetch(usernameRegistryAddress(program, "Unique1"));
+    expect(registry.owner.toString()).to.equal(rival.publicKey.toString());
+  });
+
+  it("Should deactivate and reactivate a profile", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const profilePDA = await createUserProfile(program, user, {
+      username: "OnBreak",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("OnBreak", 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+
+    // An active profile cannot be reactivated
+    try {
+      await program.methods
+        .reactivateProfile()
+        .accounts({ user: user.publicKey })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Reactivated an active profile");
+    } catch (error) {
+      expect(error.message).to.match(/ProfileStateUnchanged/);
     }
-    
-    const mxeAccountPDA = getMXEAccAddress(program.programId);
-    const mempoolPDA = getMempoolAccAddress(program.programId);
-    const executingPoolPDA = getExecutingPoolAccAddress(program.programId);
 
-    // Initialize MPC computation definitions
+    await program.methods
+      .deactivateProfile()
+      .accounts({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    expect((await program.account.userProfile.fetch(profilePDA)).isActive).to.be.false;
+
+    await program.methods
+      .reactivateProfile()
+      .accounts({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    expect((await program.account.userProfile.fetch(profilePDA)).isActive).to.be.true;
+  });
+
+  it("Should grow a compact profile without losing its data", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const encryptedPrivateData = Buffer.from(encryptSensitiveData(generatePrivateProfileData("Grower", 27), userPrivateKey));
+    const encryptedPreferences = Buffer.from(encryptSensitiveData({ preferredAgeMin: 25 }, userPrivateKey, 64));
+    const profilePDA = await createUserProfile(program, user, {
+      username: "Grower",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 27,
+      locationCity: "Hue",
+      encryptedPrivateData,
+      encryptedPreferences,
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    const before = await program.account.userProfile.fetch(profilePDA);
+    const compact = await connection.getAccountInfo(profilePDA);
+
+    // Larger preferences do not fit the compact account until it grows
+    const largerPreferences = Buffer.from(encryptSensitiveData(generateMatchingPreferences(27), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE));
+    try {
+      await program.methods
+        .setPreferences(largerPreferences)
+        .accountsPartial({ user: user.publicKey })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Preferences should not fit before the profile grows");
+    } catch (error) {
+      expect(error.toString()).to.include("ProfileTooSmall");
+    }
+
+    await program.methods
+      .growProfile(largerPreferences.length)
+      .accountsPartial({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const grown = await connection.getAccountInfo(profilePDA);
+    expect(grown.data.length).to.equal(compact.data.length + largerPreferences.length);
+    expect(grown.lamports).to.be.greaterThan(compact.lamports);
+
+    // Every field survives the realloc
+    const after = await program.account.userProfile.fetch(profilePDA);
+    expect(after.username).to.equal(before.username);
+    expect(after.locationCity).to.equal(before.locationCity);
+    expect(after.createdAt.toString()).to.equal(before.createdAt.toString());
+    expect(Buffer.from(after.encryptedPrivateData).equals(encryptedPrivateData)).to.be.true;
+    expect(Buffer.from(after.encryptedPreferences).equals(encryptedPreferences)).to.be.true;
+    expect(after.layoutVersion).to.equal(before.layoutVersion);
+
+    await program.methods
+      .setPreferences(largerPreferences)
+      .accountsPartial({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const updated = await program.account.userProfile.fetch(profilePDA);
+    expect(Buffer.from(updated.encryptedPreferences).equals(largerPreferences)).to.be.true;
+    expect(Buffer.from(updated.encryptedPrivateData).equals(encryptedPrivateData)).to.be.true;
+
+    try {
+      await program.methods
+        .growProfile(10_000)
+        .accountsPartial({ user: user.publicKey })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("A profile should not grow past its full layout");
+    } catch (error) {
+      expect(error.toString()).to.include("ProfileGrowthExceeded");
+    }
+  });
+
+  it("Should manage a photo gallery that starts with the avatar", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const avatarUrl = "https://example.com/avatar.jpg";
+    const profilePDA = await createUserProfile(program, user, {
+      username: "Gallery",
+      avatarUrl,
+      age: 28,
+      locationCity: "Hue",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("Gallery", 28), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(28), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    const photo = (n: number) => `https://example.com/photo-${n}.jpg`;
+    const manage = { user: user.publicKey };
+
+    // The first added photo joins the avatar, and the account grows to fit
+    const lengthBefore = (await connection.getAccountInfo(profilePDA)).data.length;
+    await program.methods.addPhoto(photo(1)).accountsPartial(manage).signers([user]).rpc({ commitment: "confirmed" });
+    let profile = await program.account.userProfile.fetch(profilePDA);
+    expect(profile.photoUrls).to.deep.equal([avatarUrl, photo(1)]);
+    expect((await connection.getAccountInfo(profilePDA)).data.length).to.be.greaterThan(lengthBefore);
+
+    for (const n of [2, 3, 4, 5]) {
+      await program.methods.addPhoto(photo(n)).accountsPartial(manage).signers([user]).rpc({ commitment: "confirmed" });
+    }
+    try {
+      await program.methods.addPhoto(photo(6)).accountsPartial(manage).signers([user]).rpc({ commitment: "confirmed" });
+      expect.fail("Added a seventh photo");
+    } catch (error) {
+      expect(error.toString()).to.include("TooManyPhotos");
+    }
+
+    // Moving a photo to the front makes it the avatar
+    await program.methods
+      .reorderPhotos(Buffer.from([2, 0, 1, 3, 4, 5]))
+      .accountsPartial(manage)
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    profile = await program.account.userProfile.fetch(profilePDA);
+    expect(profile.photoUrls.slice(0, 3)).to.deep.equal([photo(2), avatarUrl, photo(1)]);
+    expect(profile.avatarUrl).to.equal(photo(2));
+
+    try {
+      await program.methods
+        .reorderPhotos(Buffer.from([0, 0, 1, 3, 4, 5]))
+        .accountsPartial(manage)
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Accepted an order that repeats a photo");
+    } catch (error) {
+      expect(error.toString()).to.include("InvalidPhotoIndex");
+    }
+
+    // Removing the avatar promotes the next photo
+    await program.methods.removePhoto(0).accountsPartial(manage).signers([user]).rpc({ commitment: "confirmed" });
+    profile = await program.account.userProfile.fetch(profilePDA);
+    expect(profile.photoUrls).to.have.length(5);
+    expect(profile.avatarUrl).to.equal(avatarUrl);
+  });
+
+  it("Should close a profile and return its rent", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const profilePDA = await createUserProfile(program, user, {
+      username: "Leaver",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("Leaver", 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    const rent = (await connection.getAccountInfo(profilePDA)).lamports;
+    const balanceBefore = await connection.getBalance(user.publicKey);
+
+    const closedEvent = awaitEvent("profileClosedEvent");
+    await program.methods
+      .closeProfile()
+      .accountsPartial({ user: user.publicKey, usernameRegistry: usernameRegistryAddress(program, "Leaver") })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    expect((await closedEvent).profilePda.toString()).to.equal(profilePDA.toString());
+
+    expect(await connection.getAccountInfo(profilePDA)).to.be.null;
+    // The username is released along with the profile
+    expect(await connection.getAccountInfo(usernameRegistryAddress(program, "Leaver"))).to.be.null;
+    // The fee is far smaller than the returned rent
+    expect(await connection.getBalance(user.publicKey)).to.be.greaterThan(balanceBefore + rent - 10_000);
+  });
+
+  it("Should derive distinct computation definition offsets", async () => {
+    const circuits = [
+      "init_match_session",
+      "submit_like",
+      "check_mutual_match",
+      "calculate_compatibility",
+      "reveal_matched_data",
+      "calculate_compatibility_batch",
+      "reveal_icebreakers",
+      "rotate_session_nonce",
+      "submit_feedback",
+      "withdraw_like",
+      "submit_pass",
+    ];
+    const offsets = circuits.map((name) =>
+      Buffer.from(getCompDefAccOffset(name)).readUInt32LE()
+    );
+
+    expect(new Set(offsets).size).to.equal(circuits.length);
+  });
+
+  it("Should initialize MPC environment", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+
+    expect(mxePublicKey).to.not.be.null;
+    expect(owner.publicKey).to.not.be.null;
+  });
+
+  it("Should demonstrate encrypted matching flow", async () => {
+    const alice = anchor.web3.Keypair.generate();
+    const bob = anchor.web3.Keypair.generate();
+    
+    const aliceAirdrop = await connection.requestAirdrop(alice.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(aliceAirdrop);
+
+    const bobAirdrop = await connection.requestAirdrop(bob.publicKey, 2 * anchor.web3.LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(bobAirdrop);
+    
+    const aliceProfilePrivateKey = x25519.utils.randomSecretKey();
+    const aliceProfilePublicKey = x25519.getPublicKey(aliceProfilePrivateKey);
+    const bobProfilePrivateKey = x25519.utils.randomSecretKey();
+    const bobProfilePublicKey = x25519.getPublicKey(bobProfilePrivateKey);
+    
+    const aliceRealPrivateData = generatePrivateProfileData("Alice", 25);
+    const aliceRealPreferences = generateMatchingPreferences(25);
+    const aliceEncryptedPrivateData = encryptSensitiveData(aliceRealPrivateData, aliceProfilePrivateKey, 800);
+    const aliceEncryptedPreferences = encryptSensitiveData(aliceRealPreferences, aliceProfilePrivateKey, 300);
+    
+    const bobRealPrivateData = generatePrivateProfileData("Bob", 28);
+    const bobRealPreferences = generateMatchingPreferences(28);
+    const bobEncryptedPrivateData = encryptSensitiveData(bobRealPrivateData, bobProfilePrivateKey, 800);
+    const bobEncryptedPreferences = encryptSensitiveData(bobRealPreferences, bobProfilePrivateKey, 300);
+    
+    // Profiles data encrypted and ready
+    const [aliceProfilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), alice.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    await program.methods
+      .createProfile({
+        username: "Alice",
+        avatarUrl: "https://images.unsplash.com/photo-1494790108755-2616b612b786",
+        age: 25,
+        locationCity: "San Francisco",
+        encryptedPrivateData: Buffer.from(aliceEncryptedPrivateData),
+        encryptedPreferences: Buffer.from(aliceEncryptedPreferences),
+        encryptionPubkey: Array.from(aliceProfilePublicKey),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      })
+      .accountsPartial({
+        userProfile: aliceProfilePDA,
+        usernameReservation: await reserveUsername(program, alice, "Alice"),
+        usernameRegistry: usernameRegistryAddress(program, "Alice"),
+        user: alice.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([alice])
+      .rpc();
+    
+    const [bobProfilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), bob.publicKey.toBuffer()],
+      program.programId
+    );
+    
+    await program.methods
+      .createProfile({
+        username: "Bob",
+        avatarUrl: "https://images.unsplash.com/photo-1507003211169-0a1dd7228f2d",
+        age: 28,
+        locationCity: "New York",
+        encryptedPrivateData: Buffer.from(bobEncryptedPrivateData),
+        encryptedPreferences: Buffer.from(bobEncryptedPreferences),
+        encryptionPubkey: Array.from(bobProfilePublicKey),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      })
+      .accountsPartial({
+        userProfile: bobProfilePDA,
+        usernameReservation: await reserveUsername(program, bob, "Bob"),
+        usernameRegistry: usernameRegistryAddress(program, "Bob"),
+        user: bob.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([bob])
+      .rpc();
+
+    await initNonceCounter(program, alice);
+    await initNonceCounter(program, bob);
+      
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    
+    const ownerBalance = await connection.getBalance(owner.publicKey);
+    if (ownerBalance < 5 * anchor.web3.LAMPORTS_PER_SOL) {
+      const ownerAirdrop = await connection.requestAirdrop(owner.publicKey, 10 * anchor.web3.LAMPORTS_PER_SOL);
+      await connection.confirmTransaction(ownerAirdrop);
+    }
+    
+    const mxeAccountPDA = getMXEAccAddress(program.programId);
+    const mempoolPDA = getMempoolAccAddress(program.programId);
+    const executingPoolPDA = getExecutingPoolAccAddress(program.programId);
+
+    // Initialize MPC computation definitions
     
     const baseSeedCompDefAcc = getArciumAccountBaseSeed("ComputationDefinitionAccount");
     
@@ -651,14 +1254,8 @@ describe("Contract", () => {
     // === STEP 2: Create Match Session ===
     //console.log("\n Creating encrypted match session...");
     
-    const sessionId = Date.now(); // Unique session ID
-    const [matchSessionPDA] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("match_session"),
-        Buffer.from(new anchor.BN(sessionId).toArray("le", 8)) // session_id.to_le_bytes()
-      ],
-      program.programId
-    );
+    const sessionId = Date.now(); // Display id; the PDA is keyed by the pair
+    const matchSessionPDA = pairSessionAddress(program, alice.publicKey, bob.publicKey);
     
     // Listen for session creation event
     const sessionEventPromise = awaitEvent("matchSessionCreatedEvent");
@@ -682,7 +1279,7 @@ describe("Contract", () => {
       )
       .accountsPartial({
         matchPairSession: matchSessionPDA,
-        payer: owner.publicKey,
+        payer: alice.publicKey,
         mxeAccount: mxeAccountPDA,
         mempoolAccount: mempoolPDA,
         executingPool: executingPoolPDA,
@@ -690,7 +1287,7 @@ describe("Contract", () => {
         compDefAccount: initMatchSessionCompDefPDA,
         clusterAccount: arciumEnv.arciumClusterPubkey,
       })
-      .signers([owner])
+      .signers([alice])
       .rpc();
       
     // Wait for session creation event
@@ -747,9 +1344,9 @@ describe("Contract", () => {
     //console.log("    Bob Target ID:", bobTargetId);
     
     // Encrypt all fields together for UserLikeAction struct (like reference implementation)
-    const aliceNonce = randomBytes(16);
+    const aliceNonce = await nextNonce(program, alice.publicKey);
     const aliceCiphertext = aliceCipher.encrypt(
-      [BigInt(aliceUserId.toString()), BigInt(bobTargetId.toString()), likeAction ? BigInt(1) : BigInt(0), BigInt(currentTimestamp)],
+      [BigInt(aliceUserId.toString()), BigInt(bobTargetId.toString()), likeAction ? BigInt(1) : BigInt(0), BigInt(currentTimestamp), BigInt(80)],
       aliceNonce
     );
     
@@ -770,12 +1367,15 @@ describe("Contract", () => {
           Array.from(aliceCiphertext[1]), // encrypted_target_id
           Array.from(aliceCiphertext[2]), // encrypted_like_action  
           Array.from(aliceCiphertext[3]), // encrypted_timestamp
+          Array.from(aliceCiphertext[4]), // encrypted_intent_strength
           Array.from(aliceMpcPublicKey), // Alice's public key
           new anchor.BN(deserializeLE(aliceNonce).toString()) // nonce
         )
         .accountsPartial({
           matchPairSession: matchSessionPDA,
           user: alice.publicKey,
+          nonceCounter: nonceCounterAddress(program, alice.publicKey),
+          likeQuota: likeQuotaAddress(program, alice.publicKey),
           mxeAccount: mxeAccountPDA,
           mempoolAccount: mempoolPDA,
           executingPool: executingPoolPDA,
@@ -863,6 +1463,12 @@ describe("Contract", () => {
     } catch (error) {
       // Could not fetch session state
     }
+
+    // The like is counted on both profiles
+    const aliceAfterLike = await program.account.userProfile.fetch(aliceProfilePDA);
+    const bobAfterLike = await program.account.userProfile.fetch(bobProfilePDA);
+    expect(aliceAfterLike.totalLikesGiven).to.equal(1);
+    expect(bobAfterLike.totalLikesReceived).to.equal(1);
     
     // === STEP 4: Bob Likes Alice ===
     //console.log("\n Bob likes Alice (encrypted)...");
@@ -886,9 +1492,9 @@ describe("Contract", () => {
     //console.log("    Alice Target ID:", aliceTargetId);
     
     // Encrypt all fields together for Bob's UserLikeAction struct (like reference implementation)
-    const bobNonce = randomBytes(16);
+    const bobNonce = await nextNonce(program, bob.publicKey);
     const bobCiphertext = bobCipher.encrypt(
-      [BigInt(bobUserId.toString()), BigInt(aliceTargetId.toString()), bobLikeAction ? BigInt(1) : BigInt(0), BigInt(bobCurrentTimestamp)],
+      [BigInt(bobUserId.toString()), BigInt(aliceTargetId.toString()), bobLikeAction ? BigInt(1) : BigInt(0), BigInt(bobCurrentTimestamp), BigInt(60)],
       bobNonce
     );
     
@@ -906,12 +1512,15 @@ describe("Contract", () => {
         Array.from(bobCiphertext[1]), // encrypted_target_id
         Array.from(bobCiphertext[2]), // encrypted_like_action  
         Array.from(bobCiphertext[3]), // encrypted_timestamp
+        Array.from(bobCiphertext[4]), // encrypted_intent_strength
         Array.from(bobMpcPublicKey), // Bob's public key
         new anchor.BN(deserializeLE(bobNonce).toString()) // nonce
       )
       .accountsPartial({
         matchPairSession: matchSessionPDA,
         user: bob.publicKey,
+        nonceCounter: nonceCounterAddress(program, bob.publicKey),
+        likeQuota: likeQuotaAddress(program, bob.publicKey),
         mxeAccount: mxeAccountPDA,
         mempoolAccount: mempoolPDA,
         executingPool: executingPoolPDA,
@@ -963,6 +1572,10 @@ describe("Contract", () => {
       awaitEvent("noMutualMatchEvent")
     ]);
     
+    // Both users' dealbreakers are checked against each other in MPC
+    await stageMatchingCiphertexts(program, alice, mxePublicKey, 25);
+    await stageMatchingCiphertexts(program, bob, mxePublicKey, 28);
+
     // Generate computation offset for mutual match check
     const checkMatchComputationOffset = new anchor.BN(randomBytes(8), "hex");
     const checkMatchComputationAccountPDA = getComputationAccAddress(
@@ -974,7 +1587,7 @@ describe("Contract", () => {
       .checkMutualMatch(checkMatchComputationOffset) // computation_offset
       .accountsPartial({
         matchPairSession: matchSessionPDA,
-        payer: owner.publicKey,
+        payer: alice.publicKey,
         mxeAccount: mxeAccountPDA,
         mempoolAccount: mempoolPDA,
         executingPool: executingPoolPDA,
@@ -982,7 +1595,7 @@ describe("Contract", () => {
         compDefAccount: checkMutualMatchCompDefPDA,
         clusterAccount: arciumEnv.arciumClusterPubkey,
       })
-      .signers([ owner ])
+      .signers([alice])
       .rpc();
       
     //console.log(" Mutual match check transaction completed!");
@@ -1024,6 +1637,40 @@ describe("Contract", () => {
     //console.log("\n Verifying final state...");
     
     const finalSession = await program.account.matchPairSession.fetch(matchSessionPDA);
+    expect(finalSession.isFinalized).to.equal(true);
+
+    // A finalized session takes no further likes
+    const lateLikeComputationOffset = new anchor.BN(randomBytes(8), "hex");
+    try {
+      await program.methods
+        .submitLike(
+          lateLikeComputationOffset,
+          Array.from(aliceCiphertext[0]),
+          Array.from(aliceCiphertext[1]),
+          Array.from(aliceCiphertext[2]),
+          Array.from(aliceCiphertext[3]),
+          Array.from(aliceCiphertext[4]),
+          Array.from(aliceMpcPublicKey),
+          new anchor.BN(deserializeLE(aliceNonce).toString())
+        )
+        .accountsPartial({
+          matchPairSession: matchSessionPDA,
+          user: alice.publicKey,
+          nonceCounter: nonceCounterAddress(program, alice.publicKey),
+          likeQuota: likeQuotaAddress(program, alice.publicKey),
+          mxeAccount: mxeAccountPDA,
+          mempoolAccount: mempoolPDA,
+          executingPool: executingPoolPDA,
+          computationAccount: getComputationAccAddress(program.programId, lateLikeComputationOffset),
+          compDefAccount: submitLikeCompDefPDA,
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([alice])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Submitted a like to a finalized session");
+    } catch (error) {
+      expect(error.message).to.match(/SessionAlreadyFinalized/);
+    }
     
     //console.log(" Final Session State:");
     //console.log("  - Session ID:", finalSession.sessionId.toString());
@@ -1047,12 +1694,96 @@ describe("Contract", () => {
       //console.log("  Session not finalized yet - may need more time");
     }
     
+    // Alice liked at intent 80 and Bob at 60, revealed only as their mean
+    if (finalSession.matchFound) {
+      expect(finalSession.matchConfidence).to.equal(70);
+    }
+
+    // A confirmed match is written to both histories as session id,
+    // matched_at, then the other participant
+    if (finalSession.matchFound) {
+      const aliceHistory = Buffer.from((await program.account.userProfile.fetch(aliceProfilePDA)).matchHistory);
+      const bobHistory = Buffer.from((await program.account.userProfile.fetch(bobProfilePDA)).matchHistory);
+      expect(aliceHistory.length).to.equal(48);
+      expect(aliceHistory.readBigUInt64LE(0)).to.equal(BigInt(finalSession.sessionId.toString()));
+      expect(new PublicKey(aliceHistory.subarray(16)).toString()).to.equal(bob.publicKey.toString());
+      expect(new PublicKey(bobHistory.subarray(16)).toString()).to.equal(alice.publicKey.toString());
+    }
+
     // Test completed successfully
     expect(finalSession.isFinalized).to.be.true;
     console.log("Encrypted matching flow test completed");
   });
 
   // Test case: Multiple profiles và matching scenario
+  it("Should score a pair with calculate_compatibility", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const compDefAccount = await ensureCompDef(
+      program, provider as anchor.AnchorProvider, owner, "calculate_compatibility", "initCalculateCompatibilityCompDef"
+    );
+
+    const users = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of users.entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Scored${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hanoi",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+      await stageMatchingCiphertexts(program, user, mxePublicKey, 30);
+    }
+    const [userA, userB] = users;
+
+    const queue = (payer: anchor.web3.Keypair) => {
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      return program.methods
+        .calculateCompatibility(computationOffset, userA.publicKey, userB.publicKey, 0)
+        .accountsPartial({
+          payer: payer.publicKey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          compDefAccount,
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([payer])
+        .rpc({ commitment: "confirmed" });
+    };
+
+    // Only a participant may reveal the pair's score
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+    try {
+      await queue(outsider);
+      expect.fail("Outsider requested a pair's score");
+    } catch (error) {
+      expect(error.message).to.match(/UnauthorizedUser/);
+    }
+
+    const calculatedEvent = awaitEvent("compatibilityCalculatedEvent");
+    await queue(userA);
+    const event = await calculatedEvent;
+    expect(event.userA.toString()).to.equal(userA.publicKey.toString());
+    expect(event.userB.toString()).to.equal(userB.publicKey.toString());
+    expect(event.score).to.be.at.most(100);
+  });
+
   it("Should demonstrate multi-profile encrypted matching", async () => {
     console.log("Multi-profile matching test started");
     
@@ -1094,8 +1825,9 @@ describe("Contract", () => {
         //console.log(`    Encrypted size: ${encryptedPrivateData.length} bytes`);
       }
       
+      // Suffixed so the names stay clear of the single-pair flow's Alice and Bob
       const profileData = {
-        username: profileNames[i],
+        username: `${profileNames[i]}Multi`,
         avatarUrl: `https://api.dicebear.com/7.x/avatars/svg?seed=${profileNames[i]}`,
         age: ages[i],
         locationCity: cities[i],
@@ -1103,6 +1835,10 @@ describe("Contract", () => {
         encryptedPreferences: Buffer.from(encryptedPreferences),
         encryptionPubkey: Array.from(userPublicKey),
         profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
       };
       
       const [userProfilePDA] = PublicKey.findProgramAddressSync(
@@ -1114,11 +1850,14 @@ describe("Contract", () => {
         .createProfile(profileData)
         .accountsPartial({
           userProfile: userProfilePDA,
+          usernameReservation: await reserveUsername(program, user, profileData.username),
+          usernameRegistry: usernameRegistryAddress(program, profileData.username),
           user: user.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .signers([user])
         .rpc();
+      await initNonceCounter(program, user);
       
       profiles.push({
         user,
@@ -1263,14 +2002,8 @@ describe("Contract", () => {
       //console.log(`\n Match ${i + 1}/3: Alice + ${target.profileData.username}`);
       
       // Create match session
-      const sessionId = Date.now() + i; // Unique session ID
-      const [matchSessionPDA] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("match_session"),
-          Buffer.from(new anchor.BN(sessionId).toArray("le", 8))
-        ],
-        program.programId
-      );
+      const sessionId = Date.now() + i; // Display id; the PDA is keyed by the pair
+      const matchSessionPDA = pairSessionAddress(program, alice.user.publicKey, target.user.publicKey);
       
       const nonce = Date.now() + i * 1000;
       const initMatchComputationOffset = new anchor.BN(randomBytes(8), "hex");
@@ -1290,7 +2023,7 @@ describe("Contract", () => {
         )
         .accountsPartial({
           matchPairSession: matchSessionPDA,
-          payer: owner.publicKey,
+          payer: target.user.publicKey,
           mxeAccount: mxeAccountPDA,
           mempoolAccount: mempoolPDA,
           executingPool: executingPoolPDA,
@@ -1298,7 +2031,7 @@ describe("Contract", () => {
           compDefAccount: initMatchSessionCompDefPDA,
           clusterAccount: arciumEnv.arciumClusterPubkey,
         })
-        .signers([owner])
+        .signers([target.user])
         .rpc();
       
       // Wait for init session finalization
@@ -1332,9 +2065,9 @@ describe("Contract", () => {
       const targetId = new anchor.BN(target.user.publicKey.toBuffer().slice(0, 8), "le");
       const currentTimestamp = Math.floor(Date.now() / 1000);
       
-      const aliceNonce = randomBytes(16);
+      const aliceNonce = await nextNonce(program, alice.user.publicKey);
       const aliceCiphertext = aliceCipher.encrypt(
-        [BigInt(aliceUserId.toString()), BigInt(targetId.toString()), BigInt(1), BigInt(currentTimestamp)],
+        [BigInt(aliceUserId.toString()), BigInt(targetId.toString()), BigInt(1), BigInt(currentTimestamp), BigInt(50)],
         aliceNonce
       );
       
@@ -1351,12 +2084,15 @@ describe("Contract", () => {
           Array.from(aliceCiphertext[1]),
           Array.from(aliceCiphertext[2]),
           Array.from(aliceCiphertext[3]),
+          Array.from(aliceCiphertext[4]),
           Array.from(aliceMpcPublicKey),
           new anchor.BN(deserializeLE(aliceNonce).toString())
         )
         .accountsPartial({
           matchPairSession: matchSessionPDA,
           user: alice.user.publicKey,
+          nonceCounter: nonceCounterAddress(program, alice.user.publicKey),
+          likeQuota: likeQuotaAddress(program, alice.user.publicKey),
           mxeAccount: mxeAccountPDA,
           mempoolAccount: mempoolPDA,
           executingPool: executingPoolPDA,
@@ -1394,9 +2130,9 @@ describe("Contract", () => {
       const aliceTargetId = new anchor.BN(alice.user.publicKey.toBuffer().slice(0, 8), "le");
       const targetTimestamp = Math.floor(Date.now() / 1000);
       
-      const targetNonce = randomBytes(16);
+      const targetNonce = await nextNonce(program, target.user.publicKey);
       const targetCiphertext = targetCipher.encrypt(
-        [BigInt(targetUserId.toString()), BigInt(aliceTargetId.toString()), BigInt(1), BigInt(targetTimestamp)],
+        [BigInt(targetUserId.toString()), BigInt(aliceTargetId.toString()), BigInt(1), BigInt(targetTimestamp), BigInt(50)],
         targetNonce
       );
       
@@ -1413,12 +2149,15 @@ describe("Contract", () => {
           Array.from(targetCiphertext[1]),
           Array.from(targetCiphertext[2]),
           Array.from(targetCiphertext[3]),
+          Array.from(targetCiphertext[4]),
           Array.from(targetMpcPublicKey),
           new anchor.BN(deserializeLE(targetNonce).toString())
         )
         .accountsPartial({
           matchPairSession: matchSessionPDA,
           user: target.user.publicKey,
+          nonceCounter: nonceCounterAddress(program, target.user.publicKey),
+          likeQuota: likeQuotaAddress(program, target.user.publicKey),
           mxeAccount: mxeAccountPDA,
           mempoolAccount: mempoolPDA,
           executingPool: executingPoolPDA,
@@ -1446,6 +2185,8 @@ describe("Contract", () => {
       
       // Check for mutual match
       //console.log(`    Checking for mutual match...`);
+      await stageMatchingCiphertexts(program, alice.user, mxePublicKey, alice.profileData.age);
+      await stageMatchingCiphertexts(program, target.user, mxePublicKey, target.profileData.age);
       
       const checkMatchComputationOffset = new anchor.BN(randomBytes(8), "hex");
       const checkMatchComputationAccountPDA = getComputationAccAddress(
@@ -1457,7 +2198,7 @@ describe("Contract", () => {
         .checkMutualMatch(checkMatchComputationOffset)
         .accountsPartial({
           matchPairSession: matchSessionPDA,
-          payer: owner.publicKey,
+          payer: alice.user.publicKey,
           mxeAccount: mxeAccountPDA,
           mempoolAccount: mempoolPDA,
           executingPool: executingPoolPDA,
@@ -1465,7 +2206,7 @@ describe("Contract", () => {
           compDefAccount: checkMutualMatchCompDefPDA,
           clusterAccount: arciumEnv.arciumClusterPubkey,
         })
-        .signers([owner])
+        .signers([alice.user])
         .rpc();
       
       // Wait for match check finalization
@@ -1530,6 +2271,1276 @@ describe("Contract", () => {
     console.log("Multi-profile matching test completed with 3 successful matches");
     
   });
+
+  it("Should keep the version 2 ProfileView layout stable", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const username = "ViewLayout1";
+    const avatarUrl = "https://example.com/avatar.jpg";
+    const locationCity = "Da Nang";
+    const profilePDA = await createUserProfile(program, user, {
+      username,
+      avatarUrl,
+      age: 27,
+      locationCity,
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 27), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(27), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+
+    const view = await program.methods
+      .getProfileView()
+      .accountsPartial({ userProfile: profilePDA, caller: null, matchProof: null })
+      .view();
+
+    expect(view.summaryVersion).to.equal(2);
+    expect(Object.keys(view)).to.deep.equal([
+      "summaryVersion",
+      "owner",
+      "username",
+      "avatarUrl",
+      "age",
+      "locationCity",
+      "isActive",
+      "totalLikesGiven",
+      "totalLikesReceived",
+      "totalMatches",
+      "createdAt",
+      "lastUpdated",
+      "isDiscoverable",
+      "completeness",
+      "isVerified",
+      "isRedacted",
+    ]);
+
+    const encoded = program.coder.types.encode("profileView", view);
+    const strings = [username, avatarUrl, locationCity]
+      .reduce((total, value) => total + 4 + Buffer.byteLength(value), 0);
+    expect(encoded.length).to.equal(1 + 32 + strings + 1 + 1 + 3 * 4 + 2 * 8 + 4);
+    expect(encoded[0]).to.equal(2);
+    expect(new PublicKey(encoded.subarray(1, 33)).toString()).to.equal(user.publicKey.toString());
+  });
+
+  it("Should bundle a profile's counters and flags in one view", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const username = "StatsView1";
+    const profilePDA = await createUserProfile(program, user, {
+      username,
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 26,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 26), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(26), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+
+    const stats = await program.methods
+      .getUserStats()
+      .accountsPartial({ userProfile: profilePDA })
+      .view();
+
+    expect(stats.owner.toString()).to.equal(user.publicKey.toString());
+    expect(stats.totalLikesGiven).to.equal(0);
+    expect(stats.totalLikesReceived).to.equal(0);
+    expect(stats.totalMatches).to.equal(0);
+    expect(stats.completeness).to.equal(100);
+    expect(stats.isActive).to.be.true;
+    expect(stats.isDiscoverable).to.be.true;
+    expect(stats.matchingPaused).to.be.false;
+    expect(stats.isBoosted).to.be.false;
+
+    // A fresh profile has an empty like history: one empty page, nothing past it
+    const likesPage = (offset: number) =>
+      program.methods
+        .getLikesPage(false, offset)
+        .accountsPartial({ userProfile: profilePDA, caller: null, matchProof: null })
+        .view();
+    const page = await likesPage(0);
+    expect(page.bytes.length).to.equal(0);
+    expect(page.nextOffset).to.equal(null);
+    try {
+      await likesPage(1);
+      expect.fail("Read a likes page past the end of the history");
+    } catch (error) {
+      expect(error.message).to.match(/OffsetOutOfRange/);
+    }
+  });
+
+  it("Should record presence only for the profile owner", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const username = "Heartbeat1";
+    const profilePDA = await createUserProfile(program, user, {
+      username,
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 27,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 27), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(27), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    const created = await program.account.userProfile.fetch(profilePDA);
+    expect(created.lastSeen.toNumber()).to.equal(created.createdAt.toNumber());
+
+    await program.methods
+      .heartbeat()
+      .accountsPartial({ user: user.publicKey, userProfile: profilePDA })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const seen = await program.account.userProfile.fetch(profilePDA);
+    expect(seen.lastSeen.toNumber()).to.be.at.least(created.lastSeen.toNumber());
+    expect(seen.lastUpdated.toNumber()).to.equal(created.lastUpdated.toNumber());
+
+    // Nobody else can mark the user as online
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+    try {
+      await program.methods
+        .heartbeat()
+        .accountsPartial({ user: outsider.publicKey, userProfile: profilePDA })
+        .signers([outsider])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Outsider updated someone else's presence");
+    } catch (error) {
+      expect(error.message).to.match(/ConstraintSeeds/);
+    }
+  });
+
+  it("Should refuse a session with an unregistered user", async () => {
+    const registered = anchor.web3.Keypair.generate();
+    const phantom = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, registered);
+
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const username = "Registered1";
+    await createUserProfile(program, registered, {
+      username,
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 29,
+      locationCity: "Hue",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 29), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(29), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    await initNonceCounter(program, registered);
+
+    try {
+      await openMatchSession(program, registered, registered.publicKey, phantom.publicKey);
+      expect.fail("Session was created for a user without a profile");
+    } catch (error) {
+      expect(error.message).to.match(/ProfileNotFound/);
+    }
+  });
+
+  it("Should refuse a session between a user and themselves", async () => {
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    await createUserProfile(program, user, {
+      username: "Narcissus",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hue",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("Narcissus", 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    await initNonceCounter(program, user);
+
+    try {
+      await openMatchSession(program, user, user.publicKey, user.publicKey);
+      expect.fail("Opened a session between a user and themselves");
+    } catch (error) {
+      expect(error.toString()).to.include("CannotMatchSelf");
+    }
+    const profilePDA = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), user.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    expect((await program.account.userProfile.fetch(profilePDA)).openSessions).to.equal(0);
+  });
+
+  it("Should refuse sessions between users who blocked each other", async () => {
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Blocker${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    await initNonceCounter(program, userA);
+
+    try {
+      await program.methods
+        .addBlock(userB.publicKey)
+        .accountsPartial({ user: userB.publicKey })
+        .signers([userB])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("A user blocked themselves");
+    } catch (error) {
+      expect(error.toString()).to.include("CannotBlockSelf");
+    }
+
+    const blockedEvent = awaitEvent("userBlockedEvent");
+    await program.methods
+      .addBlock(userA.publicKey)
+      .accountsPartial({ user: userB.publicKey })
+      .signers([userB])
+      .rpc({ commitment: "confirmed" });
+    expect((await blockedEvent).target.toString()).to.equal(userA.publicKey.toString());
+    const blocked = await program.methods
+      .isBlocked(userB.publicKey)
+      .accountsPartial({
+        user: userA.publicKey,
+        userBlocklist: null,
+        targetBlocklist: PublicKey.findProgramAddressSync(
+          [Buffer.from("blocklist"), userB.publicKey.toBuffer()],
+          program.programId
+        )[0],
+      })
+      .signers([userA])
+      .view();
+    expect(blocked).to.be.true;
+
+    // The blocked user cannot open a session with the blocker
+    try {
+      await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+      expect.fail("Opened a session with a user who blocked the payer");
+    } catch (error) {
+      expect(error.toString()).to.include("UserBlocked");
+    }
+
+    await program.methods
+      .removeBlock(userA.publicKey)
+      .accountsPartial({ user: userB.publicKey })
+      .signers([userB])
+      .rpc({ commitment: "confirmed" });
+    await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+  });
+
+  it("Should record one report per reporter and throttle repeats", async () => {
+    const [reporter, reported] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [reporter, reported].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Report${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+
+    try {
+      await program.methods
+        .reportProfile(reporter.publicKey, 1)
+        .accountsPartial({ reporter: reporter.publicKey })
+        .signers([reporter])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("A user reported themselves");
+    } catch (error) {
+      expect(error.toString()).to.include("CannotReportSelf");
+    }
+
+    // Only verified reporters are heard
+    const unverifiedEvent = awaitEvent("reportThrottledEvent");
+    await program.methods
+      .reportProfile(reported.publicKey, 2)
+      .accountsPartial({ reporter: reporter.publicKey })
+      .signers([reporter])
+      .rpc({ commitment: "confirmed" });
+    expect((await unverifiedEvent).reason).to.equal(1); // REPORT_THROTTLE_UNVERIFIED_REPORTER
+
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    await program.methods
+      .setVerificationAuthority(owner.publicKey)
+      .accounts({ admin: owner.publicKey })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+    const [reporterProfilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), reporter.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .setVerification(true)
+      .accountsPartial({ verificationAuthority: owner.publicKey, userProfile: reporterProfilePDA })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    const reportedEvent = awaitEvent("profileReportedEvent");
+    await program.methods
+      .reportProfile(reported.publicKey, 2)
+      .accountsPartial({ reporter: reporter.publicKey })
+      .signers([reporter])
+      .rpc({ commitment: "confirmed" });
+    const event = await reportedEvent;
+    expect(event.reported.toString()).to.equal(reported.publicKey.toString());
+    expect(event.reasonCode).to.equal(2);
+
+    const [reportPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("report"), reporter.publicKey.toBuffer(), reported.publicKey.toBuffer()],
+      program.programId
+    );
+    const report = await program.account.profileReport.fetch(reportPDA);
+    expect(report.reasonCode).to.equal(2);
+    const [tallyPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("report_tally"), reported.publicKey.toBuffer()],
+      program.programId
+    );
+    expect((await program.account.reportTally.fetch(tallyPDA)).verifiedReports).to.equal(1);
+
+    // A repeat within the cooldown is dropped and the first report kept
+    const throttledEvent = awaitEvent("reportThrottledEvent");
+    await program.methods
+      .reportProfile(reported.publicKey, 3)
+      .accountsPartial({ reporter: reporter.publicKey })
+      .signers([reporter])
+      .rpc({ commitment: "confirmed" });
+    expect((await throttledEvent).reason).to.equal(2); // REPORT_THROTTLE_COOLDOWN
+    expect((await program.account.profileReport.fetch(reportPDA)).reasonCode).to.equal(2);
+    expect((await program.account.reportTally.fetch(tallyPDA)).verifiedReports).to.equal(1);
+  });
+
+  it("Should reject lifecycle operations on an open session", async () => {
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `StateCheck${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    await initNonceCounter(program, userA);
+
+    const matchSessionPDA = await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+
+    // The pair already has a session, whichever way round it is named
+    try {
+      await openMatchSession(program, userA, userB.publicKey, userA.publicKey);
+      expect.fail("Opened a second session for the same pair");
+    } catch (error) {
+      expect(error.message).to.match(/already in use/);
+    }
+
+    // Each of these needs a finalized session, so the open one must be refused
+    const participant = { user: userA.publicKey, matchPairSession: matchSessionPDA };
+    const transitions = [
+      () => program.methods.markConversationStarted().accountsPartial(participant).signers([userA]),
+      () => program.methods.unarchiveMatch().accountsPartial(participant).signers([userA]),
+      () => program.methods.archiveStaleMatch().accountsPartial({ matchPairSession: matchSessionPDA }),
+    ];
+    for (const transition of transitions) {
+      try {
+        await transition().rpc({ commitment: "confirmed" });
+        expect.fail("Lifecycle operation accepted an open session");
+      } catch (error) {
+        expect(error.message).to.match(/InvalidSessionState/);
+      }
+    }
+
+    // A freshly touched session is still inside its idle window
+    try {
+      await program.methods
+        .expireMatchSession()
+        .accountsPartial({ matchPairSession: matchSessionPDA })
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Expired a session that is not idle");
+    } catch (error) {
+      expect(error.message).to.match(/SessionNotExpired/);
+    }
+  });
+
+  it("Should only let a participant withdraw a like", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const compDefAccount = await ensureCompDef(
+      program, provider as anchor.AnchorProvider, owner, "withdraw_like", "initWithdrawLikeCompDef"
+    );
+
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Withdraw${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    await initNonceCounter(program, userA);
+    const matchSessionPDA = await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+    await initNonceCounter(program, outsider);
+
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    try {
+      await program.methods
+        .withdrawLike(
+          computationOffset,
+          Array.from(new Uint8Array(32)),
+          Array.from(new Uint8Array(32)),
+          Array.from(new Uint8Array(32)),
+          new anchor.BN(1)
+        )
+        .accountsPartial({
+          user: outsider.publicKey,
+          matchPairSession: matchSessionPDA,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          compDefAccount,
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([outsider])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Outsider withdrew a like from someone else's session");
+    } catch (error) {
+      expect(error.message).to.match(/UnauthorizedUser/);
+    }
+  });
+
+  it("Should only let a participant pass on a session", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const compDefAccount = await ensureCompDef(
+      program, provider as anchor.AnchorProvider, owner, "submit_pass", "initSubmitPassCompDef"
+    );
+
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Passer${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    await initNonceCounter(program, userA);
+    const matchSessionPDA = await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+    await initNonceCounter(program, outsider);
+
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    try {
+      await program.methods
+        .submitPass(
+          computationOffset,
+          Array.from(new Uint8Array(32)),
+          Array.from(new Uint8Array(32)),
+          Array.from(new Uint8Array(32)),
+          new anchor.BN(1)
+        )
+        .accountsPartial({
+          user: outsider.publicKey,
+          matchPairSession: matchSessionPDA,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          compDefAccount,
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([outsider])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Outsider passed on someone else's session");
+    } catch (error) {
+      expect(error.message).to.match(/UnauthorizedUser/);
+    }
+  });
+
+  it("Should only check a reciprocated session for a participant", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const mxePublicKey = await getMXEPublicKeyWithRetry(
+      provider as anchor.AnchorProvider,
+      program.programId
+    );
+    const compDefAccount = await ensureCompDef(
+      program, provider as anchor.AnchorProvider, owner, "check_mutual_match", "initCheckMutualMatchCompDef"
+    );
+
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Checker${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+      await stageMatchingCiphertexts(program, user, mxePublicKey, 30);
+    }
+    await initNonceCounter(program, userA);
+    const matchSessionPDA = await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+
+    const check = (payer: anchor.web3.Keypair) => {
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      return program.methods
+        .checkMutualMatch(computationOffset)
+        .accountsPartial({
+          payer: payer.publicKey,
+          matchPairSession: matchSessionPDA,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          compDefAccount,
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([payer])
+        .rpc({ commitment: "confirmed" });
+    };
+
+    try {
+      await check(outsider);
+      expect.fail("Outsider finalized someone else's session");
+    } catch (error) {
+      expect(error.message).to.match(/UnauthorizedUser/);
+    }
+
+    // Nobody has liked yet, so there is nothing to verify
+    const session = await program.account.matchPairSession.fetch(matchSessionPDA);
+    expect(session.isReadyForVerification).to.equal(false);
+    try {
+      await check(userA);
+      expect.fail("Checked a session before both users liked");
+    } catch (error) {
+      expect(error.message).to.match(/SessionNotReady/);
+    }
+  });
+
+  it("Should only close a finalized session for a participant", async () => {
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Closer${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    await initNonceCounter(program, userA);
+    const matchSessionPDA = await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+
+    const close = (user: anchor.web3.Keypair) =>
+      program.methods
+        .closeMatchSession()
+        .accountsPartial({ user: user.publicKey, matchPairSession: matchSessionPDA })
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await close(outsider);
+      expect.fail("Outsider closed someone else's session");
+    } catch (error) {
+      expect(error.message).to.match(/UnauthorizedUser/);
+    }
+
+    // An open session keeps its rent until it is finalized
+    try {
+      await close(userB);
+      expect.fail("Closed a session that is still open");
+    } catch (error) {
+      expect(error.message).to.match(/SessionNotFinalized/);
+    }
+    expect(await connection.getAccountInfo(matchSessionPDA)).to.not.equal(null);
+  });
+
+  it("Should enforce the target's like policy", async () => {
+    const [liker, target] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [liker, target].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `LikePolicy${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 31,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 31), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(31), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    await initNonceCounter(program, liker);
+
+    try {
+      await program.methods
+        .setLikePolicy(3, 0, 0)
+        .accountsPartial({ user: target.publicKey })
+        .signers([target])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Accepted an unknown like policy");
+    } catch (error) {
+      expect(error.message).to.match(/InvalidLikePolicy/);
+    }
+
+    // Only verified users may like the target, and the liker is not verified
+    await program.methods
+      .setLikePolicy(1, 0, 0)
+      .accountsPartial({ user: target.publicKey })
+      .signers([target])
+      .rpc({ commitment: "confirmed" });
+
+    const matchSessionPDA = await openMatchSession(program, liker, liker.publicKey, target.publicKey);
+
+    // The policy is checked before any ciphertext is read, so placeholders suffice
+    const placeholder = Array.from(new Uint8Array(32));
+    const computationOffset = new anchor.BN(randomBytes(8), "hex");
+    try {
+      await program.methods
+        .submitLike(
+          computationOffset,
+          placeholder,
+          placeholder,
+          placeholder,
+          placeholder,
+          placeholder,
+          placeholder,
+          new anchor.BN(deserializeLE(await nextNonce(program, liker.publicKey)).toString())
+        )
+        .accountsPartial({
+          matchPairSession: matchSessionPDA,
+          user: liker.publicKey,
+          nonceCounter: nonceCounterAddress(program, liker.publicKey),
+          likeQuota: likeQuotaAddress(program, liker.publicKey),
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("submit_like")).readUInt32LE()
+          ),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([liker])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Like bypassed the target's like policy");
+    } catch (error) {
+      expect(error.message).to.match(/LikePolicyViolation/);
+    }
+  });
+
+  it("Should let only the verification authority set a badge", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const username = "Verified1";
+    const profilePDA = await createUserProfile(program, user, {
+      username,
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 31,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 31), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(31), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    expect((await program.account.userProfile.fetch(profilePDA)).isVerified).to.be.false;
+
+    const setVerification = (authority: anchor.web3.Keypair, verified: boolean) =>
+      program.methods
+        .setVerification(verified)
+        .accountsPartial({ verificationAuthority: authority.publicKey, userProfile: profilePDA })
+        .signers([authority])
+        .rpc({ commitment: "confirmed" });
+
+    // The admin hands the role to a dedicated key
+    const verifier = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, verifier);
+    await program.methods
+      .setVerificationAuthority(verifier.publicKey)
+      .accounts({ admin: owner.publicKey })
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+
+    for (const outsider of [user, owner]) {
+      try {
+        await setVerification(outsider, true);
+        expect.fail("Someone other than the verification authority set a badge");
+      } catch (error) {
+        expect(error.message).to.match(/UnauthorizedUser/);
+      }
+    }
+
+    const verifiedEvent = awaitEvent("profileVerifiedEvent");
+    await setVerification(verifier, true);
+    const event = await verifiedEvent;
+    expect(event.user.toString()).to.equal(user.publicKey.toString());
+    expect(event.verified).to.be.true;
+    expect((await program.account.userProfile.fetch(profilePDA)).isVerified).to.be.true;
+
+    await setVerification(verifier, false);
+    expect((await program.account.userProfile.fetch(profilePDA)).isVerified).to.be.false;
+  });
+
+  it("Should number auto-initialized sessions from the global counter", async () => {
+    const users = [0, 1, 2].map(() => anchor.web3.Keypair.generate());
+    for (const [index, user] of users.entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `AutoSession${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 29,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 29), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(29), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+    }
+    const [opener, first, second] = users;
+    await initNonceCounter(program, opener);
+
+    const sessionIds: number[] = [];
+    for (const partner of [first, second]) {
+      const matchSessionPDA = pairSessionAddress(program, opener.publicKey, partner.publicKey);
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      await program.methods
+        .initMatchSessionAuto(
+          computationOffset,
+          opener.publicKey,
+          partner.publicKey,
+          new anchor.BN(deserializeLE(await nextNonce(program, opener.publicKey)).toString())
+        )
+        .accountsPartial({
+          matchPairSession: matchSessionPDA,
+          payer: opener.publicKey,
+          mxeAccount: getMXEAccAddress(program.programId),
+          mempoolAccount: getMempoolAccAddress(program.programId),
+          executingPool: getExecutingPoolAccAddress(program.programId),
+          computationAccount: getComputationAccAddress(program.programId, computationOffset),
+          compDefAccount: getCompDefAccAddress(
+            program.programId,
+            Buffer.from(getCompDefAccOffset("init_match_session")).readUInt32LE()
+          ),
+          clusterAccount: arciumEnv.arciumClusterPubkey,
+        })
+        .signers([opener])
+        .rpc({ commitment: "confirmed" });
+      sessionIds.push((await program.account.matchPairSession.fetch(matchSessionPDA)).sessionId.toNumber());
+    }
+
+    // Other tests may open auto sessions too, but never in between these two
+    expect(sessionIds[0]).to.be.greaterThan(0);
+    expect(sessionIds[1]).to.equal(sessionIds[0] + 1);
+  });
+
+  it("Should deactivate a batch of profiles from an admin sweep", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const profiles: PublicKey[] = [];
+    for (const index of [1, 2]) {
+      const user = anchor.web3.Keypair.generate();
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `Sweep${index}`;
+      profiles.push(await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hanoi",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      }));
+    }
+    const remainingAccounts = profiles.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }));
+
+    // Only the config admin may sweep
+    const outsider = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, outsider);
+    try {
+      await program.methods
+        .bulkDeactivate()
+        .accounts({ admin: outsider.publicKey })
+        .remainingAccounts(remainingAccounts)
+        .signers([outsider])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Non-admin swept profiles");
+    } catch (error) {
+      expect(error.message).to.match(/UnauthorizedUser/);
+    }
+
+    const sweptEvent = awaitEvent("bulkDeactivatedEvent");
+    await program.methods
+      .bulkDeactivate()
+      .accounts({ admin: owner.publicKey })
+      .remainingAccounts(remainingAccounts)
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+    expect((await sweptEvent).count).to.equal(2);
+    for (const profilePDA of profiles) {
+      expect((await program.account.userProfile.fetch(profilePDA)).isActive).to.be.false;
+    }
+
+    // Already-inactive profiles are skipped rather than failing the sweep
+    const resweptEvent = awaitEvent("bulkDeactivatedEvent");
+    await program.methods
+      .bulkDeactivate()
+      .accounts({ admin: owner.publicKey })
+      .remainingAccounts(remainingAccounts)
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+    expect((await resweptEvent).count).to.equal(0);
+  });
+
+  it("Should count a repeated account once when rebuilding stats", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    const username = "Rebuild1";
+    const profilePDA = await createUserProfile(program, user, {
+      username,
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+    const profileAccount = { pubkey: profilePDA, isWritable: false, isSigner: false };
+
+    const rebuiltEvent = awaitEvent("statsRebuiltEvent");
+    await program.methods
+      .rebuildStats(false)
+      .accounts({ admin: owner.publicKey })
+      .remainingAccounts([profileAccount, profileAccount, profileAccount])
+      .signers([owner])
+      .rpc({ commitment: "confirmed" });
+    expect((await rebuiltEvent).processed).to.equal(1);
+  });
+
+  it("Should hold a reserved username for its reserver only", async () => {
+    const [reserver, sniper] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    await ensureSufficientBalance(connection, reserver);
+    await ensureSufficientBalance(connection, sniper);
+
+    const reservation = await reserveUsername(program, reserver, "Reserved1");
+    const [sniperProfilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), sniper.publicKey.toBuffer()],
+      program.programId
+    );
+    const sniperPrivateKey = x25519.utils.randomSecretKey();
+
+    // The hash is case-insensitive, so a differently cased name hits the same reservation
+    try {
+      await program.methods
+        .createProfile({
+          username: "reserved1",
+          avatarUrl: "https://example.com/avatar.jpg",
+          age: 30,
+          locationCity: "Hanoi",
+          encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("reserved1", 30), sniperPrivateKey)),
+          encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), sniperPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+          encryptionPubkey: Array.from(x25519.getPublicKey(sniperPrivateKey)),
+          profileVersion: 1,
+          gender: 0,
+          orientation: 0,
+          photoUrls: [],
+          bio: "",
+        })
+        .accountsPartial({
+          userProfile: sniperProfilePDA,
+          usernameReservation: reservation,
+          usernameRegistry: usernameRegistryAddress(program, "reserved1"),
+          user: sniper.publicKey,
+        })
+        .signers([sniper])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Consumed another user's reservation");
+    } catch (error) {
+      expect(error.message).to.match(/InvalidUsernameReservation/);
+    }
+
+    // Only the reserver may release before expiry
+    try {
+      await program.methods
+        .releaseUsernameReservation()
+        .accountsPartial({ caller: sniper.publicKey, usernameReservation: reservation, reserver: reserver.publicKey })
+        .signers([sniper])
+        .rpc({ commitment: "confirmed" });
+      expect.fail("Released an active reservation");
+    } catch (error) {
+      expect(error.message).to.match(/UsernameReservationActive/);
+    }
+
+    await program.methods
+      .releaseUsernameReservation()
+      .accountsPartial({ caller: reserver.publicKey, usernameReservation: reservation, reserver: reserver.publicKey })
+      .signers([reserver])
+      .rpc({ commitment: "confirmed" });
+    expect(await connection.getAccountInfo(reservation)).to.be.null;
+  });
+
+  it("Should archive match history only past the inline cap", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const user = anchor.web3.Keypair.generate();
+    await ensureSufficientBalance(connection, user);
+    const userPrivateKey = x25519.utils.randomSecretKey();
+    await createUserProfile(program, user, {
+      username: "Archivist",
+      avatarUrl: "https://example.com/avatar.jpg",
+      age: 30,
+      locationCity: "Hanoi",
+      encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData("Archivist", 30), userPrivateKey)),
+      encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+      encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+      profileVersion: 1,
+      gender: 0,
+      orientation: 0,
+      photoUrls: [],
+      bio: "",
+    });
+
+    // An empty history is already under the cap, so nothing moves
+    await program.methods
+      .archiveMatchRecords()
+      .accounts({ user: user.publicKey })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+    const [userProfilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user_profile"), user.publicKey.toBuffer()],
+      program.programId
+    );
+    const profile = await program.account.userProfile.fetch(userProfilePDA);
+    expect(profile.archivedMatchRecords).to.equal(0);
+    expect(profile.matchHistory.length).to.equal(0);
+
+    // The inline cap must leave room for at least one record and fit the blob
+    for (const cap of [0, 7]) {
+      try {
+        await program.methods
+          .setInlineMatchCap(cap)
+          .accounts({ admin: owner.publicKey })
+          .signers([owner])
+          .rpc({ commitment: "confirmed" });
+        expect.fail(`Accepted inline match cap ${cap}`);
+      } catch (error) {
+        expect(error.message).to.match(/InvalidInlineMatchCap/);
+      }
+    }
+  });
+
+  // The mock backend applies results inline, so these only run against a
+  // program built with `--features mock-mpc` and MOCK_MPC=1 set
+  const itWithMockMpc = process.env.MOCK_MPC === "1" ? it : it.skip;
+
+  itWithMockMpc("Should write mock check and batch results to their accounts", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    const compDefs: Record<string, PublicKey> = {};
+    for (const [circuit, method] of [
+      ["init_match_session", "initInitMatchSessionCompDef"],
+      ["submit_like", "initSubmitLikeCompDef"],
+      ["check_mutual_match", "initCheckMutualMatchCompDef"],
+      ["calculate_compatibility_batch", "initCalculateCompatibilityBatchCompDef"],
+    ]) {
+      compDefs[circuit] = await ensureCompDef(program, provider as anchor.AnchorProvider, owner, circuit, method);
+    }
+    const arciumAccounts = (computationOffset: anchor.BN, circuit: string) => ({
+      mxeAccount: getMXEAccAddress(program.programId),
+      mempoolAccount: getMempoolAccAddress(program.programId),
+      executingPool: getExecutingPoolAccAddress(program.programId),
+      computationAccount: getComputationAccAddress(program.programId, computationOffset),
+      compDefAccount: compDefs[circuit],
+      clusterAccount: arciumEnv.arciumClusterPubkey,
+    });
+
+    const [userA, userB] = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    for (const [index, user] of [userA, userB].entries()) {
+      await ensureSufficientBalance(connection, user);
+      const userPrivateKey = x25519.utils.randomSecretKey();
+      const username = `MockMpc${index}`;
+      await createUserProfile(program, user, {
+        username,
+        avatarUrl: "https://example.com/avatar.jpg",
+        age: 30,
+        locationCity: "Hue",
+        encryptedPrivateData: Buffer.from(encryptSensitiveData(generatePrivateProfileData(username, 30), userPrivateKey)),
+        encryptedPreferences: Buffer.from(encryptSensitiveData(generateMatchingPreferences(30), userPrivateKey, ENCRYPTION_LIMITS.PREFERENCES_MAX_SIZE)),
+        encryptionPubkey: Array.from(x25519.getPublicKey(userPrivateKey)),
+        profileVersion: 1,
+        gender: 0,
+        orientation: 0,
+        photoUrls: [],
+        bio: "",
+      });
+      await initNonceCounter(program, user);
+      await stageMockMatchingCiphertexts(program, user, 30);
+    }
+    const matchSessionPDA = await openMatchSession(program, userA, userA.publicKey, userB.publicKey);
+
+    for (const [liker, target] of [[userA, userB], [userB, userA]]) {
+      const computationOffset = new anchor.BN(randomBytes(8), "hex");
+      await program.methods
+        .submitLike(
+          computationOffset,
+          mockCiphertext(userMpcId(liker.publicKey)),
+          mockCiphertext(userMpcId(target.publicKey)),
+          mockCiphertext(1),
+          mockCiphertext(Math.floor(Date.now() / 1000)),
+          mockCiphertext(80),
+          Array.from(new Uint8Array(32)),
+          new anchor.BN(deserializeLE(await nextNonce(program, liker.publicKey)).toString())
+        )
+        .accountsPartial({
+          matchPairSession: matchSessionPDA,
+          user: liker.publicKey,
+          nonceCounter: nonceCounterAddress(program, liker.publicKey),
+          likeQuota: likeQuotaAddress(program, liker.publicKey),
+          ...arciumAccounts(computationOffset, "submit_like"),
+        })
+        .signers([liker])
+        .rpc({ commitment: "confirmed" });
+    }
+
+    const [platformStatsPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("platform_stats")],
+      program.programId
+    );
+    const statsBefore = await program.account.platformStats.fetch(platformStatsPDA);
+    const checkOffset = new anchor.BN(randomBytes(8), "hex");
+    await program.methods
+      .checkMutualMatch(checkOffset)
+      .accountsPartial({
+        payer: userA.publicKey,
+        matchPairSession: matchSessionPDA,
+        ...arciumAccounts(checkOffset, "check_mutual_match"),
+      })
+      .signers([userA])
+      .rpc({ commitment: "confirmed" });
+
+    const statsAfter = await program.account.platformStats.fetch(platformStatsPDA);
+    expect(statsAfter.finalizedSessions.toNumber()).to.equal(statsBefore.finalizedSessions.toNumber() + 1);
+    expect(statsAfter.totalMatches.toNumber()).to.equal(statsBefore.totalMatches.toNumber() + 1);
+
+    // Each candidate is scored from their own staged MatchingData
+    const [candidateData] = PublicKey.findProgramAddressSync(
+      [Buffer.from("matching_ciphertexts"), userB.publicKey.toBuffer()],
+      program.programId
+    );
+    const batchOffset = new anchor.BN(randomBytes(8), "hex");
+    await program.methods
+      .calculateCompatibilityBatch(batchOffset, 0)
+      .accountsPartial({
+        payer: userA.publicKey,
+        ...arciumAccounts(batchOffset, "calculate_compatibility_batch"),
+      })
+      .remainingAccounts([{ pubkey: candidateData, isSigner: false, isWritable: false }])
+      .signers([userA])
+      .rpc({ commitment: "confirmed" });
+
+    const [batchPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("compat_batch"), userA.publicKey.toBuffer()],
+      program.programId
+    );
+    const batch = await program.account.compatibilityBatch.fetch(batchPDA);
+    expect(batch.candidateCount).to.equal(1);
+    expect(batch.scoredAt.toNumber()).to.be.greaterThan(0);
+    expect(batch.scores[0]).to.be.greaterThan(0);
+    expect(batch.scores.slice(1)).to.deep.equal([0, 0, 0, 0, 0, 0, 0]);
+  });
+
+  it("Should reject a wrong-type account in each callback", async () => {
+    const owner = readKeypairFromJson(`${os.homedir()}/.config/solana/id.json`);
+    // Program-owned, but a ProgramConfig rather than a MatchPairSession
+    const [configPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    );
+    const [platformStatsPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("platform_stats")],
+      program.programId
+    );
+    const compDefPDA = (circuit: string) => getCompDefAccAddress(
+      program.programId,
+      Buffer.from(getCompDefAccOffset(circuit)).readUInt32LE()
+    );
+    const common = {
+      arciumProgram: getArciumProgAddress(),
+      instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      matchPairSession: configPDA,
+      computationAccount: configPDA,
+    };
+    // Account validation runs before the output is inspected
+    const emptyOutput = { failure: {} } as any;
+
+    const callbacks = [
+      () => program.methods.initMatchSessionCallback(emptyOutput).accountsPartial({
+        ...common,
+        payer: owner.publicKey,
+        compDefAccount: compDefPDA("init_match_session"),
+      }),
+      () => program.methods.submitLikeCallback(emptyOutput).accountsPartial({
+        ...common,
+        user: owner.publicKey,
+        compDefAccount: compDefPDA("submit_like"),
+        likerProfile: configPDA,
+        targetProfile: configPDA,
+      }),
+      () => program.methods.checkMutualMatchCallback(emptyOutput).accountsPartial({
+        ...common,
+        payer: owner.publicKey,
+        compDefAccount: compDefPDA("check_mutual_match"),
+        platformStats: platformStatsPDA,
+        userAProfile: configPDA,
+        userBProfile: configPDA,
+      }),
+    ];
+
+    for (const callback of callbacks) {
+      try {
+        await callback().signers([owner]).rpc({ commitment: "confirmed" });
+        expect.fail("Callback accepted a spoofed session account");
+      } catch (error) {
+        expect(error.message).to.match(/AccountDiscriminatorMismatch|Error Number: 3002/);
+      }
+    }
+  });
 });
 
 